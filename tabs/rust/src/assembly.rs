@@ -0,0 +1,190 @@
+use crate::money::{Currency, Money, MoneyError};
+use crate::BomItem;
+
+/// One BoM line's place in the assembly tree, with its figures rolled up
+/// through the lines below it.
+#[derive(Debug, Clone)]
+pub struct AssemblyNode {
+    /// Index of the assembly this line is a part of.
+    pub parent: Option<usize>,
+    /// Indices of the lines that are directly parts of this one, in BoM order.
+    pub children: Vec<usize>,
+    /// 0 for top-level lines.
+    pub depth: u32,
+    /// Units needed for the whole estimate: the line's quantity times that
+    /// of every assembly above it.
+    pub total_quantity: u32,
+    /// The line total plus, for an assembly, its quantity times the
+    /// rolled-up totals of its parts.
+    pub rolled_up_total: Money,
+    /// The line's lead time plus that of its slowest part: how long an
+    /// assembly takes to be ready once everything in it is ordered.
+    pub rolled_up_lead_time_days: u32,
+}
+
+/// A BoM's lines arranged as assemblies and their parts.
+///
+/// The BoM is kept as an outline: a line is a part of the nearest line
+/// above it with a smaller [`BomItem::level`], and the quantity of a part
+/// is per one of its assembly. A BoM whose lines are all at level 0 is
+/// flat and every line is its own root.
+#[derive(Debug, Clone)]
+pub struct BomTree {
+    /// One node per BoM line, in BoM order.
+    pub nodes: Vec<AssemblyNode>,
+}
+
+impl BomTree {
+    pub fn build(items: &[BomItem]) -> Result<Self, MoneyError> {
+        let mut nodes: Vec<AssemblyNode> = Vec::with_capacity(items.len());
+        // Lines that can still take parts: each one's index and level.
+        let mut open: Vec<(usize, u32)> = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            while open.last().is_some_and(|&(_, level)| level >= item.level) {
+                open.pop();
+            }
+            let parent = open.last().map(|&(parent, _)| parent);
+            let (depth, multiplier) = match parent {
+                Some(parent) => {
+                    nodes[parent].children.push(index);
+                    (nodes[parent].depth + 1, nodes[parent].total_quantity)
+                }
+                None => (0, 1),
+            };
+            nodes.push(AssemblyNode {
+                parent,
+                children: Vec::new(),
+                depth,
+                total_quantity: item
+                    .quantity
+                    .checked_mul(multiplier)
+                    .ok_or(MoneyError::Overflow)?,
+                rolled_up_total: item.total_cost,
+                rolled_up_lead_time_days: item.lead_time_days,
+            });
+            open.push((index, item.level));
+        }
+
+        // Parts come after their assembly, so walking backwards finishes
+        // every part before the assembly it rolls up into.
+        for index in (0..items.len()).rev() {
+            let node = &nodes[index];
+            let mut parts = Money::zero(items[index].total_cost.currency());
+            let mut slowest = 0;
+            for &child in &node.children {
+                parts = parts.checked_add(nodes[child].rolled_up_total)?;
+                slowest = slowest.max(nodes[child].rolled_up_lead_time_days);
+            }
+            let node = &mut nodes[index];
+            node.rolled_up_total = node
+                .rolled_up_total
+                .checked_add(parts.checked_mul_quantity(items[index].quantity)?)?;
+            node.rolled_up_lead_time_days = node.rolled_up_lead_time_days.saturating_add(slowest);
+        }
+        Ok(Self { nodes })
+    }
+
+    /// Indices of the top-level lines, in BoM order.
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.parent.is_none())
+            .map(|(index, _)| index)
+    }
+
+    /// Whether the line at `index` has parts of its own.
+    pub fn is_assembly(&self, index: usize) -> bool {
+        self.nodes
+            .get(index)
+            .is_some_and(|node| !node.children.is_empty())
+    }
+
+    /// Whether no line has parts.
+    pub fn is_flat(&self) -> bool {
+        self.nodes.iter().all(|node| node.parent.is_none())
+    }
+
+    /// Sum of the rolled-up totals of the top-level lines.
+    pub fn total(&self, currency: Currency) -> Result<Money, MoneyError> {
+        Money::sum(
+            currency,
+            self.roots().map(|index| &self.nodes[index].rolled_up_total),
+        )
+    }
+
+    /// `items` with each line's quantity and total scaled to the whole
+    /// estimate, and its lead time extended by those of the assemblies above
+    /// it, since a part has to arrive before its assembly is built. Pricing,
+    /// ordering and planning work on these; a flat BoM comes back unchanged.
+    ///
+    /// `items` must be the BoM this tree was built from.
+    pub fn explode(&self, items: &[BomItem]) -> Result<Vec<BomItem>, MoneyError> {
+        let mut exploded: Vec<BomItem> = Vec::with_capacity(items.len());
+        for (item, node) in items.iter().zip(&self.nodes) {
+            let mut line = item.clone();
+            if let Some(parent) = node.parent {
+                let assembly = &exploded[parent];
+                line.quantity = node.total_quantity;
+                line.total_cost = item
+                    .total_cost
+                    .checked_mul_quantity(self.nodes[parent].total_quantity)?;
+                // The assembly's exploded lead time already includes everything above it.
+                line.lead_time_days = item.lead_time_days.saturating_add(assembly.lead_time_days);
+            }
+            exploded.push(line);
+        }
+        Ok(exploded)
+    }
+}
+
+/// Renumber levels so each line is exactly one deeper than its assembly,
+/// keeping the same tree. Returns the index and old level of every line
+/// that changed.
+pub(crate) fn normalize_levels(items: &mut [BomItem]) -> Vec<(usize, u32)> {
+    let mut depths: Vec<u32> = Vec::with_capacity(items.len());
+    let mut open: Vec<(usize, u32)> = Vec::new();
+    let mut changed = Vec::new();
+    for (index, item) in items.iter_mut().enumerate() {
+        while open.last().is_some_and(|&(_, level)| level >= item.level) {
+            open.pop();
+        }
+        let depth = open.last().map_or(0, |&(parent, _)| depths[parent] + 1);
+        open.push((index, item.level));
+        depths.push(depth);
+        if item.level != depth {
+            changed.push((index, item.level));
+            item.level = depth;
+        }
+    }
+    changed
+}
+
+/// Levels for the lines at `selected` when they are written out without the
+/// rest of the BoM, in BoM order. Each line goes under its nearest selected
+/// assembly, so a part whose assembly is left out moves up to the level of
+/// the first one kept above it, and the levels still describe a tree.
+pub(crate) fn levels_of_selection(items: &[BomItem], selected: &[usize]) -> Vec<u32> {
+    let mut keep = vec![false; items.len()];
+    for &index in selected {
+        keep[index] = true;
+    }
+    let mut levels = Vec::with_capacity(selected.len());
+    // Lines that can still take parts: each one's level and, if it is
+    // selected, the level it is written at.
+    let mut open: Vec<(u32, Option<u32>)> = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        while open.last().is_some_and(|&(level, _)| level >= item.level) {
+            open.pop();
+        }
+        let written = keep[index].then(|| {
+            open.iter()
+                .rev()
+                .find_map(|&(_, written)| written)
+                .map_or(0, |level| level + 1)
+        });
+        levels.extend(written);
+        open.push((item.level, written));
+    }
+    levels
+}
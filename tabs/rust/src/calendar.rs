@@ -0,0 +1,338 @@
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::Path;
+
+/// Longest lead time, in working days, accepted when importing or editing
+/// lines and supplier catalogues: well over any real lead time, and short
+/// enough that planning dates stay in range.
+pub const MAX_LEAD_TIME_DAYS: u32 = 2000;
+
+/// Why a lead time over [`MAX_LEAD_TIME_DAYS`] is rejected.
+pub(crate) const LEAD_TIME_TOO_LONG: &str = "longer than 2000 working days";
+
+/// Which days work (ordering, delivery, installation) can happen on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkingCalendar {
+    /// Days of the week nobody works; Saturday and Sunday by default.
+    pub weekend: Vec<Weekday>,
+    /// Non-working dates on top of the weekend.
+    #[serde(default)]
+    pub holidays: BTreeSet<NaiveDate>,
+}
+
+impl Default for WorkingCalendar {
+    fn default() -> Self {
+        Self {
+            weekend: vec![Weekday::Sat, Weekday::Sun],
+            holidays: BTreeSet::new(),
+        }
+    }
+}
+
+impl WorkingCalendar {
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        !self.weekend.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// Make `day` a weekend day, or a working day again.
+    pub fn set_weekend_day(&mut self, day: Weekday, off: bool) {
+        self.weekend.retain(|d| *d != day);
+        if off {
+            self.weekend.push(day);
+            self.weekend.sort_by_key(Weekday::num_days_from_monday);
+        }
+    }
+
+    /// Add holidays, returning how many were not already listed.
+    pub fn add_holidays(&mut self, dates: impl IntoIterator<Item = NaiveDate>) -> usize {
+        dates
+            .into_iter()
+            .filter(|date| self.holidays.insert(*date))
+            .count()
+    }
+
+    /// The date `days` working days before `date`.
+    ///
+    /// Zero returns `date` itself, moved back to a working day if it is not one.
+    /// A calendar with no working days at all returns `date` unchanged, and
+    /// counting stops at the earliest date chrono can represent.
+    pub fn subtract_working_days(&self, date: NaiveDate, days: u32) -> NaiveDate {
+        let per_week = self.working_days_per_week();
+        if per_week == 0 {
+            return date;
+        }
+        let mut current = self.previous_working_day(date);
+        let mut left = days;
+        while left > 0 {
+            // Before the first holiday every week has the same working days,
+            // so whole weeks can be skipped at once.
+            if left > per_week && self.holidays.first().is_none_or(|first| current < *first) {
+                let weeks = (left - 1) / per_week;
+                match current.checked_sub_days(Days::new(u64::from(weeks) * 7)) {
+                    Some(earlier) => current = earlier,
+                    None => return NaiveDate::MIN,
+                }
+                left -= weeks * per_week;
+                continue;
+            }
+            match current.pred_opt() {
+                Some(earlier) => current = self.previous_working_day(earlier),
+                None => return current,
+            }
+            left -= 1;
+        }
+        current
+    }
+
+    /// The date `days` working days after `date`; the mirror of
+    /// [`WorkingCalendar::subtract_working_days`].
+    pub fn add_working_days(&self, date: NaiveDate, days: u32) -> NaiveDate {
+        let per_week = self.working_days_per_week();
+        if per_week == 0 {
+            return date;
+        }
+        let mut current = self.next_working_day(date);
+        let mut left = days;
+        while left > 0 {
+            if left > per_week && self.holidays.last().is_none_or(|last| current > *last) {
+                let weeks = (left - 1) / per_week;
+                match current.checked_add_days(Days::new(u64::from(weeks) * 7)) {
+                    Some(later) => current = later,
+                    None => return NaiveDate::MAX,
+                }
+                left -= weeks * per_week;
+                continue;
+            }
+            match current.succ_opt() {
+                Some(later) => current = self.next_working_day(later),
+                None => return current,
+            }
+            left -= 1;
+        }
+        current
+    }
+
+    fn working_days_per_week(&self) -> u32 {
+        let off = (0..7)
+            .filter(|day| {
+                self.weekend
+                    .iter()
+                    .any(|weekend| weekend.num_days_from_monday() == *day)
+            })
+            .count();
+        7 - off as u32
+    }
+
+    /// Working days from `from` up to `to`, counting `to` but not `from`;
+    /// negative when `to` is earlier.
+    pub fn working_days_between(&self, from: NaiveDate, to: NaiveDate) -> i64 {
+        let (start, end, sign) = if to >= from {
+            (from, to, 1)
+        } else {
+            (to, from, -1)
+        };
+        let count = start
+            .iter_days()
+            .skip(1)
+            .take_while(|day| *day <= end)
+            .filter(|day| self.is_working_day(*day))
+            .count();
+        sign * i64::try_from(count).unwrap_or(i64::MAX)
+    }
+
+    /// `date`, or the closest working day after it.
+    pub fn next_working_day(&self, date: NaiveDate) -> NaiveDate {
+        if self.working_days_per_week() == 0 {
+            return date;
+        }
+        let mut current = date;
+        while !self.is_working_day(current) {
+            match current.succ_opt() {
+                Some(later) => current = later,
+                None => break,
+            }
+        }
+        current
+    }
+
+    /// `date`, or the closest working day before it.
+    pub fn previous_working_day(&self, date: NaiveDate) -> NaiveDate {
+        if self.working_days_per_week() == 0 {
+            return date;
+        }
+        let mut current = date;
+        while !self.is_working_day(current) {
+            match current.pred_opt() {
+                Some(earlier) => current = earlier,
+                None => break,
+            }
+        }
+        current
+    }
+}
+
+/// Error from reading a holiday file.
+#[derive(Debug)]
+pub enum CalendarError {
+    Io(std::io::Error),
+    Csv(csv::Error),
+    /// A line that should hold a date does not.
+    InvalidDate {
+        line: u64,
+        value: String,
+    },
+}
+
+impl fmt::Display for CalendarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalendarError::Io(err) => write!(f, "could not read holiday file: {err}"),
+            CalendarError::Csv(err) => write!(f, "could not parse holiday file: {err}"),
+            CalendarError::InvalidDate { line, value } => {
+                write!(f, "line {line}: \"{value}\" is not a date")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CalendarError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CalendarError::Io(err) => Some(err),
+            CalendarError::Csv(err) => Some(err),
+            CalendarError::InvalidDate { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for CalendarError {
+    fn from(err: std::io::Error) -> Self {
+        CalendarError::Io(err)
+    }
+}
+
+impl From<csv::Error> for CalendarError {
+    fn from(err: csv::Error) -> Self {
+        CalendarError::Csv(err)
+    }
+}
+
+/// Read holiday dates from an iCalendar (`.ics`) or CSV file, chosen by extension.
+pub fn load_holidays(path: &Path) -> Result<Vec<NaiveDate>, CalendarError> {
+    let is_ical = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ics"));
+    if is_ical {
+        parse_ical_holidays(&std::fs::read_to_string(path)?)
+    } else {
+        parse_csv_holidays(std::fs::File::open(path)?)
+    }
+}
+
+/// Holidays from a CSV whose first column is a date (`YYYY-MM-DD` or `DD/MM/YYYY`).
+///
+/// Further columns (e.g. the holiday's name) are ignored, as is a header row.
+pub fn parse_csv_holidays<R: std::io::Read>(reader: R) -> Result<Vec<NaiveDate>, CalendarError> {
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+
+    let mut dates = Vec::new();
+    for (index, record) in csv_reader.records().enumerate() {
+        let record = record?;
+        let value = record.get(0).unwrap_or_default();
+        if value.is_empty() || value.starts_with('#') {
+            continue;
+        }
+        match parse_holiday_date(value) {
+            Some(date) => dates.push(date),
+            // A first row that is not a date is a header.
+            None if index == 0 => {}
+            None => {
+                return Err(CalendarError::InvalidDate {
+                    line: record.position().map_or(0, |pos| pos.line()),
+                    value: value.to_string(),
+                })
+            }
+        }
+    }
+    Ok(dates)
+}
+
+/// Holidays from the `VEVENT`s of an iCalendar file.
+///
+/// Each event's `DTSTART` to `DTEND` (exclusive, as iCalendar defines for
+/// all-day events) becomes holidays. Recurrence rules are not expanded;
+/// published holiday calendars list every occurrence explicitly.
+pub fn parse_ical_holidays(source: &str) -> Result<Vec<NaiveDate>, CalendarError> {
+    let mut dates = Vec::new();
+    let mut event: Option<(Option<NaiveDate>, Option<NaiveDate>)> = None;
+
+    for (line_no, line) in unfold_ical_lines(source) {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Property parameters such as `;VALUE=DATE` follow the name.
+        let name = name
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+        match (name.as_str(), value.trim()) {
+            ("BEGIN", "VEVENT") => event = Some((None, None)),
+            ("END", "VEVENT") => {
+                if let Some((Some(start), end)) = event.take() {
+                    let end = end
+                        .filter(|end| *end > start)
+                        .unwrap_or(start + Days::new(1));
+                    dates.extend(start.iter_days().take_while(|day| *day < end));
+                }
+            }
+            ("DTSTART" | "DTEND", value) => {
+                let Some((start, end)) = event.as_mut() else {
+                    continue;
+                };
+                let date = parse_ical_date(value).ok_or_else(|| CalendarError::InvalidDate {
+                    line: line_no,
+                    value: value.to_string(),
+                })?;
+                if name == "DTSTART" {
+                    *start = Some(date);
+                } else {
+                    *end = Some(date);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(dates)
+}
+
+/// Join folded iCalendar lines (continuations start with a space or tab),
+/// keeping the line number each logical line starts on.
+fn unfold_ical_lines(source: &str) -> Vec<(u64, String)> {
+    let mut lines: Vec<(u64, String)> = Vec::new();
+    for (index, raw) in source.lines().enumerate() {
+        let raw = raw.trim_end_matches('\r');
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some((_, previous))) => previous.push_str(continuation),
+            _ => lines.push((index as u64 + 1, raw.to_string())),
+        }
+    }
+    lines
+}
+
+/// `20261225` or `20261225T090000Z`; only the date part is used.
+fn parse_ical_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()
+}
+
+fn parse_holiday_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%d/%m/%Y"))
+        .ok()
+}
@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A `BomItem` field that can be filled from a CSV column.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BomField {
+    Name,
+    Quantity,
+    UnitCost,
+    TotalCost,
+    LeadTimeDays,
+    MinQuantity,
+    Category,
+    PackSize,
+    /// Depth in the assembly tree.
+    Level,
+    Supplier,
+    /// The supplier's code for the item (SKU).
+    PartNumber,
+}
+
+impl BomField {
+    pub const ALL: [BomField; 11] = [
+        BomField::Name,
+        BomField::Quantity,
+        BomField::UnitCost,
+        BomField::TotalCost,
+        BomField::LeadTimeDays,
+        BomField::MinQuantity,
+        BomField::Category,
+        BomField::PackSize,
+        BomField::Level,
+        BomField::Supplier,
+        BomField::PartNumber,
+    ];
+
+    /// Whether a BoM file must provide a column for this field.
+    pub fn is_required(&self) -> bool {
+        !matches!(
+            self,
+            BomField::Category
+                | BomField::PackSize
+                | BomField::Level
+                | BomField::Supplier
+                | BomField::PartNumber
+        )
+    }
+}
+
+impl fmt::Display for BomField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            BomField::Name => "name",
+            BomField::Quantity => "quantity",
+            BomField::UnitCost => "unit cost",
+            BomField::TotalCost => "line total",
+            BomField::LeadTimeDays => "lead time",
+            BomField::MinQuantity => "minimum quantity",
+            BomField::Category => "category",
+            BomField::PackSize => "pack size",
+            BomField::Level => "level",
+            BomField::Supplier => "supplier",
+            BomField::PartNumber => "part number",
+        };
+        f.write_str(label)
+    }
+}
+
+/// Maps CSV header names onto `BomItem` fields.
+///
+/// Headers are compared after normalisation (see [`normalize_header`]), so
+/// "Min QTY.", "min_qty" and "MIN QTY" all match the alias "min qty".
+/// Columns may appear in any order; columns that match no alias are kept
+/// as extra attributes on each imported item.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ColumnMapping {
+    pub aliases: Vec<(BomField, Vec<String>)>,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+impl ColumnMapping {
+    /// Aliases covering the backend's own column names and common supplier exports.
+    pub fn standard() -> Self {
+        let aliases = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        Self {
+            aliases: vec![
+                (
+                    BomField::Name,
+                    aliases(&["name", "material", "item", "description", "part"]),
+                ),
+                (
+                    BomField::Quantity,
+                    aliases(&["quantity", "qty", "count", "units"]),
+                ),
+                (
+                    BomField::UnitCost,
+                    aliases(&["unit cost", "unit price", "price", "cost each"]),
+                ),
+                (
+                    BomField::TotalCost,
+                    aliases(&["total cost", "line total", "total", "extended cost"]),
+                ),
+                (
+                    BomField::LeadTimeDays,
+                    aliases(&["lead time days", "lead time", "lead days", "lead"]),
+                ),
+                (
+                    BomField::MinQuantity,
+                    aliases(&["min quantity", "min qty", "moq", "minimum order quantity"]),
+                ),
+                (
+                    BomField::Category,
+                    aliases(&["category", "type", "tax category", "group"]),
+                ),
+                (
+                    BomField::PackSize,
+                    aliases(&["pack size", "pack qty", "pack quantity", "order multiple"]),
+                ),
+                (
+                    BomField::Level,
+                    aliases(&["level", "bom level", "assembly level", "indent level"]),
+                ),
+                (
+                    BomField::Supplier,
+                    aliases(&["supplier", "vendor", "supplier name"]),
+                ),
+                (
+                    BomField::PartNumber,
+                    aliases(&[
+                        "part number",
+                        "part no",
+                        "sku",
+                        "supplier part number",
+                        "mpn",
+                    ]),
+                ),
+            ],
+        }
+    }
+
+    /// Add an extra header alias for `field`.
+    pub fn with_alias(mut self, field: BomField, alias: impl Into<String>) -> Self {
+        let alias = alias.into();
+        match self.aliases.iter_mut().find(|(f, _)| *f == field) {
+            Some((_, names)) => names.push(alias),
+            None => self.aliases.push((field, vec![alias])),
+        }
+        self
+    }
+
+    /// The field a header maps to, if any.
+    pub fn field_for(&self, header: &str) -> Option<BomField> {
+        let header = normalize_header(header);
+        self.aliases.iter().find_map(|(field, names)| {
+            names
+                .iter()
+                .any(|name| normalize_header(name) == header)
+                .then_some(*field)
+        })
+    }
+
+    /// Resolve a header row into column positions.
+    pub fn resolve(&self, headers: &[String]) -> ResolvedColumns {
+        let mut resolved = ResolvedColumns::default();
+        for (index, header) in headers.iter().enumerate() {
+            match self.field_for(header) {
+                Some(field) if resolved.position(field).is_none() => {
+                    resolved.fields.push((field, index));
+                }
+                Some(field) => resolved.duplicates.push((field, index)),
+                None => resolved.extra.push(index),
+            }
+        }
+        resolved
+    }
+}
+
+/// Column positions found for one particular header row.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolvedColumns {
+    /// Field and the zero-based column index it is read from.
+    pub fields: Vec<(BomField, usize)>,
+    /// Columns that matched a field which was already mapped; these are kept as extras.
+    pub duplicates: Vec<(BomField, usize)>,
+    /// Columns that matched no field.
+    pub extra: Vec<usize>,
+}
+
+impl ResolvedColumns {
+    pub fn position(&self, field: BomField) -> Option<usize> {
+        self.fields
+            .iter()
+            .find(|(f, _)| *f == field)
+            .map(|(_, index)| *index)
+    }
+
+    /// Required fields that no column maps to.
+    pub fn missing(&self) -> Vec<BomField> {
+        BomField::ALL
+            .into_iter()
+            .filter(|field| field.is_required() && self.position(*field).is_none())
+            .collect()
+    }
+}
+
+/// Lowercase a header and drop everything except letters and digits.
+pub fn normalize_header(header: &str) -> String {
+    header
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
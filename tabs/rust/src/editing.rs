@@ -0,0 +1,166 @@
+use crate::calendar::{LEAD_TIME_TOO_LONG, MAX_LEAD_TIME_DAYS};
+use crate::columns::BomField;
+use crate::import::ExpectedType;
+use crate::money::{Currency, Money, MoneyError, RoundingMode, DISPLAY_DECIMALS};
+use crate::BomItem;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Error from editing BoM lines in place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BomEditError {
+    NoSuchLine(usize),
+    /// The field is derived from others and cannot be typed in.
+    NotEditable(BomField),
+    /// The text entered for a cell does not hold a valid value.
+    InvalidValue {
+        field: BomField,
+        value: String,
+        expected: ExpectedType,
+        reason: &'static str,
+    },
+    Money(MoneyError),
+}
+
+impl fmt::Display for BomEditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BomEditError::NoSuchLine(index) => write!(f, "no BoM line at {index}"),
+            BomEditError::NotEditable(field) => write!(f, "the {field} cannot be edited"),
+            BomEditError::InvalidValue {
+                field,
+                value,
+                expected,
+                reason,
+            } => write!(
+                f,
+                "{field}: {reason} (got \"{value}\", expected {expected})"
+            ),
+            BomEditError::Money(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for BomEditError {}
+
+impl From<MoneyError> for BomEditError {
+    fn from(err: MoneyError) -> Self {
+        BomEditError::Money(err)
+    }
+}
+
+impl BomItem {
+    /// An empty line: no name yet, nothing ordered, costs in `currency`.
+    pub fn blank(currency: Currency) -> Self {
+        Self {
+            name: String::new(),
+            quantity: 0,
+            unit_cost: Money::zero(currency),
+            total_cost: Money::zero(currency),
+            file_total: None,
+            lead_time_days: 0,
+            min_quantity: 0,
+            category: None,
+            pack_size: None,
+            extra: BTreeMap::new(),
+            level: 0,
+            supplier: None,
+            part_number: None,
+        }
+    }
+
+    /// The value of `field` as it would be typed into an editable cell.
+    pub fn field_text(&self, field: BomField) -> String {
+        match field {
+            BomField::Name => self.name.clone(),
+            BomField::Quantity => self.quantity.to_string(),
+            BomField::UnitCost => self.unit_cost.amount_string(),
+            BomField::TotalCost => self.total_cost.amount_string(),
+            BomField::LeadTimeDays => self.lead_time_days.to_string(),
+            BomField::MinQuantity => self.min_quantity.to_string(),
+            BomField::Category => self.category.clone().unwrap_or_default(),
+            BomField::PackSize => self
+                .pack_size
+                .map(|size| size.to_string())
+                .unwrap_or_default(),
+            BomField::Level => self.level.to_string(),
+            BomField::Supplier => self.supplier.clone().unwrap_or_default(),
+            BomField::PartNumber => self.part_number.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// A copy of `item` with `field` set from user-entered text.
+///
+/// Changing the quantity or unit cost recomputes the line total as
+/// quantity × unit cost, rounded to two places; other edits leave it alone.
+///
+/// Values are checked with the same rules as the CSV importer, so a line
+/// that can be edited can also be exported and read back.
+pub fn edit_bom_item(
+    item: &BomItem,
+    field: BomField,
+    value: &str,
+    rounding: RoundingMode,
+) -> Result<BomItem, BomEditError> {
+    let raw = value.trim();
+    let invalid = |expected, reason| BomEditError::InvalidValue {
+        field,
+        value: value.to_string(),
+        expected,
+        reason,
+    };
+    let whole_number = || {
+        raw.parse::<u32>()
+            .map_err(|_| invalid(ExpectedType::WholeNumber, "not a whole number"))
+    };
+
+    let mut edited = item.clone();
+    match field {
+        BomField::Name if raw.is_empty() => {
+            return Err(invalid(ExpectedType::Text, "value is empty"))
+        }
+        BomField::Name => edited.name = raw.to_string(),
+        BomField::Quantity => edited.quantity = whole_number()?,
+        BomField::UnitCost => {
+            edited.unit_cost = match Money::parse(raw, item.unit_cost.currency()) {
+                Ok(cost) => cost,
+                Err(MoneyError::TooPrecise(_)) => {
+                    return Err(invalid(ExpectedType::Money, "too many decimal places"))
+                }
+                Err(_) => return Err(invalid(ExpectedType::Money, "not an amount")),
+            }
+        }
+        BomField::LeadTimeDays => match whole_number()? {
+            days if days > MAX_LEAD_TIME_DAYS => {
+                return Err(invalid(ExpectedType::WholeNumber, LEAD_TIME_TOO_LONG))
+            }
+            days => edited.lead_time_days = days,
+        },
+        BomField::MinQuantity => edited.min_quantity = whole_number()?,
+        BomField::Category => edited.category = (!raw.is_empty()).then(|| raw.to_string()),
+        BomField::PackSize if raw.is_empty() => edited.pack_size = None,
+        BomField::PackSize => match whole_number()? {
+            0 => {
+                return Err(invalid(
+                    ExpectedType::WholeNumber,
+                    "pack size must be at least 1",
+                ))
+            }
+            size => edited.pack_size = Some(size),
+        },
+        BomField::Level => edited.level = whole_number()?,
+        BomField::Supplier => edited.supplier = (!raw.is_empty()).then(|| raw.to_string()),
+        BomField::PartNumber => edited.part_number = (!raw.is_empty()).then(|| raw.to_string()),
+        BomField::TotalCost => return Err(BomEditError::NotEditable(field)),
+    }
+
+    if matches!(field, BomField::Quantity | BomField::UnitCost) {
+        edited.file_total = None;
+        edited.total_cost = edited
+            .unit_cost
+            .checked_mul_quantity(edited.quantity)?
+            .round(DISPLAY_DECIMALS, rounding);
+    }
+    Ok(edited)
+}
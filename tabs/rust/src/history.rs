@@ -0,0 +1,243 @@
+use crate::calendar::WorkingCalendar;
+use crate::columns::BomField;
+use crate::pricing::{Adjustment, Rate};
+use crate::reconcile::ReconcilePolicy;
+use crate::revisions::QuoteRevision;
+use crate::suppliers::SupplierCatalogue;
+use crate::BomItem;
+use chrono::NaiveDate;
+use std::fmt;
+
+/// Undo steps kept before the oldest are discarded.
+pub const DEFAULT_HISTORY_DEPTH: usize = 100;
+
+/// One reversible change to [`crate::AppState`], holding the values on
+/// both sides so it can be applied in either direction.
+#[derive(Debug, Clone)]
+pub enum Change {
+    /// A BoM line edited in place.
+    BomLine {
+        index: usize,
+        field: BomField,
+        before: Box<BomItem>,
+        after: Box<BomItem>,
+    },
+    BomLineInserted {
+        index: usize,
+        item: BomItem,
+    },
+    BomLineRemoved {
+        index: usize,
+        item: BomItem,
+    },
+    /// The pricing pipeline before and after an adjustment was added,
+    /// removed, moved or changed.
+    Adjustments {
+        before: Vec<Adjustment>,
+        after: Vec<Adjustment>,
+    },
+    InstallationDate {
+        before: Option<NaiveDate>,
+        after: Option<NaiveDate>,
+    },
+    /// Our own calendar (`supplier: None`) or a supplier's; `before` is
+    /// `None` when the supplier had no calendar yet.
+    Calendar {
+        supplier: Option<String>,
+        before: Option<WorkingCalendar>,
+        after: WorkingCalendar,
+    },
+    SurplusThreshold {
+        before: Rate,
+        after: Rate,
+    },
+    /// The supplier catalogue before and after suppliers were imported.
+    Suppliers {
+        before: SupplierCatalogue,
+        after: SupplierCatalogue,
+    },
+    ReconcilePolicy {
+        before: ReconcilePolicy,
+        after: ReconcilePolicy,
+    },
+    RevisionIssued {
+        revision: QuoteRevision,
+    },
+}
+
+impl Change {
+    /// Whether undoing or redoing this change alters the BoM lines.
+    pub fn touches_bom(&self) -> bool {
+        matches!(
+            self,
+            Change::BomLine { .. } | Change::BomLineInserted { .. } | Change::BomLineRemoved { .. }
+        )
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::BomLine { index, field, .. } => write!(f, "edit {field} on line {}", index + 1),
+            Change::BomLineInserted { index, .. } => write!(f, "add line {}", index + 1),
+            Change::BomLineRemoved { index, .. } => write!(f, "remove line {}", index + 1),
+            Change::Adjustments { .. } => f.write_str("change pricing"),
+            Change::InstallationDate { .. } => f.write_str("set installation date"),
+            Change::Calendar {
+                supplier: Some(supplier),
+                ..
+            } => write!(f, "change {supplier}'s calendar"),
+            Change::Calendar { supplier: None, .. } => f.write_str("change calendar"),
+            Change::SurplusThreshold { .. } => f.write_str("set surplus threshold"),
+            Change::Suppliers { .. } => f.write_str("import supplier catalogue"),
+            Change::ReconcilePolicy { .. } => f.write_str("change total reconciliation"),
+            Change::RevisionIssued { revision } => {
+                write!(f, "issue quote revision {}", revision.number)
+            }
+        }
+    }
+}
+
+/// Changes undone or redone together.
+#[derive(Debug, Clone)]
+pub struct HistoryStep {
+    pub label: String,
+    /// In the order they were made; undone in reverse.
+    pub changes: Vec<Change>,
+}
+
+/// Undo and redo stacks of [`HistoryStep`]s.
+///
+/// Every change recorded outside a group is its own step. Between
+/// [`History::begin_group`] and the matching [`History::end_group`],
+/// changes collect into one step instead; groups may nest, and only the
+/// outermost label is kept. Recording a new change clears the redo stack.
+#[derive(Debug, Clone)]
+pub struct History {
+    undo: Vec<HistoryStep>,
+    redo: Vec<HistoryStep>,
+    /// Step being collected and how many groups are open.
+    group: Option<(HistoryStep, usize)>,
+    depth: usize,
+    /// Length of `undo` when the state was last saved; `None` once that
+    /// point can no longer be reached by undoing or redoing.
+    saved_at: Option<usize>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::with_depth(DEFAULT_HISTORY_DEPTH)
+    }
+}
+
+impl History {
+    pub fn with_depth(depth: usize) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            group: None,
+            depth: depth.max(1),
+            saved_at: Some(0),
+        }
+    }
+
+    pub fn record(&mut self, change: Change) {
+        if self.saved_at.is_some_and(|saved| saved > self.undo.len()) {
+            // The saved state was an undone step that is about to be discarded.
+            self.saved_at = None;
+        }
+        self.redo.clear();
+        match &mut self.group {
+            Some((step, _)) => step.changes.push(change),
+            None => self.push_undo(HistoryStep {
+                label: change.to_string(),
+                changes: vec![change],
+            }),
+        }
+    }
+
+    pub fn begin_group(&mut self, label: impl Into<String>) {
+        match &mut self.group {
+            Some((_, open)) => *open += 1,
+            None => {
+                self.group = Some((
+                    HistoryStep {
+                        label: label.into(),
+                        changes: Vec::new(),
+                    },
+                    1,
+                ))
+            }
+        }
+    }
+
+    /// Close the innermost group; closing the outermost one makes its
+    /// changes a single undo step. Groups with no changes leave no step.
+    pub fn end_group(&mut self) {
+        match self.group.take() {
+            Some((step, open)) if open > 1 => self.group = Some((step, open - 1)),
+            Some((step, _)) if !step.changes.is_empty() => self.push_undo(step),
+            _ => {}
+        }
+    }
+
+    /// Remove the latest step so it can be reverted; it moves to the redo stack.
+    pub fn take_undo(&mut self) -> Option<HistoryStep> {
+        let step = self.undo.pop()?;
+        self.redo.push(step.clone());
+        Some(step)
+    }
+
+    /// Remove the latest undone step so it can be re-applied; it moves back to the undo stack.
+    pub fn take_redo(&mut self) -> Option<HistoryStep> {
+        let step = self.redo.pop()?;
+        self.undo.push(step.clone());
+        Some(step)
+    }
+
+    /// Label of the step [`History::take_undo`] would return.
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.last().map(|step| step.label.as_str())
+    }
+
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|step| step.label.as_str())
+    }
+
+    /// Whether anything has changed since [`History::mark_saved`] (or since
+    /// the history was created or cleared).
+    pub fn is_modified(&self) -> bool {
+        self.saved_at != Some(self.undo.len())
+            || self
+                .group
+                .as_ref()
+                .is_some_and(|(step, _)| !step.changes.is_empty())
+    }
+
+    /// Note a change that cannot be undone: the state no longer matches the
+    /// last save, whatever is undone or redone from here.
+    pub fn mark_unsaved(&mut self) {
+        self.saved_at = None;
+    }
+
+    /// Treat the current state as saved.
+    pub fn mark_saved(&mut self) {
+        self.saved_at = Some(self.undo.len());
+    }
+
+    /// Forget all steps; the current state counts as saved.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.group = None;
+        self.saved_at = Some(0);
+    }
+
+    fn push_undo(&mut self, step: HistoryStep) {
+        self.undo.push(step);
+        if self.undo.len() > self.depth {
+            self.undo.remove(0);
+            self.saved_at = self.saved_at.and_then(|saved| saved.checked_sub(1));
+        }
+    }
+}
@@ -0,0 +1,674 @@
+use crate::assembly::normalize_levels;
+use crate::calendar::{LEAD_TIME_TOO_LONG, MAX_LEAD_TIME_DAYS};
+use crate::columns::{normalize_header, BomField, ColumnMapping, ResolvedColumns};
+use crate::money::{Currency, Money, MoneyError};
+use crate::BomItem;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::Read;
+use std::path::Path;
+
+/// How serious an import diagnostic is.
+///
+/// Ordered so that the most severe entry in a report can be found with `max`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        f.write_str(label)
+    }
+}
+
+/// The kind of value a BoM column is expected to hold.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ExpectedType {
+    Text,
+    WholeNumber,
+    Decimal,
+    Money,
+}
+
+impl fmt::Display for ExpectedType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            ExpectedType::Text => "text",
+            ExpectedType::WholeNumber => "whole number",
+            ExpectedType::Decimal => "decimal number",
+            ExpectedType::Money => "amount with at most 4 decimal places",
+        };
+        f.write_str(label)
+    }
+}
+
+/// A single problem (or note) found while importing a BoM file.
+///
+/// Row-level problems leave `column`, `raw_value` and `expected` empty;
+/// cell-level problems fill them in so the UI can point at the exact value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportDiagnostic {
+    /// 1-based line number in the source file, if the problem is tied to a line.
+    pub line: Option<u64>,
+    pub column: Option<String>,
+    pub raw_value: Option<String>,
+    pub expected: Option<ExpectedType>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for ImportDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {line}: ")?;
+        }
+        if let Some(column) = &self.column {
+            write!(f, "{column}: ")?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+/// Outcome of importing a BoM file: accepted rows plus everything that went wrong.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BomImportReport {
+    /// Where the rows were read from, for display purposes.
+    pub source: String,
+    pub items: Vec<BomItem>,
+    pub diagnostics: Vec<ImportDiagnostic>,
+    /// Non-blank data rows seen in the file (excluding the header row).
+    pub rows_read: usize,
+    pub rows_rejected: usize,
+}
+
+impl BomImportReport {
+    /// The most severe diagnostic in the report, or `Info` when there are none.
+    pub fn severity(&self) -> Severity {
+        self.diagnostics
+            .iter()
+            .map(|d| d.severity)
+            .max()
+            .unwrap_or(Severity::Info)
+    }
+
+    pub fn rows_imported(&self) -> usize {
+        self.rows_read.saturating_sub(self.rows_rejected)
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.severity() == Severity::Info
+    }
+
+    /// Short human-readable outcome, e.g. "10 rows imported, 3 rows rejected".
+    pub fn summary(&self) -> String {
+        let imported = self.rows_imported();
+        let mut summary = format!("{} {} imported", imported, plural(imported, "row"));
+        if self.rows_rejected > 0 {
+            summary.push_str(&format!(
+                ", {} {} rejected",
+                self.rows_rejected,
+                plural(self.rows_rejected, "row")
+            ));
+        }
+        summary
+    }
+
+    /// Build a report for a file that could not be read at all.
+    pub fn failed(source: impl Into<String>, error: &BomImportError) -> Self {
+        Self {
+            source: source.into(),
+            diagnostics: vec![ImportDiagnostic {
+                line: None,
+                column: None,
+                raw_value: None,
+                expected: None,
+                severity: Severity::Error,
+                message: error.to_string(),
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn push_row_error(&mut self, line: Option<u64>, message: String) {
+        self.diagnostics.push(ImportDiagnostic {
+            line,
+            column: None,
+            raw_value: None,
+            expected: None,
+            severity: Severity::Error,
+            message,
+        });
+    }
+}
+
+fn plural(count: usize, word: &str) -> String {
+    if count == 1 {
+        word.to_string()
+    } else {
+        format!("{word}s")
+    }
+}
+
+/// Failure that prevents a BoM file from being read at all.
+///
+/// Problems with individual rows are reported as diagnostics instead.
+#[derive(Debug)]
+pub enum BomImportError {
+    Io(std::io::Error),
+    Csv(csv::Error),
+}
+
+impl fmt::Display for BomImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BomImportError::Io(err) => write!(f, "could not read BoM file: {err}"),
+            BomImportError::Csv(err) => write!(f, "could not parse BoM file: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for BomImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BomImportError::Io(err) => Some(err),
+            BomImportError::Csv(err) => Some(err),
+        }
+    }
+}
+
+impl From<std::io::Error> for BomImportError {
+    fn from(err: std::io::Error) -> Self {
+        BomImportError::Io(err)
+    }
+}
+
+impl From<csv::Error> for BomImportError {
+    fn from(err: csv::Error) -> Self {
+        BomImportError::Csv(err)
+    }
+}
+
+/// Settings that control how a BoM file is interpreted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BomImportOptions {
+    pub mapping: ColumnMapping,
+    /// Currency that cost columns are denominated in.
+    pub currency: Currency,
+}
+
+/// Import a BoM from a CSV file using [`BomImportOptions::default`].
+///
+/// Rows that are short or contain unparseable values are rejected and
+/// described in the report rather than being imported with zeroed fields.
+pub fn import_bom_from_csv<P: AsRef<Path>>(path: P) -> Result<BomImportReport, BomImportError> {
+    import_bom_from_csv_with(path, &BomImportOptions::default())
+}
+
+/// Import a BoM from a CSV file with caller-supplied options.
+pub fn import_bom_from_csv_with<P: AsRef<Path>>(
+    path: P,
+    options: &BomImportOptions,
+) -> Result<BomImportReport, BomImportError> {
+    let path = path.as_ref();
+    let file = std::fs::File::open(path)?;
+    let mut report = import_bom_from_reader(file, options)?;
+    report.source = path.display().to_string();
+    Ok(report)
+}
+
+/// Import a BoM from any CSV reader. See [`import_bom_from_csv`].
+pub fn import_bom_from_reader<R: Read>(
+    reader: R,
+    options: &BomImportOptions,
+) -> Result<BomImportReport, BomImportError> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_reader(reader);
+
+    let headers: Vec<String> = rdr
+        .headers()?
+        .iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+    let columns = options.mapping.resolve(&headers);
+    let mut report = BomImportReport::default();
+
+    for (field, index) in &columns.duplicates {
+        report.diagnostics.push(ImportDiagnostic {
+            line: Some(1),
+            column: Some(headers[*index].clone()),
+            raw_value: None,
+            expected: None,
+            severity: Severity::Warning,
+            message: format!(
+                "{field} is already read from another column; kept as an extra attribute"
+            ),
+        });
+    }
+
+    let missing = columns.missing();
+    if !missing.is_empty() {
+        let missing: Vec<String> = missing.iter().map(|f| f.to_string()).collect();
+        report.push_row_error(
+            Some(1),
+            format!("no column found for: {}", missing.join(", ")),
+        );
+        return Ok(report);
+    }
+
+    // Every column that is not mapped to a field is carried through as an extra attribute.
+    let extra_columns: Vec<usize> = columns
+        .extra
+        .iter()
+        .copied()
+        .chain(columns.duplicates.iter().map(|(_, index)| *index))
+        .collect();
+    let id_column = find_header(&headers, &columns.extra, &ID_HEADERS);
+    let parent_column = find_header(&headers, &columns.extra, &PARENT_HEADERS);
+    let mut placements = Vec::new();
+
+    for result in rdr.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(err) => {
+                let line = err.position().map(|p| p.line());
+                report.rows_read += 1;
+                report.rows_rejected += 1;
+                report.push_row_error(line, format!("unreadable row: {err}"));
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line());
+
+        if record.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        report.rows_read += 1;
+
+        let mut row = RowParser {
+            line,
+            headers: &headers,
+            columns: &columns,
+            currency: options.currency,
+            record: &record,
+            diagnostics: Vec::new(),
+        };
+        let name = row.text(BomField::Name);
+        let quantity = row.whole_number(BomField::Quantity);
+        let unit_cost = row.money(BomField::UnitCost);
+        let total_cost = row.money(BomField::TotalCost);
+        let lead_time_days = row.lead_time();
+        let min_quantity = row.whole_number(BomField::MinQuantity);
+        let category = row.optional_text(BomField::Category);
+        let pack_size = row.optional_pack_size(BomField::PackSize);
+        let level = row.optional_whole_number(BomField::Level);
+        let supplier = row.optional_text(BomField::Supplier);
+        let part_number = row.optional_text(BomField::PartNumber);
+
+        let rejected = !row.diagnostics.is_empty();
+        report.diagnostics.append(&mut row.diagnostics);
+        if rejected {
+            report.rows_rejected += 1;
+            continue;
+        }
+
+        let extra: BTreeMap<String, String> = extra_columns
+            .iter()
+            .filter_map(|&index| {
+                let value = record.get(index)?.trim();
+                (!value.is_empty()).then(|| (headers[index].clone(), value.to_string()))
+            })
+            .collect();
+
+        let cell = |column: Option<usize>| {
+            let value = record.get(column?)?.trim();
+            (!value.is_empty()).then(|| value.to_string())
+        };
+        placements.push(Placement {
+            line,
+            indent: columns
+                .position(BomField::Name)
+                .and_then(|index| record.get(index))
+                .map_or(0, indent_width),
+            id: cell(id_column),
+            parent: cell(parent_column),
+        });
+        report.items.push(BomItem {
+            name: name.unwrap_or_default(),
+            quantity: quantity.unwrap_or_default(),
+            unit_cost: unit_cost.unwrap_or(Money::zero(options.currency)),
+            total_cost: total_cost.unwrap_or(Money::zero(options.currency)),
+            file_total: total_cost,
+            lead_time_days: lead_time_days.unwrap_or_default(),
+            min_quantity: min_quantity.unwrap_or_default(),
+            category,
+            pack_size,
+            extra,
+            level: level.unwrap_or_default(),
+            supplier,
+            part_number,
+        });
+    }
+
+    if columns.position(BomField::Level).is_some() {
+        let changed = normalize_levels(&mut report.items);
+        for (index, old) in changed {
+            let new = report.items[index].level;
+            report.diagnostics.push(ImportDiagnostic {
+                line: placements[index].line,
+                column: columns
+                    .position(BomField::Level)
+                    .map(|index| headers[index].clone()),
+                raw_value: Some(old.to_string()),
+                expected: None,
+                severity: Severity::Warning,
+                message: format!(
+                    "level {old} does not follow on from the line above; read as level {new}"
+                ),
+            });
+        }
+    } else if let (Some(_), Some(parent_column)) = (id_column, parent_column) {
+        let header = &headers[parent_column];
+        nest_by_parent_id(&mut report, &placements, header);
+    } else {
+        nest_by_indent(&mut report.items, &placements);
+    }
+
+    if report.rows_read == 0 {
+        report.diagnostics.push(ImportDiagnostic {
+            line: None,
+            column: None,
+            raw_value: None,
+            expected: None,
+            severity: Severity::Warning,
+            message: "file contains no data rows".to_string(),
+        });
+    }
+
+    Ok(report)
+}
+
+/// Headers of the columns holding a row's own id and its assembly's id, in
+/// BoM files that describe assemblies that way instead of with levels.
+const ID_HEADERS: [&str; 4] = ["id", "item id", "line id", "part id"];
+const PARENT_HEADERS: [&str; 4] = ["parent", "parent id", "parent item", "assembly id"];
+
+/// Where an imported row sits in the assembly tree, as the file tells it.
+struct Placement {
+    line: Option<u64>,
+    /// Leading whitespace before the name, with tabs counting as four spaces.
+    indent: usize,
+    id: Option<String>,
+    parent: Option<String>,
+}
+
+fn indent_width(raw: &str) -> usize {
+    raw.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// The first of `columns` whose header is one of `names`.
+fn find_header(headers: &[String], columns: &[usize], names: &[&str]) -> Option<usize> {
+    columns.iter().copied().find(|&index| {
+        let header = normalize_header(&headers[index]);
+        names.iter().any(|name| normalize_header(name) == header)
+    })
+}
+
+/// Set levels from how far each name is indented: a row indented further
+/// than the one above is one of its parts, and a row indented as far as an
+/// earlier one is at that row's level.
+fn nest_by_indent(items: &mut [BomItem], placements: &[Placement]) {
+    let mut indents: Vec<usize> = Vec::new();
+    for (item, placement) in items.iter_mut().zip(placements) {
+        while indents.last().is_some_and(|&open| open > placement.indent) {
+            indents.pop();
+        }
+        if indents.last() != Some(&placement.indent) {
+            indents.push(placement.indent);
+        }
+        item.level = indents.len() as u32 - 1;
+    }
+}
+
+/// Reorder rows so each assembly is followed by its parts, with levels to
+/// match. Parts keep their order in the file. Rows whose assembly is missing,
+/// or that are caught in a loop of assemblies, become top-level lines.
+fn nest_by_parent_id(report: &mut BomImportReport, placements: &[Placement], header: &str) {
+    let mut by_id: HashMap<&str, usize> = HashMap::new();
+    for (index, placement) in placements.iter().enumerate() {
+        if let Some(id) = &placement.id {
+            by_id.entry(id.as_str()).or_insert(index);
+        }
+    }
+
+    let warn = |report: &mut BomImportReport, index: usize, message: String| {
+        report.diagnostics.push(ImportDiagnostic {
+            line: placements[index].line,
+            column: Some(header.to_string()),
+            raw_value: placements[index].parent.clone(),
+            expected: None,
+            severity: Severity::Warning,
+            message,
+        });
+    };
+    let mut parts: Vec<Vec<usize>> = vec![Vec::new(); placements.len()];
+    let mut roots = Vec::new();
+    for (index, placement) in placements.iter().enumerate() {
+        match &placement.parent {
+            None => roots.push(index),
+            Some(parent) => match by_id.get(parent.as_str()) {
+                Some(&assembly) if assembly != index => parts[assembly].push(index),
+                _ => {
+                    warn(
+                        report,
+                        index,
+                        format!("no row has id \"{parent}\"; imported as a top-level line"),
+                    );
+                    roots.push(index);
+                }
+            },
+        }
+    }
+
+    // Each row with its level, assemblies before their parts.
+    let mut order: Vec<(usize, u32)> = Vec::with_capacity(placements.len());
+    let mut placed = vec![false; placements.len()];
+    let place = |root: usize, order: &mut Vec<(usize, u32)>, placed: &mut [bool]| {
+        let mut pending = vec![(root, 0)];
+        while let Some((index, level)) = pending.pop() {
+            if std::mem::replace(&mut placed[index], true) {
+                continue;
+            }
+            order.push((index, level));
+            pending.extend(parts[index].iter().rev().map(|&part| (part, level + 1)));
+        }
+    };
+    for &root in &roots {
+        place(root, &mut order, &mut placed);
+    }
+    for index in 0..placements.len() {
+        if !placed[index] {
+            warn(
+                report,
+                index,
+                "assemblies contain each other; imported as a top-level line".to_string(),
+            );
+            place(index, &mut order, &mut placed);
+        }
+    }
+
+    let mut items: Vec<Option<BomItem>> = report.items.drain(..).map(Some).collect();
+    report.items = order
+        .into_iter()
+        .filter_map(|(index, level)| {
+            let mut item = items[index].take()?;
+            item.level = level;
+            Some(item)
+        })
+        .collect();
+}
+
+/// Parses the cells of one record, collecting a diagnostic for each bad value.
+struct RowParser<'a> {
+    line: Option<u64>,
+    headers: &'a [String],
+    columns: &'a ResolvedColumns,
+    currency: Currency,
+    record: &'a csv::StringRecord,
+    diagnostics: Vec<ImportDiagnostic>,
+}
+
+impl<'a> RowParser<'a> {
+    /// The trimmed cell for `field`, or `None` (with a diagnostic) if the row is too short.
+    fn cell(&mut self, field: BomField, expected: ExpectedType) -> Option<(usize, &'a str)> {
+        let index = self.columns.position(field)?;
+        let record: &'a csv::StringRecord = self.record;
+        match record.get(index) {
+            Some(raw) => Some((index, raw.trim())),
+            None => {
+                self.diagnostics.push(ImportDiagnostic {
+                    line: self.line,
+                    column: Some(self.header(index)),
+                    raw_value: None,
+                    expected: Some(expected),
+                    severity: Severity::Error,
+                    message: format!("value is missing (expected {expected})"),
+                });
+                None
+            }
+        }
+    }
+
+    fn text(&mut self, field: BomField) -> Option<String> {
+        let (index, raw) = self.cell(field, ExpectedType::Text)?;
+        if raw.is_empty() {
+            self.reject(index, raw, ExpectedType::Text, "value is empty");
+            return None;
+        }
+        Some(raw.to_string())
+    }
+
+    /// Text for an optional field; absent columns and blank cells are `None`.
+    fn optional_text(&self, field: BomField) -> Option<String> {
+        let index = self.columns.position(field)?;
+        let raw = self.record.get(index)?.trim();
+        (!raw.is_empty()).then(|| raw.to_string())
+    }
+
+    fn whole_number(&mut self, field: BomField) -> Option<u32> {
+        let (index, raw) = self.cell(field, ExpectedType::WholeNumber)?;
+        match raw.parse() {
+            Ok(value) => Some(value),
+            Err(_) => {
+                self.reject(index, raw, ExpectedType::WholeNumber, "not a whole number");
+                None
+            }
+        }
+    }
+
+    /// A lead time of at most [`MAX_LEAD_TIME_DAYS`] working days.
+    fn lead_time(&mut self) -> Option<u32> {
+        let field = BomField::LeadTimeDays;
+        let (index, raw) = self.cell(field, ExpectedType::WholeNumber)?;
+        match raw.parse() {
+            Ok(days) if days > MAX_LEAD_TIME_DAYS => {
+                self.reject(index, raw, ExpectedType::WholeNumber, LEAD_TIME_TOO_LONG);
+                None
+            }
+            Ok(days) => Some(days),
+            Err(_) => {
+                self.reject(index, raw, ExpectedType::WholeNumber, "not a whole number");
+                None
+            }
+        }
+    }
+
+    /// A whole number for an optional field; absent columns and blank cells are `None`.
+    fn optional_whole_number(&mut self, field: BomField) -> Option<u32> {
+        let index = self.columns.position(field)?;
+        let record: &'a csv::StringRecord = self.record;
+        let raw = record.get(index)?.trim();
+        if raw.is_empty() {
+            return None;
+        }
+        match raw.parse() {
+            Ok(value) => Some(value),
+            Err(_) => {
+                self.reject(index, raw, ExpectedType::WholeNumber, "not a whole number");
+                None
+            }
+        }
+    }
+
+    /// A pack size of at least one for an optional field; absent columns and blank cells are `None`.
+    fn optional_pack_size(&mut self, field: BomField) -> Option<u32> {
+        let index = self.columns.position(field)?;
+        let record: &'a csv::StringRecord = self.record;
+        let raw = record.get(index)?.trim();
+        if raw.is_empty() {
+            return None;
+        }
+        match raw.parse() {
+            Ok(0) => {
+                self.reject(
+                    index,
+                    raw,
+                    ExpectedType::WholeNumber,
+                    "pack size must be at least 1",
+                );
+                None
+            }
+            Ok(value) => Some(value),
+            Err(_) => {
+                self.reject(index, raw, ExpectedType::WholeNumber, "not a whole number");
+                None
+            }
+        }
+    }
+
+    fn money(&mut self, field: BomField) -> Option<Money> {
+        let (index, raw) = self.cell(field, ExpectedType::Money)?;
+        match Money::parse(raw, self.currency) {
+            Ok(value) => Some(value),
+            Err(MoneyError::TooPrecise(_)) => {
+                self.reject(index, raw, ExpectedType::Money, "too many decimal places");
+                None
+            }
+            Err(_) => {
+                self.reject(index, raw, ExpectedType::Money, "not an amount");
+                None
+            }
+        }
+    }
+
+    fn header(&self, index: usize) -> String {
+        self.headers
+            .get(index)
+            .cloned()
+            .unwrap_or_else(|| format!("column {}", index + 1))
+    }
+
+    fn reject(&mut self, index: usize, raw: &str, expected: ExpectedType, reason: &str) {
+        self.diagnostics.push(ImportDiagnostic {
+            line: self.line,
+            column: Some(self.header(index)),
+            raw_value: Some(raw.to_string()),
+            expected: Some(expected),
+            severity: Severity::Error,
+            message: format!("{reason} (got \"{raw}\", expected {expected})"),
+        });
+    }
+}
@@ -1,10 +1,88 @@
+use crate::assembly::levels_of_selection;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
 
+mod assembly;
+mod calendar;
+mod columns;
+mod editing;
+mod history;
+mod import;
+mod logging;
+mod migrate;
+mod money;
+mod ordering;
+mod planning;
+mod pricing;
+mod project;
+mod purchase;
+mod query;
+mod quote;
+mod reconcile;
+mod revisions;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod storage;
+mod suppliers;
+
+pub use assembly::{AssemblyNode, BomTree};
+pub use calendar::{
+    load_holidays, parse_csv_holidays, parse_ical_holidays, CalendarError, WorkingCalendar,
+    MAX_LEAD_TIME_DAYS,
+};
+pub use columns::{normalize_header, BomField, ColumnMapping, ResolvedColumns};
+pub use editing::{edit_bom_item, BomEditError};
+pub use history::{Change, History, HistoryStep, DEFAULT_HISTORY_DEPTH};
+pub use import::{
+    import_bom_from_csv, import_bom_from_csv_with, import_bom_from_reader, BomImportError,
+    BomImportOptions, BomImportReport, ExpectedType, ImportDiagnostic, Severity,
+};
+pub use logging::{LogEvent, LogFilter, LogLevel, LogStore, DEFAULT_LOG_CAPACITY};
+pub use migrate::{migrate_project, Migration, MigrationError, OLDEST_PROJECT_VERSION};
+pub use money::{Currency, Money, MoneyError, RoundingMode, DISPLAY_DECIMALS, MONEY_SCALE};
+pub use ordering::{advise_orders, OrderAdvice, OrderingPolicy};
+pub use planning::{
+    parse_date, plan_orders, OrderUrgency, PlannedOrder, PlanningError, PlanningSettings,
+};
+pub use pricing::{
+    price_bom, Adjustment, AdjustmentKind, PriceBreakdown, PricingError, PricingLine, PricingRules,
+    Rate,
+};
+pub use project::{
+    load_project, project_from_str, project_to_string, save_project, ProjectError, ProjectFile,
+    PROJECT_EXTENSION, PROJECT_FORMAT, PROJECT_VERSION,
+};
+pub use purchase::{
+    draft_purchase_orders, render_purchase_order_pdf, write_purchase_order,
+    write_purchase_order_csv, LeftOutLine, LeftOutReason, PurchaseOrder, PurchaseOrderDraft,
+    PurchaseOrderError, PurchaseOrderInputs, PurchaseOrderLine,
+};
+pub use query::{export_bom_csv, BomQuery, BomSortKey, SortDirection};
+pub use quote::{
+    render_quote_pdf, write_quote_pdf, CustomerDetails, QuoteDetails, QuoteDocument, QuoteError,
+    QuoteTemplate,
+};
+pub use reconcile::{reconcile_bom, LineReconciliation, LineStatus, ReconcilePolicy, TotalSource};
+pub use revisions::{diff_bom, BomDiff, LineDiff, QuoteRevision, RevisionError};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteRepository;
+pub use storage::{
+    MemoryRepository, PricePoint, ProjectId, ProjectRepository, StorageError, StoredProject,
+    StoredRevision,
+};
+pub use suppliers::{
+    group_by_supplier, import_suppliers_from_csv, import_suppliers_from_reader, CatalogueError,
+    Supplier, SupplierCatalogue, SupplierGroup, SupplierImportReport,
+};
+
 /// High-level tabs in the example application.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum TabKind {
     Overview,
+    Breakdown,
+    Revisions,
+    Orders,
     Logs,
     Settings,
     Advanced,
@@ -19,8 +97,41 @@ pub enum TabKind {
 pub struct AppState {
     pub overview: OverviewSummary,
     pub bom: Vec<BomItem>,
+    pub bom_import: BomImportReport,
+    /// Per-line check of supplied totals against quantity × unit cost, aligned with `bom`.
+    pub reconciliation: Vec<LineReconciliation>,
     pub settings: SettingsSummary,
+    /// Customer, numbering and template used when generating the quote PDF.
+    pub quote: QuoteDetails,
+    /// Installation date and working-day calendar used for order planning.
+    pub planning: PlanningSettings,
+    /// Contact details, default lead times and currencies of the suppliers
+    /// lines are bought from.
+    pub suppliers: SupplierCatalogue,
+    /// Quotes issued to the customer so far, oldest first.
+    pub revisions: Vec<QuoteRevision>,
+    /// Purchase orders issued to suppliers so far, oldest first.
+    pub purchase_orders: Vec<PurchaseOrder>,
     pub advanced: AdvancedSummary,
+    /// What the backend did this session; not persisted with the state.
+    #[serde(skip)]
+    pub log: LogStore,
+    /// Changes made this session that can be undone; not persisted either.
+    #[serde(skip)]
+    pub history: History,
+    /// The cell being typed into, if any.
+    #[serde(skip)]
+    cell_edit: Option<CellEdit>,
+}
+
+/// A BoM cell being typed into, whose values are applied as they are typed
+/// but undo and are logged as one edit.
+#[derive(Debug, Clone)]
+struct CellEdit {
+    index: usize,
+    field: BomField,
+    /// The last value applied, if any was.
+    value: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,16 +145,63 @@ pub struct OverviewSummary {
 pub struct BomItem {
     pub name: String,
     pub quantity: u32,
-    pub unit_cost: f32,
-    pub total_cost: f32,
+    pub unit_cost: Money,
+    /// The line total in use: the supplied or the computed one, as the
+    /// [`ReconcilePolicy`] decides.
+    pub total_cost: Money,
+    /// The line total as supplied in the BoM file. Reconciliation reads it
+    /// but never changes it; it is `None` for lines entered in the app and
+    /// once the quantity or unit cost has been edited, as the supplied
+    /// total no longer describes the line.
+    #[serde(default)]
+    pub file_total: Option<Money>,
     pub lead_time_days: u32,
     pub min_quantity: u32,
+    /// Optional grouping used for category-specific tax rates.
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Units per pack; orders are placed in whole packs. `None` means single units.
+    #[serde(default)]
+    pub pack_size: Option<u32>,
+    /// Columns from the source file that do not map to a known field, keyed by header.
+    #[serde(default)]
+    pub extra: BTreeMap<String, String>,
+    /// Depth in the assembly tree; 0 for top-level lines. See [`BomTree`].
+    #[serde(default)]
+    pub level: u32,
+    /// Who the line is bought from; details may be in the [`SupplierCatalogue`].
+    #[serde(default)]
+    pub supplier: Option<String>,
+    /// The supplier's code for the item.
+    #[serde(default)]
+    pub part_number: Option<String>,
+}
+
+impl BomItem {
+    /// The line's supplier, if it names one.
+    pub fn supplier(&self) -> Option<&str> {
+        self.supplier
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SettingsSummary {
     pub configured: bool,
     pub description: String,
+    /// Currency that BoM costs and quotes are expressed in.
+    pub currency: Currency,
+    /// Rounding applied when totals are presented to two decimal places.
+    pub rounding: RoundingMode,
+    /// How supplied line totals are checked and which value is used.
+    pub reconcile: ReconcilePolicy,
+    /// Ordered markup, fee, discount and tax steps applied to the BoM subtotal.
+    pub pricing: PricingRules,
+    /// When rounding up to MOQs and pack sizes is flagged as wasteful.
+    #[serde(default)]
+    pub ordering: OrderingPolicy,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,11 +210,8 @@ pub struct AdvancedSummary {
 }
 
 impl AppState {
-    /// Construct a sample application state suitable for local runs and initial integration.
-    ///
-    /// `bom_path` is expected to point to a CSV file with columns:
-    /// name, quantity, unit_cost, total_cost, lead_time_days, min_quantity.
-    pub fn demo_with_bom_path<P: AsRef<Path>>(bom_path: P) -> Self {
+    /// An estimate with no materials, standard settings and no installation date.
+    pub fn blank() -> Self {
         let overview = OverviewSummary {
             title: "Service overview".to_string(),
             status: "All systems nominal".to_string(),
@@ -70,6 +225,11 @@ impl AppState {
         let settings = SettingsSummary {
             configured: true,
             description: "Core credentials and thresholds are configured. Details are kept in the backend layer.".to_string(),
+            currency: Currency::GBP,
+            rounding: RoundingMode::HalfUp,
+            reconcile: ReconcilePolicy::default(),
+            pricing: PricingRules::standard(Currency::GBP),
+            ordering: OrderingPolicy::default(),
         };
 
         let advanced = AdvancedSummary {
@@ -78,13 +238,305 @@ impl AppState {
                     .to_string(),
         };
 
-        let bom = load_bom_from_csv(bom_path).unwrap_or_else(|_| demo_bom());
-
         Self {
             overview,
-            bom,
+            bom: Vec::new(),
+            bom_import: BomImportReport::default(),
+            reconciliation: Vec::new(),
             settings,
+            quote: QuoteDetails::default(),
+            planning: PlanningSettings::default(),
+            suppliers: SupplierCatalogue::default(),
+            revisions: Vec::new(),
+            purchase_orders: Vec::new(),
             advanced,
+            log: LogStore::default(),
+            history: History::default(),
+            cell_edit: None,
+        }
+    }
+
+    /// Construct a sample application state suitable for local runs and initial integration.
+    ///
+    /// `bom_path` is expected to point to a CSV file whose headers match
+    /// [`ColumnMapping::standard`] (e.g. "Material", "QTY", "Unit cost").
+    pub fn demo_with_bom_path<P: AsRef<Path>>(bom_path: P) -> Self {
+        let mut state = Self::blank();
+
+        let bom_path = bom_path.as_ref();
+        let options = BomImportOptions {
+            currency: state.settings.currency,
+            ..Default::default()
+        };
+        let mut bom_import = match import_bom_from_csv_with(bom_path, &options) {
+            Ok(report) => report,
+            Err(err) => {
+                // Keep the demo usable, but make the fallback visible in the report.
+                let mut report = BomImportReport::failed(bom_path.display().to_string(), &err);
+                report.items = demo_bom();
+                report.diagnostics.push(ImportDiagnostic {
+                    line: None,
+                    column: None,
+                    raw_value: None,
+                    expected: None,
+                    severity: Severity::Warning,
+                    message: "showing built-in demo materials instead".to_string(),
+                });
+                report
+            }
+        };
+        // The accepted rows move into the state; the report keeps the counts and diagnostics.
+        state.bom = std::mem::take(&mut bom_import.items);
+        state.bom_import = bom_import;
+        // Six weeks out gives the demo a mix of due and scheduled orders.
+        state.planning.installation_date =
+            Some(chrono::Local::now().date_naive() + chrono::Days::new(42));
+
+        state.log_import();
+        state.reconcile_totals();
+        state
+    }
+
+    /// Replace the estimate with a blank one, keeping the session log.
+    pub fn new_project(&mut self) {
+        let mut blank = Self::blank();
+        blank.log = std::mem::take(&mut self.log);
+        *self = blank;
+        self.log.record(LogEvent::new(
+            LogLevel::Info,
+            "project",
+            "new project started",
+        ));
+    }
+
+    /// Replace the estimate with the project saved at `path`, keeping the
+    /// session log. Returns the quote PDF attached to the project, if any.
+    ///
+    /// On error the current estimate is left as it was.
+    pub fn open_project<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<Option<Vec<u8>>, ProjectError> {
+        let path = path.as_ref();
+        let project = match load_project(path) {
+            Ok(project) => project,
+            Err(err) => {
+                self.log.record(
+                    LogEvent::new(
+                        LogLevel::Error,
+                        "project",
+                        format!("project not opened: {err}"),
+                    )
+                    .with_field("path", path.display()),
+                );
+                return Err(err);
+            }
+        };
+        let event = LogEvent::new(LogLevel::Info, "project", "project opened")
+            .with_field("path", path.display());
+        Ok(self.replace_with_project(project, event))
+    }
+
+    /// Replace the estimate with the latest revision of stored project `id`,
+    /// keeping the session log. Returns the quote PDF saved with it, if any.
+    ///
+    /// On error the current estimate is left as it was.
+    pub fn open_stored_project(
+        &mut self,
+        repository: &dyn ProjectRepository,
+        id: ProjectId,
+    ) -> Result<Option<Vec<u8>>, StorageError> {
+        let project = match repository.load_project(id) {
+            Ok(project) => project,
+            Err(err) => {
+                self.log.record(
+                    LogEvent::new(
+                        LogLevel::Error,
+                        "storage",
+                        format!("stored project not opened: {err}"),
+                    )
+                    .with_field("project", id),
+                );
+                return Err(err);
+            }
+        };
+        let event = LogEvent::new(LogLevel::Info, "storage", "stored project opened")
+            .with_field("project", id);
+        Ok(self.replace_with_project(project, event))
+    }
+
+    /// Swap in an opened project's state and log `event` with its details.
+    fn replace_with_project(
+        &mut self,
+        project: ProjectFile,
+        mut event: LogEvent,
+    ) -> Option<Vec<u8>> {
+        let mut state = project.state;
+        state.log = std::mem::take(&mut self.log);
+        *self = state;
+        self.reconcile_totals();
+        event = event.with_field("lines", self.bom.len());
+        if let Some(saved_at) = project.saved_at {
+            event = event.with_field("saved_at", saved_at.format("%Y-%m-%d %H:%M"));
+        }
+        if project.format_version < PROJECT_VERSION {
+            event = event.with_field("upgraded_from_version", project.format_version);
+        }
+        self.log.record(event);
+        project.quote_pdf
+    }
+
+    /// Save the estimate to `path` with the quote PDF dated today attached,
+    /// and mark it as unmodified.
+    pub fn save_project<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ProjectError> {
+        let path = path.as_ref();
+        // A quote that cannot be priced is left out rather than blocking the save.
+        let quote_pdf = self.quote_pdf(chrono::Local::now().date_naive()).ok();
+        let result = save_project(path, self, quote_pdf.as_deref());
+        let event = match &result {
+            Ok(()) => {
+                self.history.mark_saved();
+                LogEvent::new(LogLevel::Info, "project", "project saved")
+                    .with_field("quote_attached", quote_pdf.is_some())
+            }
+            Err(err) => LogEvent::new(
+                LogLevel::Error,
+                "project",
+                format!("project not saved: {err}"),
+            ),
+        };
+        self.log.record(event.with_field("path", path.display()));
+        result
+    }
+
+    /// Save the estimate to `repository` as a new revision of project `id`
+    /// (or as a new project), with the quote PDF dated today attached, and
+    /// mark it as unmodified. Returns the stored project's id.
+    pub fn save_stored_project(
+        &mut self,
+        repository: &mut dyn ProjectRepository,
+        id: Option<ProjectId>,
+    ) -> Result<ProjectId, StorageError> {
+        let quote_pdf = self.quote_pdf(chrono::Local::now().date_naive()).ok();
+        let result = repository.save_project(id, self, quote_pdf.as_deref());
+        let event = match &result {
+            Ok(id) => {
+                self.history.mark_saved();
+                LogEvent::new(LogLevel::Info, "storage", "project stored")
+                    .with_field("project", id)
+                    .with_field("quote_attached", quote_pdf.is_some())
+            }
+            Err(err) => LogEvent::new(
+                LogLevel::Error,
+                "storage",
+                format!("project not stored: {err}"),
+            ),
+        };
+        self.log.record(event);
+        result
+    }
+
+    /// Whether the estimate has changed since it was last saved, opened or started.
+    pub fn is_modified(&self) -> bool {
+        self.history.is_modified()
+    }
+
+    /// Record the import summary and each diagnostic in the log.
+    fn log_import(&mut self) {
+        let import = &self.bom_import;
+        let mut events = vec![LogEvent::new(
+            import.severity().into(),
+            "import",
+            format!("BoM import: {}", import.summary()),
+        )
+        .with_field("source", &import.source)
+        .with_field("rows_read", import.rows_read)];
+        events.extend(import.diagnostics.iter().map(|diagnostic| {
+            let mut event = LogEvent::new(
+                diagnostic.severity.into(),
+                "import",
+                diagnostic.message.clone(),
+            );
+            if let Some(line) = diagnostic.line {
+                event = event.with_field("line", line);
+            }
+            if let Some(column) = &diagnostic.column {
+                event = event.with_field("column", column);
+            }
+            if let Some(raw) = &diagnostic.raw_value {
+                event = event.with_field("value", raw);
+            }
+            event
+        }));
+        for event in events {
+            self.log.record(event);
+        }
+    }
+
+    /// Change which line total is authoritative and re-run the reconciliation.
+    ///
+    /// Supplied totals are kept apart from the totals in use, so switching
+    /// back and forth is lossless. Choosing the policy already in force
+    /// changes nothing and leaves no undo step.
+    pub fn set_reconcile_policy(&mut self, policy: ReconcilePolicy) {
+        if policy == self.settings.reconcile {
+            return;
+        }
+        self.history.record(Change::ReconcilePolicy {
+            before: self.settings.reconcile,
+            after: policy,
+        });
+        self.apply_reconcile_policy(policy);
+    }
+
+    fn apply_reconcile_policy(&mut self, policy: ReconcilePolicy) {
+        self.settings.reconcile = policy;
+        self.reconcile_totals();
+    }
+
+    fn reconcile_totals(&mut self) {
+        match reconcile_bom(
+            &mut self.bom,
+            &self.settings.reconcile,
+            self.settings.rounding,
+        ) {
+            Ok(lines) => {
+                let mismatches = lines
+                    .iter()
+                    .filter(|line| line.status == LineStatus::Mismatch)
+                    .count();
+                let level = if mismatches == 0 {
+                    LogLevel::Info
+                } else {
+                    LogLevel::Warn
+                };
+                // A cell being typed into is checked once more when the edit ends.
+                if self.cell_edit.is_none() {
+                    self.log.record(
+                        LogEvent::new(level, "reconcile", "line totals checked")
+                            .with_field("lines", lines.len())
+                            .with_field("mismatches", mismatches)
+                            .with_field("authoritative", self.settings.reconcile.authoritative),
+                    );
+                }
+                self.reconciliation = lines;
+            }
+            Err(err) => {
+                self.log.record(LogEvent::new(
+                    LogLevel::Error,
+                    "reconcile",
+                    format!("line totals could not be reconciled: {err}"),
+                ));
+                self.reconciliation.clear();
+                self.bom_import.diagnostics.push(ImportDiagnostic {
+                    line: None,
+                    column: None,
+                    raw_value: None,
+                    expected: None,
+                    severity: Severity::Error,
+                    message: format!("line totals could not be reconciled: {err}"),
+                });
+            }
         }
     }
 
@@ -96,6 +548,834 @@ impl AppState {
         &self.bom
     }
 
+    /// Categories named on BoM lines, sorted, each once however it is
+    /// capitalised (tax matches categories case-insensitively).
+    pub fn bom_categories(&self) -> Vec<String> {
+        let mut categories: Vec<String> = Vec::new();
+        for category in self.bom.iter().filter_map(|item| item.category.as_deref()) {
+            let category = category.trim();
+            if !category.is_empty()
+                && !categories
+                    .iter()
+                    .any(|known| known.eq_ignore_ascii_case(category))
+            {
+                categories.push(category.to_string());
+            }
+        }
+        categories.sort_by_key(|category| category.to_lowercase());
+        categories
+    }
+
+    /// Set one cell of a BoM line from user-entered text.
+    ///
+    /// The line is left untouched when the value is rejected. See
+    /// [`edit_bom_item`] for validation and when the line total changes.
+    pub fn set_bom_cell(
+        &mut self,
+        index: usize,
+        field: BomField,
+        value: &str,
+    ) -> Result<(), BomEditError> {
+        let result = self
+            .bom
+            .get(index)
+            .ok_or(BomEditError::NoSuchLine(index))
+            .and_then(|item| edit_bom_item(item, field, value, self.settings.rounding));
+        let event = match (&result, &mut self.cell_edit) {
+            (Ok(_), Some(edit)) if (edit.index, edit.field) == (index, field) => {
+                // Logged once, with the final value, when the edit ends.
+                edit.value = Some(value.to_string());
+                None
+            }
+            (Ok(_), _) => Some(LogEvent::new(LogLevel::Info, "bom", "line edited")),
+            (Err(err), _) => Some(LogEvent::new(
+                LogLevel::Warn,
+                "bom",
+                format!("edit rejected: {err}"),
+            )),
+        };
+        if let Some(event) = event {
+            self.log.record(
+                event
+                    .with_field("line", index + 1)
+                    .with_field("field", field)
+                    .with_field("value", value),
+            );
+        }
+        let after = result?;
+
+        // A line given a supplier takes its usual lead time if it has none
+        // yet; both edits undo together.
+        let lead_time = match field {
+            BomField::Supplier if after.lead_time_days == 0 => self
+                .suppliers
+                .for_item(&after)
+                .and_then(|supplier| supplier.default_lead_time_days),
+            _ => None,
+        };
+        if lead_time.is_some() {
+            self.history
+                .begin_group(format!("set supplier and lead time on line {}", index + 1));
+        }
+        self.replace_bom_line(index, field, after);
+        if let Some(days) = lead_time {
+            let mut filled = self.bom[index].clone();
+            filled.lead_time_days = days;
+            self.replace_bom_line(index, BomField::LeadTimeDays, filled);
+            self.log.record(
+                LogEvent::new(LogLevel::Info, "bom", "lead time taken from supplier")
+                    .with_field("line", index + 1)
+                    .with_field("days", days),
+            );
+            self.history.end_group();
+        }
+        self.reconcile_totals();
+        Ok(())
+    }
+
+    /// Put an edited line in place of line `index` and record the change.
+    fn replace_bom_line(&mut self, index: usize, field: BomField, after: BomItem) {
+        let before = std::mem::replace(&mut self.bom[index], after.clone());
+        self.history.record(Change::BomLine {
+            index,
+            field,
+            before: Box::new(before),
+            after: Box::new(after),
+        });
+    }
+
+    /// Add an empty line at the end of the BoM, returning its index.
+    pub fn add_bom_line(&mut self) -> usize {
+        let item = BomItem::blank(self.settings.currency);
+        let index = self.bom.len();
+        self.history.record(Change::BomLineInserted {
+            index,
+            item: item.clone(),
+        });
+        self.bom.push(item);
+        self.log.record(
+            LogEvent::new(LogLevel::Info, "bom", "line added").with_field("line", index + 1),
+        );
+        self.reconcile_totals();
+        index
+    }
+
+    /// Insert a copy of a line directly below it, returning the copy's index.
+    pub fn duplicate_bom_line(&mut self, index: usize) -> Result<usize, BomEditError> {
+        let copy = self
+            .bom
+            .get(index)
+            .cloned()
+            .ok_or(BomEditError::NoSuchLine(index))?;
+        self.log.record(
+            LogEvent::new(LogLevel::Info, "bom", "line duplicated")
+                .with_field("line", index + 1)
+                .with_field("name", &copy.name),
+        );
+        self.history.record(Change::BomLineInserted {
+            index: index + 1,
+            item: copy.clone(),
+        });
+        self.bom.insert(index + 1, copy);
+        self.reconcile_totals();
+        Ok(index + 1)
+    }
+
+    pub fn remove_bom_line(&mut self, index: usize) -> Result<BomItem, BomEditError> {
+        if index >= self.bom.len() {
+            return Err(BomEditError::NoSuchLine(index));
+        }
+        let removed = self.bom.remove(index);
+        self.log.record(
+            LogEvent::new(LogLevel::Info, "bom", "line removed")
+                .with_field("line", index + 1)
+                .with_field("name", &removed.name),
+        );
+        self.history.record(Change::BomLineRemoved {
+            index,
+            item: removed.clone(),
+        });
+        self.reconcile_totals();
+        Ok(removed)
+    }
+
+    /// Indices of the BoM lines `query` selects, in its order.
+    pub fn query_bom(&self, query: &BomQuery) -> Vec<usize> {
+        query.apply(&self.bom, &self.reconciliation)
+    }
+
+    /// Write the lines `query` selects to `path` as CSV and return how many
+    /// were written.
+    ///
+    /// Lines are written in BoM order whatever the query's sort, as the
+    /// file's levels only describe assemblies in that order; a part whose
+    /// assembly the query leaves out is written under the nearest one kept.
+    pub fn export_bom<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        query: &BomQuery,
+    ) -> std::io::Result<usize> {
+        let path = path.as_ref();
+        let mut lines = self.query_bom(query);
+        lines.sort_unstable();
+        let levels = levels_of_selection(&self.bom, &lines);
+        let items: Vec<BomItem> = lines
+            .iter()
+            .zip(levels)
+            .map(|(&index, level)| BomItem {
+                level,
+                ..self.bom[index].clone()
+            })
+            .collect();
+        let result = export_bom_csv(path, &items);
+        let event = match &result {
+            Ok(written) => LogEvent::new(LogLevel::Info, "bom", "BoM view exported")
+                .with_field("lines", written),
+            Err(err) => LogEvent::new(
+                LogLevel::Error,
+                "bom",
+                format!("BoM view not exported: {err}"),
+            ),
+        };
+        self.log.record(
+            event
+                .with_field("filtered", query.is_filtered())
+                .with_field("path", path.display()),
+        );
+        result
+    }
+
+    /// The BoM's assemblies and parts, with costs and lead times rolled up.
+    pub fn bom_tree(&self) -> Result<BomTree, MoneyError> {
+        BomTree::build(&self.bom)
+    }
+
+    /// Every BoM line with its quantity and total for the whole estimate;
+    /// see [`BomTree::explode`].
+    pub fn exploded_bom(&self) -> Result<Vec<BomItem>, MoneyError> {
+        self.bom_tree()?.explode(&self.bom)
+    }
+
+    /// Lines that are bought rather than built, with quantities for the
+    /// whole estimate, keeping their BoM index.
+    fn purchased_lines(&self) -> Result<Vec<(usize, BomItem)>, MoneyError> {
+        let tree = self.bom_tree()?;
+        Ok(tree
+            .explode(&self.bom)?
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !tree.is_assembly(*index))
+            .collect())
+    }
+
+    /// Sum of all BoM line totals through their assemblies, rounded to two
+    /// places with the configured rounding mode.
+    pub fn bom_total(&self) -> Result<Money, MoneyError> {
+        let total = self.bom_tree()?.total(self.settings.currency)?;
+        Ok(total.round(DISPLAY_DECIMALS, self.settings.rounding))
+    }
+
+    /// Subtotal, each pricing adjustment and the grand total for the current BoM.
+    pub fn price_breakdown(&self) -> Result<PriceBreakdown, MoneyError> {
+        price_bom(
+            &self.exploded_bom()?,
+            &self.settings.pricing,
+            self.settings.currency,
+            self.settings.rounding,
+        )
+    }
+
+    pub fn quote(&self) -> &QuoteDetails {
+        &self.quote
+    }
+
+    /// Render the current BoM and pricing as quote PDF bytes dated `issue_date`.
+    pub fn quote_pdf(&self, issue_date: chrono::NaiveDate) -> Result<Vec<u8>, QuoteError> {
+        let breakdown = self.price_breakdown()?;
+        Ok(render_quote_pdf(&QuoteDocument {
+            details: &self.quote,
+            issue_date,
+            revision: self.quote_revision_number()?,
+            items: &self.exploded_bom()?,
+            breakdown: &breakdown,
+        }))
+    }
+
+    /// Generate the quote dated today, write it to `path` and return the bytes written.
+    pub fn export_quote_pdf<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<u8>, QuoteError> {
+        let path = path.as_ref();
+        let result = self
+            .quote_pdf(chrono::Local::now().date_naive())
+            .and_then(|bytes| {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(path, &bytes)?;
+                Ok(bytes)
+            });
+        let event = match &result {
+            Ok(bytes) => LogEvent::new(LogLevel::Info, "quote", "quote PDF written")
+                .with_field("bytes", bytes.len()),
+            Err(err) => LogEvent::new(
+                LogLevel::Error,
+                "quote",
+                format!("quote PDF not written: {err}"),
+            ),
+        };
+        self.log.record(
+            event
+                .with_field("number", &self.quote.number)
+                .with_field("path", path.display()),
+        );
+        result
+    }
+
+    /// Append an adjustment to the end of the pricing pipeline, unless its
+    /// value is out of range (see [`AdjustmentKind::check`]).
+    pub fn add_adjustment(&mut self, adjustment: Adjustment) -> Result<(), PricingError> {
+        adjustment.kind.check()?;
+        self.log.record(
+            LogEvent::new(LogLevel::Info, "pricing", "adjustment added")
+                .with_field("label", &adjustment.label)
+                .with_field("kind", adjustment.kind.kind_label()),
+        );
+        let before = self.settings.pricing.adjustments.clone();
+        self.settings.pricing.adjustments.push(adjustment);
+        self.record_adjustments_change(before);
+        Ok(())
+    }
+
+    pub fn remove_adjustment(&mut self, index: usize) -> Result<Adjustment, PricingError> {
+        if index >= self.settings.pricing.adjustments.len() {
+            return Err(PricingError::NoSuchAdjustment(index));
+        }
+        let before = self.settings.pricing.adjustments.clone();
+        let removed = self.settings.pricing.adjustments.remove(index);
+        self.record_adjustments_change(before);
+        self.log.record(
+            LogEvent::new(LogLevel::Info, "pricing", "adjustment removed")
+                .with_field("label", &removed.label),
+        );
+        Ok(removed)
+    }
+
+    /// Swap an adjustment with its neighbour; `up` moves it earlier in the pipeline.
+    pub fn move_adjustment(&mut self, index: usize, up: bool) -> Result<(), PricingError> {
+        let before = self.settings.pricing.adjustments.clone();
+        let adjustments = &mut self.settings.pricing.adjustments;
+        let target = if up {
+            index.checked_sub(1)
+        } else {
+            Some(index + 1)
+        };
+        match target {
+            Some(target) if index < adjustments.len() && target < adjustments.len() => {
+                adjustments.swap(index, target);
+                let label = adjustments[target].label.clone();
+                self.log.record(
+                    LogEvent::new(LogLevel::Info, "pricing", "adjustment moved")
+                        .with_field("label", label)
+                        .with_field("position", target + 1),
+                );
+                self.record_adjustments_change(before);
+                Ok(())
+            }
+            _ => Err(PricingError::NoSuchAdjustment(index)),
+        }
+    }
+
+    /// Update an adjustment's percentage or amount from user-entered text.
+    pub fn set_adjustment_value(&mut self, index: usize, value: &str) -> Result<(), PricingError> {
+        let before = self.settings.pricing.adjustments.clone();
+        let result = self.apply_adjustment_value(index, value);
+        let event = match &result {
+            Ok(()) => {
+                self.record_adjustments_change(before);
+                LogEvent::new(LogLevel::Info, "pricing", "adjustment value changed")
+            }
+            Err(err) => LogEvent::new(
+                LogLevel::Warn,
+                "pricing",
+                format!("adjustment value rejected: {err}"),
+            ),
+        };
+        self.log
+            .record(event.with_field("index", index).with_field("value", value));
+        result
+    }
+
+    /// Limit a tax adjustment to the lines of one category, or with `None`
+    /// charge it on the whole running total.
+    pub fn set_tax_category(
+        &mut self,
+        index: usize,
+        category: Option<&str>,
+    ) -> Result<(), PricingError> {
+        let before = self.settings.pricing.adjustments.clone();
+        let adjustment = self
+            .settings
+            .pricing
+            .adjustments
+            .get_mut(index)
+            .ok_or(PricingError::NoSuchAdjustment(index))?;
+        let AdjustmentKind::Tax {
+            category: current, ..
+        } = &mut adjustment.kind
+        else {
+            return Err(PricingError::NotATax(index));
+        };
+        let category = category
+            .map(str::trim)
+            .filter(|category| !category.is_empty());
+        if current.as_deref() == category {
+            return Ok(());
+        }
+        *current = category.map(str::to_string);
+        self.log.record(
+            LogEvent::new(LogLevel::Info, "pricing", "tax category changed")
+                .with_field("label", &adjustment.label)
+                .with_field("category", category.unwrap_or("all lines")),
+        );
+        self.record_adjustments_change(before);
+        Ok(())
+    }
+
+    fn record_adjustments_change(&mut self, before: Vec<Adjustment>) {
+        self.history.record(Change::Adjustments {
+            before,
+            after: self.settings.pricing.adjustments.clone(),
+        });
+    }
+
+    fn apply_adjustment_value(&mut self, index: usize, value: &str) -> Result<(), PricingError> {
+        let currency = self.settings.currency;
+        let adjustment = self
+            .settings
+            .pricing
+            .adjustments
+            .get_mut(index)
+            .ok_or(PricingError::NoSuchAdjustment(index))?;
+        let mut kind = adjustment.kind.clone();
+        match &mut kind {
+            AdjustmentKind::Markup(rate)
+            | AdjustmentKind::Discount(rate)
+            | AdjustmentKind::Tax { rate, .. } => *rate = value.parse()?,
+            AdjustmentKind::FixedFee(amount) => {
+                *amount = Money::parse(value, currency)
+                    .map_err(|_| PricingError::InvalidAmount(value.to_string()))?;
+            }
+        }
+        kind.check()?;
+        adjustment.kind = kind;
+        Ok(())
+    }
+
+    pub fn revisions(&self) -> &[QuoteRevision] {
+        &self.revisions
+    }
+
+    /// Record the current BoM and quoted total as the next quote revision,
+    /// issued on `issued_on`. Returns the revision number.
+    pub fn issue_revision(
+        &mut self,
+        issued_on: chrono::NaiveDate,
+        note: &str,
+    ) -> Result<u32, MoneyError> {
+        let revision = QuoteRevision {
+            number: self.revisions.last().map_or(1, |last| last.number + 1),
+            issued_on,
+            note: note.trim().to_string(),
+            bom: self.bom.clone(),
+            grand_total: self.price_breakdown()?.grand_total,
+        };
+        let number = revision.number;
+        self.log.record(
+            LogEvent::new(LogLevel::Info, "quote", "quote revision issued")
+                .with_field("revision", number)
+                .with_field("total", revision.grand_total),
+        );
+        self.history.record(Change::RevisionIssued {
+            revision: revision.clone(),
+        });
+        self.revisions.push(revision);
+        Ok(number)
+    }
+
+    /// Revision number the quote as it stands carries: the latest issued
+    /// revision while neither the BoM nor the total has changed since,
+    /// otherwise the one it would be issued as next.
+    pub fn quote_revision_number(&self) -> Result<u32, MoneyError> {
+        let Some(latest) = self.revisions.last() else {
+            return Ok(1);
+        };
+        let unchanged = latest.grand_total == self.price_breakdown()?.grand_total
+            && diff_bom(&latest.bom, &self.bom, self.settings.currency)?.is_empty();
+        Ok(if unchanged {
+            latest.number
+        } else {
+            latest.number + 1
+        })
+    }
+
+    /// Compare the BoMs of two revisions by number; `None` stands for the
+    /// current BoM.
+    pub fn compare_revisions(
+        &self,
+        from: Option<u32>,
+        to: Option<u32>,
+    ) -> Result<BomDiff, RevisionError> {
+        let bom = |number: Option<u32>| match number {
+            Some(number) => self
+                .revisions
+                .iter()
+                .find(|revision| revision.number == number)
+                .map(|revision| revision.bom.as_slice())
+                .ok_or(RevisionError::NoSuchRevision(number)),
+            None => Ok(self.bom.as_slice()),
+        };
+        Ok(diff_bom(bom(from)?, bom(to)?, self.settings.currency)?)
+    }
+
+    pub fn planning(&self) -> &PlanningSettings {
+        &self.planning
+    }
+
+    /// Set the installation date from user-entered `YYYY-MM-DD` text.
+    pub fn set_installation_date(&mut self, value: &str) -> Result<(), PlanningError> {
+        let date = parse_date(value)?;
+        self.history.record(Change::InstallationDate {
+            before: self.planning.installation_date,
+            after: Some(date),
+        });
+        self.planning.installation_date = Some(date);
+        self.log.record(
+            LogEvent::new(LogLevel::Info, "planning", "installation date set")
+                .with_field("date", date),
+        );
+        Ok(())
+    }
+
+    /// Mark `day` as a weekend day (or a working day) in our own calendar,
+    /// or in `supplier`'s when given.
+    pub fn set_weekend_day(&mut self, supplier: Option<&str>, day: chrono::Weekday, off: bool) {
+        self.edit_calendar(supplier, |calendar| calendar.set_weekend_day(day, off));
+        self.log.record(
+            LogEvent::new(LogLevel::Info, "planning", "weekend changed")
+                .with_field("calendar", supplier.unwrap_or("ours"))
+                .with_field("day", day)
+                .with_field("working", !off),
+        );
+    }
+
+    /// Add the holidays in an iCalendar or CSV file to our own calendar, or
+    /// to `supplier`'s when given. Returns how many new dates were added.
+    pub fn load_holidays<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        supplier: Option<&str>,
+    ) -> Result<usize, CalendarError> {
+        let path = path.as_ref();
+        let calendar_name = supplier.unwrap_or("ours").to_string();
+        let dates = match load_holidays(path) {
+            Ok(dates) => dates,
+            Err(err) => {
+                self.log.record(
+                    LogEvent::new(
+                        LogLevel::Error,
+                        "planning",
+                        format!("holidays not loaded: {err}"),
+                    )
+                    .with_field("calendar", calendar_name)
+                    .with_field("path", path.display()),
+                );
+                return Err(err);
+            }
+        };
+        let added = self.edit_calendar(supplier, |calendar| calendar.add_holidays(dates));
+        self.log.record(
+            LogEvent::new(LogLevel::Info, "planning", "holidays loaded")
+                .with_field("calendar", calendar_name)
+                .with_field("added", added)
+                .with_field("path", path.display()),
+        );
+        Ok(added)
+    }
+
+    /// Change our own calendar, or `supplier`'s (creating it if needed),
+    /// and record the change in the history.
+    fn edit_calendar<T>(
+        &mut self,
+        supplier: Option<&str>,
+        edit: impl FnOnce(&mut WorkingCalendar) -> T,
+    ) -> T {
+        let (key, before, calendar) = match supplier {
+            Some(supplier) => {
+                let before = self
+                    .planning
+                    .supplier_calendars
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(supplier))
+                    .map(|(_, calendar)| calendar.clone());
+                (
+                    Some(supplier.to_string()),
+                    before,
+                    self.planning.supplier_calendar_mut(supplier),
+                )
+            }
+            None => (
+                None,
+                Some(self.planning.calendar.clone()),
+                &mut self.planning.calendar,
+            ),
+        };
+        let result = edit(calendar);
+        let after = calendar.clone();
+        self.history.record(Change::Calendar {
+            supplier: key,
+            before,
+            after,
+        });
+        result
+    }
+
+    /// When each bought BoM line must be ordered, most urgent first, as of
+    /// `today`. Parts of an assembly must arrive in time for it to be built.
+    pub fn order_plan(&self, today: chrono::NaiveDate) -> Result<Vec<PlannedOrder>, MoneyError> {
+        let (indices, lines): (Vec<usize>, Vec<BomItem>) =
+            self.purchased_lines()?.into_iter().unzip();
+        let mut orders = plan_orders(&lines, &self.planning, today);
+        for order in &mut orders {
+            order.index = indices[order.index];
+        }
+        Ok(orders)
+    }
+
+    /// Suggested order quantities for the bought lines given MOQs and pack
+    /// sizes, in BoM order.
+    pub fn order_advice(&self) -> Result<Vec<OrderAdvice>, MoneyError> {
+        let (indices, lines): (Vec<usize>, Vec<BomItem>) =
+            self.purchased_lines()?.into_iter().unzip();
+        let mut advice = advise_orders(&lines, &self.settings.ordering, self.settings.rounding)?;
+        for line in &mut advice {
+            line.index = indices[line.index];
+        }
+        Ok(advice)
+    }
+
+    pub fn suppliers(&self) -> &SupplierCatalogue {
+        &self.suppliers
+    }
+
+    /// Add the suppliers in a catalogue CSV to the estimate's catalogue,
+    /// replacing the details of any already in it.
+    pub fn import_suppliers<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<SupplierImportReport, CatalogueError> {
+        let path = path.as_ref();
+        let report = match import_suppliers_from_csv(path, self.settings.currency) {
+            Ok(report) => report,
+            Err(err) => {
+                self.log.record(
+                    LogEvent::new(
+                        LogLevel::Error,
+                        "suppliers",
+                        format!("supplier catalogue not loaded: {err}"),
+                    )
+                    .with_field("path", path.display()),
+                );
+                return Err(err);
+            }
+        };
+        let before = self.suppliers.clone();
+        let added = self.suppliers.merge(report.suppliers.iter().cloned());
+        self.history.record(Change::Suppliers {
+            before,
+            after: self.suppliers.clone(),
+        });
+        self.log.record(
+            LogEvent::new(
+                report.severity().into(),
+                "suppliers",
+                format!("supplier catalogue: {}", report.summary()),
+            )
+            .with_field("added", added)
+            .with_field("path", path.display()),
+        );
+        for diagnostic in &report.diagnostics {
+            self.log.record(
+                LogEvent::new(
+                    diagnostic.severity.into(),
+                    "suppliers",
+                    diagnostic.to_string(),
+                )
+                .with_field("path", path.display()),
+            );
+        }
+        Ok(report)
+    }
+
+    /// The bought lines, with quantities for the whole estimate, grouped by
+    /// the supplier they name; see [`group_by_supplier`].
+    pub fn supplier_groups(&self) -> Result<Vec<SupplierGroup>, MoneyError> {
+        group_by_supplier(self.purchased_lines()?, self.settings.currency)
+    }
+
+    pub fn purchase_orders(&self) -> &[PurchaseOrder] {
+        &self.purchase_orders
+    }
+
+    /// One purchase order per supplier for what the bought lines still need
+    /// beyond the orders already issued, numbered on from those; see
+    /// [`draft_purchase_orders`].
+    pub fn draft_purchase_orders(
+        &self,
+        issued_on: chrono::NaiveDate,
+    ) -> Result<PurchaseOrderDraft, MoneyError> {
+        let inputs = PurchaseOrderInputs {
+            quote_number: &self.quote.number,
+            issued: &self.purchase_orders,
+            catalogue: &self.suppliers,
+            planning: &self.planning,
+            ordering: &self.settings.ordering,
+            currency: self.settings.currency,
+            rounding: self.settings.rounding,
+        };
+        let next = self
+            .purchase_orders
+            .last()
+            .map_or(1, |last| last.number + 1);
+        draft_purchase_orders(self.purchased_lines()?, &inputs, issued_on, next)
+    }
+
+    /// Draft purchase orders dated `issued_on`, write each as PDF and CSV
+    /// into `dir` and keep them as issued. Returns the orders issued.
+    ///
+    /// An order whose files already exist, e.g. from another estimate with
+    /// the same quote number, takes the next free number instead, so no
+    /// order is ever overwritten. Issuing cannot be undone: the files may
+    /// already be with the supplier. If writing fails part way, the orders
+    /// written before the failure are still kept as issued.
+    pub fn issue_purchase_orders<P: AsRef<Path>>(
+        &mut self,
+        dir: P,
+        issued_on: chrono::NaiveDate,
+    ) -> Result<Vec<PurchaseOrder>, PurchaseOrderError> {
+        let dir = dir.as_ref();
+        let draft = match self.draft_purchase_orders(issued_on) {
+            Ok(draft) => draft,
+            Err(err) => {
+                self.log.record(
+                    LogEvent::new(
+                        LogLevel::Error,
+                        "ordering",
+                        format!("purchase orders not issued: {err}"),
+                    )
+                    .with_field("path", dir.display()),
+                );
+                return Err(err.into());
+            }
+        };
+        for (reason, count) in draft.left_out_counts() {
+            let level = match reason {
+                LeftOutReason::AlreadyOrdered => LogLevel::Info,
+                _ => LogLevel::Warn,
+            };
+            self.log.record(
+                LogEvent::new(
+                    level,
+                    "ordering",
+                    format!("lines left off the purchase orders: {reason}"),
+                )
+                .with_field("lines", count),
+            );
+        }
+
+        let mut next = draft.orders.first().map_or(1, |order| order.number);
+        let mut issued = Vec::new();
+        let mut failure = None;
+        for mut order in draft.orders {
+            let written = loop {
+                order.number = next;
+                next += 1;
+                match write_purchase_order(&order, &self.quote, dir) {
+                    Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                        self.log.record(
+                            LogEvent::new(
+                                LogLevel::Warn,
+                                "ordering",
+                                "purchase order number already used; taking the next",
+                            )
+                            .with_field("number", order.reference())
+                            .with_field("path", dir.display()),
+                        );
+                    }
+                    written => break written,
+                }
+            };
+            if let Err(err) = written {
+                failure = Some(err);
+                break;
+            }
+            self.log.record(
+                LogEvent::new(LogLevel::Info, "ordering", "purchase order issued")
+                    .with_field("number", order.reference())
+                    .with_field("supplier", &order.supplier)
+                    .with_field("lines", order.lines.len())
+                    .with_field("total", order.total)
+                    .with_field("path", order.file_path(dir, "pdf").display()),
+            );
+            issued.push(order);
+        }
+        if !issued.is_empty() {
+            self.history.mark_unsaved();
+            self.purchase_orders.extend(issued.iter().cloned());
+        }
+        match failure {
+            Some(err) => {
+                self.log.record(
+                    LogEvent::new(
+                        LogLevel::Error,
+                        "ordering",
+                        format!("purchase orders not issued: {err}"),
+                    )
+                    .with_field("issued", issued.len())
+                    .with_field("path", dir.display()),
+                );
+                Err(err.into())
+            }
+            None => Ok(issued),
+        }
+    }
+
+    /// Set the surplus warning threshold from a user-entered percentage.
+    pub fn set_surplus_threshold(&mut self, value: &str) -> Result<(), PricingError> {
+        let rate: Rate = value.parse()?;
+        if rate.basis_points < 0 {
+            return Err(PricingError::InvalidRate(value.to_string()));
+        }
+        self.history.record(Change::SurplusThreshold {
+            before: self.settings.ordering.surplus_threshold,
+            after: rate,
+        });
+        self.settings.ordering.surplus_threshold = rate;
+        self.log.record(
+            LogEvent::new(LogLevel::Info, "ordering", "surplus threshold set")
+                .with_field("threshold", rate),
+        );
+        Ok(())
+    }
+
+    /// Reconciliation result for each BoM line, in the same order as [`AppState::bom`].
+    pub fn reconciliation(&self) -> &[LineReconciliation] {
+        &self.reconciliation
+    }
+
+    /// Outcome of the most recent BoM import, including rejected rows.
+    pub fn bom_import(&self) -> &BomImportReport {
+        &self.bom_import
+    }
+
     pub fn settings(&self) -> &SettingsSummary {
         &self.settings
     }
@@ -103,64 +1383,213 @@ impl AppState {
     pub fn advanced(&self) -> &AdvancedSummary {
         &self.advanced
     }
-}
 
-fn load_bom_from_csv<P: AsRef<Path>>(path: P) -> Result<Vec<BomItem>, csv::Error> {
-    let mut rdr = csv::ReaderBuilder::new()
-        .has_headers(true)
-        .from_path(path)?;
-
-    let mut items = Vec::new();
-    for result in rdr.records() {
-        let record = result?;
-        if record.len() < 6 {
-            continue;
-        }
-        let name = record[0].to_string();
-        let quantity = record[1].parse().unwrap_or(0);
-        let unit_cost = record[2].parse().unwrap_or(0.0);
-        let total_cost = record[3].parse().unwrap_or(0.0);
-        let lead_time_days = record[4].parse().unwrap_or(0);
-        let min_quantity = record[5].parse().unwrap_or(0);
-
-        items.push(BomItem {
-            name,
-            quantity,
-            unit_cost,
-            total_cost,
-            lead_time_days,
-            min_quantity,
+    /// Recent backend and UI events, oldest first.
+    pub fn log(&self) -> &LogStore {
+        &self.log
+    }
+
+    /// Changes that can be undone and redone.
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Start typing into one BoM cell. Values set on it until
+    /// [`AppState::end_cell_edit`] apply at once, so totals follow along,
+    /// but undo as one step and are logged once, as the final value.
+    pub fn begin_cell_edit(&mut self, index: usize, field: BomField) {
+        self.end_cell_edit();
+        self.history
+            .begin_group(format!("edit {field} on line {}", index + 1));
+        self.cell_edit = Some(CellEdit {
+            index,
+            field,
+            value: None,
         });
     }
 
-    Ok(items)
+    /// Finish the cell edit in progress, if any, logging it if it changed
+    /// the line. Returns whether it did.
+    pub fn end_cell_edit(&mut self) -> bool {
+        let Some(edit) = self.cell_edit.take() else {
+            return false;
+        };
+        self.history.end_group();
+        let Some(value) = edit.value else {
+            return false;
+        };
+        self.log.record(
+            LogEvent::new(LogLevel::Info, "bom", "line edited")
+                .with_field("line", edit.index + 1)
+                .with_field("field", edit.field)
+                .with_field("value", value),
+        );
+        self.reconcile_totals();
+        true
+    }
+
+    /// Forget all undo and redo steps, e.g. once a project has been loaded.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Revert the latest step; returns its label, or `None` if there was nothing to undo.
+    pub fn undo(&mut self) -> Option<String> {
+        let step = self.history.take_undo()?;
+        for change in step.changes.iter().rev() {
+            self.apply_change(change, true);
+        }
+        self.finish_history_step(&step, "undone");
+        Some(step.label)
+    }
+
+    /// Re-apply the latest undone step; returns its label, or `None` if there was nothing to redo.
+    pub fn redo(&mut self) -> Option<String> {
+        let step = self.history.take_redo()?;
+        for change in &step.changes {
+            self.apply_change(change, false);
+        }
+        self.finish_history_step(&step, "redone");
+        Some(step.label)
+    }
+
+    /// Put the `before` side of `change` back when `undo`, otherwise the `after` side.
+    fn apply_change(&mut self, change: &Change, undo: bool) {
+        match change {
+            Change::BomLine {
+                index,
+                before,
+                after,
+                ..
+            } => {
+                if let Some(item) = self.bom.get_mut(*index) {
+                    *item = BomItem::clone(if undo { before } else { after });
+                }
+            }
+            Change::BomLineInserted { index, item } | Change::BomLineRemoved { index, item } => {
+                let insert = matches!(change, Change::BomLineInserted { .. }) != undo;
+                if insert {
+                    self.bom.insert((*index).min(self.bom.len()), item.clone());
+                } else if *index < self.bom.len() {
+                    self.bom.remove(*index);
+                }
+            }
+            Change::Adjustments { before, after } => {
+                self.settings.pricing.adjustments = if undo { before } else { after }.clone();
+            }
+            Change::InstallationDate { before, after } => {
+                self.planning.installation_date = if undo { *before } else { *after };
+            }
+            Change::Calendar {
+                supplier,
+                before,
+                after,
+            } => {
+                let calendar = if undo {
+                    before.clone()
+                } else {
+                    Some(after.clone())
+                };
+                match (supplier, calendar) {
+                    (Some(supplier), Some(calendar)) => {
+                        *self.planning.supplier_calendar_mut(supplier) = calendar;
+                    }
+                    (Some(supplier), None) => {
+                        self.planning
+                            .supplier_calendars
+                            .retain(|name, _| !name.eq_ignore_ascii_case(supplier));
+                    }
+                    (None, Some(calendar)) => self.planning.calendar = calendar,
+                    (None, None) => {}
+                }
+            }
+            Change::SurplusThreshold { before, after } => {
+                self.settings.ordering.surplus_threshold = if undo { *before } else { *after };
+            }
+            Change::Suppliers { before, after } => {
+                self.suppliers = if undo { before } else { after }.clone();
+            }
+            Change::ReconcilePolicy { before, after } => {
+                self.apply_reconcile_policy(if undo { *before } else { *after });
+            }
+            Change::RevisionIssued { revision } => {
+                if undo {
+                    self.revisions
+                        .retain(|issued| issued.number != revision.number);
+                } else {
+                    self.revisions.push(revision.clone());
+                }
+            }
+        }
+    }
+
+    fn finish_history_step(&mut self, step: &HistoryStep, action: &str) {
+        if step.changes.iter().any(Change::touches_bom) {
+            self.reconcile_totals();
+        }
+        self.log.record(
+            LogEvent::new(
+                LogLevel::Info,
+                "history",
+                format!("{} {action}", step.label),
+            )
+            .with_field("changes", step.changes.len()),
+        );
+    }
+
+    /// Add an event to the in-app log, e.g. from the UI layer.
+    pub fn record(&mut self, event: LogEvent) {
+        self.log.record(event);
+    }
 }
 
 fn demo_bom() -> Vec<BomItem> {
+    let gbp = |pence| Money::from_minor(pence, Currency::GBP).expect("demo amounts are in range");
     vec![
         BomItem {
             name: "Steel frame sections".to_string(),
             quantity: 120,
-            unit_cost: 45.50,
-            total_cost: 5460.0,
+            unit_cost: gbp(4550),
+            total_cost: gbp(546000),
+            file_total: Some(gbp(546000)),
             lead_time_days: 21,
             min_quantity: 50,
+            category: None,
+            pack_size: None,
+            extra: BTreeMap::new(),
+            level: 0,
+            supplier: None,
+            part_number: None,
         },
         BomItem {
             name: "Electrical fixtures".to_string(),
             quantity: 80,
-            unit_cost: 32.00,
-            total_cost: 2560.0,
+            unit_cost: gbp(3200),
+            total_cost: gbp(256000),
+            file_total: Some(gbp(256000)),
             lead_time_days: 14,
             min_quantity: 40,
+            category: None,
+            pack_size: None,
+            extra: BTreeMap::new(),
+            level: 0,
+            supplier: None,
+            part_number: None,
         },
         BomItem {
             name: "Finishing materials".to_string(),
             quantity: 200,
-            unit_cost: 12.75,
-            total_cost: 2550.0,
+            unit_cost: gbp(1275),
+            total_cost: gbp(255000),
+            file_total: Some(gbp(255000)),
             lead_time_days: 10,
             min_quantity: 100,
+            category: None,
+            pack_size: None,
+            extra: BTreeMap::new(),
+            level: 0,
+            supplier: None,
+            part_number: None,
         },
     ]
 }
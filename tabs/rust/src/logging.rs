@@ -0,0 +1,234 @@
+use crate::import::Severity;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Events kept before the oldest are discarded.
+pub const DEFAULT_LOG_CAPACITY: usize = 1000;
+
+/// How important a log event is; ordered so a minimum level can filter with `>=`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 4] = [
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ];
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        };
+        f.write_str(label)
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LogLevel::ALL
+            .into_iter()
+            .find(|level| level.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("unknown log level \"{s}\""))
+    }
+}
+
+impl From<Severity> for LogLevel {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Info => LogLevel::Info,
+            Severity::Warning => LogLevel::Warn,
+            Severity::Error => LogLevel::Error,
+        }
+    }
+}
+
+/// One structured event: what happened, where, and the values involved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEvent {
+    /// Position in the store's history; keeps increasing after old events are dropped.
+    pub sequence: u64,
+    pub timestamp: DateTime<Local>,
+    pub level: LogLevel,
+    /// Subsystem that produced the event, e.g. `"import"` or `"quote"`.
+    pub target: String,
+    pub message: String,
+    /// Key/value details, in the order they were added.
+    pub fields: Vec<(String, String)>,
+}
+
+impl LogEvent {
+    /// An event timestamped now; the sequence number is assigned when it is recorded.
+    pub fn new(level: LogLevel, target: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            sequence: 0,
+            timestamp: Local::now(),
+            level,
+            target: target.into(),
+            message: message.into(),
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn with_field(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.fields.push((key.into(), value.to_string()));
+        self
+    }
+
+    /// Case-insensitive match of `needle` against target, message and fields.
+    pub fn contains(&self, needle: &str) -> bool {
+        let needle = needle.to_lowercase();
+        let hit = |haystack: &str| haystack.to_lowercase().contains(&needle);
+        hit(&self.target)
+            || hit(&self.message)
+            || self
+                .fields
+                .iter()
+                .any(|(key, value)| hit(key) || hit(value))
+    }
+}
+
+/// `2026-10-17 09:30:00.123 WARN  import: row rejected line=4 column=QTY`
+impl fmt::Display for LogEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:<5} {}: {}",
+            self.timestamp.format("%Y-%m-%d %H:%M:%S%.3f"),
+            self.level.to_string(),
+            self.target,
+            self.message
+        )?;
+        for (key, value) in &self.fields {
+            if value.contains(char::is_whitespace) {
+                write!(f, " {key}={value:?}")?;
+            } else {
+                write!(f, " {key}={value}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Which events to show or export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFilter {
+    pub min_level: LogLevel,
+    /// Case-insensitive text to look for; empty matches everything.
+    pub search: String,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            min_level: LogLevel::Debug,
+            search: String::new(),
+        }
+    }
+}
+
+impl LogFilter {
+    pub fn matches(&self, event: &LogEvent) -> bool {
+        event.level >= self.min_level
+            && (self.search.trim().is_empty() || event.contains(self.search.trim()))
+    }
+}
+
+/// Bounded in-memory history of log events; the oldest are dropped once full.
+#[derive(Debug, Clone)]
+pub struct LogStore {
+    capacity: usize,
+    events: VecDeque<LogEvent>,
+    next_sequence: u64,
+}
+
+impl Default for LogStore {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_LOG_CAPACITY)
+    }
+}
+
+impl LogStore {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            events: VecDeque::with_capacity(capacity.max(1)),
+            next_sequence: 0,
+        }
+    }
+
+    pub fn record(&mut self, mut event: LogEvent) {
+        event.sequence = self.next_sequence;
+        self.next_sequence += 1;
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Retained events, oldest first.
+    pub fn events(&self) -> impl DoubleEndedIterator<Item = &LogEvent> {
+        self.events.iter()
+    }
+
+    /// Retained events accepted by `filter`, oldest first.
+    pub fn query<'a>(
+        &'a self,
+        filter: &'a LogFilter,
+    ) -> impl DoubleEndedIterator<Item = &'a LogEvent> + 'a {
+        self.events
+            .iter()
+            .filter(move |event| filter.matches(event))
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Events discarded because the buffer was full.
+    pub fn dropped(&self) -> u64 {
+        self.next_sequence - self.events.len() as u64
+    }
+
+    /// Write the events accepted by `filter` to `path` as text, one per line.
+    /// Returns how many were written.
+    pub fn export(&self, path: &Path, filter: &LogFilter) -> std::io::Result<usize> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let mut written = 0;
+        for event in self.query(filter) {
+            writeln!(out, "{event}")?;
+            written += 1;
+        }
+        out.flush()?;
+        Ok(written)
+    }
+}
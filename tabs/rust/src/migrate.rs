@@ -0,0 +1,298 @@
+use crate::columns::normalize_header;
+use crate::project::PROJECT_VERSION;
+use serde_json::{json, Map, Value as JsonValue};
+use std::fmt;
+
+/// Oldest project format version that can still be read.
+pub const OLDEST_PROJECT_VERSION: u64 = 1;
+
+/// One step in the upgrade chain, turning a version `from` project document
+/// into a version `from + 1` one.
+///
+/// Steps work on the parsed JSON of the whole file (format, version, state
+/// and attachments) rather than on [`crate::AppState`], because the Rust
+/// types only describe the current version. Each step must leave a document
+/// that the next step, or the current types, can read.
+pub struct Migration {
+    pub from: u64,
+    /// What changed in the format, for the log.
+    pub description: &'static str,
+    apply: fn(&mut JsonValue) -> Result<(), MigrationError>,
+}
+
+/// Upgrade steps in version order, one per format change.
+///
+/// When a persisted type changes shape, bump [`PROJECT_VERSION`], append a
+/// step from the previous version here, and add a fixture saved by the
+/// previous build to `tests/fixtures`.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 1,
+        description: "keep issued quote revisions",
+        apply: add_quote_revisions,
+    },
+    Migration {
+        from: 2,
+        description: "nest BoM lines in assemblies",
+        apply: add_assembly_levels,
+    },
+    Migration {
+        from: 3,
+        description: "name suppliers and part numbers on BoM lines",
+        apply: add_supplier_fields,
+    },
+    Migration {
+        from: 4,
+        description: "keep issued purchase orders",
+        apply: add_purchase_orders,
+    },
+    Migration {
+        from: 5,
+        description: "keep supplied line totals apart from the totals in use",
+        apply: add_file_totals,
+    },
+];
+
+/// Error from upgrading a project document to the current format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationError {
+    /// The document predates the oldest readable version.
+    TooOld(u64),
+    /// The document was written by a newer build.
+    TooNew(u64),
+    /// No step upgrades documents from this version.
+    MissingStep(u64),
+    /// The document does not have the shape its version promises.
+    Malformed { version: u64, message: String },
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::TooOld(version) => write!(
+                f,
+                "project was saved in format version {version}; the oldest this build reads is {OLDEST_PROJECT_VERSION}"
+            ),
+            MigrationError::TooNew(version) => write!(
+                f,
+                "project was saved in format version {version}; this build reads up to {PROJECT_VERSION}"
+            ),
+            MigrationError::MissingStep(version) => {
+                write!(f, "no upgrade from project format version {version}")
+            }
+            MigrationError::Malformed { version, message } => {
+                write!(f, "project format version {version}: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+/// Upgrade a project document saved in format `version` to
+/// [`PROJECT_VERSION`], one step at a time, updating its `version` member as
+/// it goes. Returns the steps applied, oldest first.
+pub fn migrate_project(
+    document: &mut JsonValue,
+    version: u64,
+) -> Result<Vec<&'static Migration>, MigrationError> {
+    if version < OLDEST_PROJECT_VERSION {
+        return Err(MigrationError::TooOld(version));
+    }
+    if version > PROJECT_VERSION {
+        return Err(MigrationError::TooNew(version));
+    }
+
+    let mut applied = Vec::new();
+    for from in version..PROJECT_VERSION {
+        let step = MIGRATIONS
+            .iter()
+            .find(|step| step.from == from)
+            .ok_or(MigrationError::MissingStep(from))?;
+        (step.apply)(document)?;
+        set_member(document, "version", json!(from + 1));
+        applied.push(step);
+    }
+    Ok(applied)
+}
+
+/// Set `key` on a JSON object, replacing any existing member; values that
+/// are not objects are left alone.
+fn set_member(object: &mut JsonValue, key: &str, value: JsonValue) {
+    if let Some(members) = object.as_object_mut() {
+        members.insert(key.to_string(), value);
+    }
+}
+
+/// The state of a project document, which every version has.
+fn state_mut(document: &mut JsonValue, version: u64) -> Result<&mut JsonValue, MigrationError> {
+    document
+        .get_mut("state")
+        .filter(|state| state.is_object())
+        .ok_or_else(|| MigrationError::Malformed {
+            version,
+            message: "no estimate state".to_string(),
+        })
+}
+
+/// Version 2 records the quotes issued so far; older projects had none.
+fn add_quote_revisions(document: &mut JsonValue) -> Result<(), MigrationError> {
+    set_member(state_mut(document, 1)?, "revisions", json!([]));
+    Ok(())
+}
+
+/// Version 3 gives every BoM line, including those in issued revisions, an
+/// assembly level; older BoMs were flat.
+fn add_assembly_levels(document: &mut JsonValue) -> Result<(), MigrationError> {
+    let state = state_mut(document, 2)?;
+    if let Some(bom) = state.get_mut("bom") {
+        set_top_level(bom)?;
+    }
+    let revisions = state.get_mut("revisions").and_then(JsonValue::as_array_mut);
+    for revision in revisions.into_iter().flatten() {
+        if let Some(bom) = revision.get_mut("bom") {
+            set_top_level(bom)?;
+        }
+    }
+    Ok(())
+}
+
+fn set_top_level(bom: &mut JsonValue) -> Result<(), MigrationError> {
+    let lines = bom
+        .as_array_mut()
+        .ok_or_else(|| MigrationError::Malformed {
+            version: 2,
+            message: "BoM is not a list of lines".to_string(),
+        })?;
+    for line in lines {
+        set_member(line, "level", json!(0));
+    }
+    Ok(())
+}
+
+/// Headers that older imports kept as extra attributes but that version 4
+/// reads into a line's supplier and part number.
+const SUPPLIER_HEADERS: [&str; 3] = ["supplier", "vendor", "supplier name"];
+const PART_NUMBER_HEADERS: [&str; 5] = [
+    "part number",
+    "part no",
+    "sku",
+    "supplier part number",
+    "mpn",
+];
+
+/// Version 4 gives BoM lines, including those in issued revisions, a
+/// supplier and part number of their own, taken from the extra columns
+/// they used to be kept in, and adds an empty supplier catalogue.
+fn add_supplier_fields(document: &mut JsonValue) -> Result<(), MigrationError> {
+    let state = state_mut(document, 3)?;
+    if let Some(bom) = state.get_mut("bom") {
+        move_supplier_columns(bom)?;
+    }
+    let revisions = state.get_mut("revisions").and_then(JsonValue::as_array_mut);
+    for revision in revisions.into_iter().flatten() {
+        if let Some(bom) = revision.get_mut("bom") {
+            move_supplier_columns(bom)?;
+        }
+    }
+    set_member(state, "suppliers", json!({ "suppliers": [] }));
+    Ok(())
+}
+
+fn move_supplier_columns(bom: &mut JsonValue) -> Result<(), MigrationError> {
+    let lines = bom
+        .as_array_mut()
+        .ok_or_else(|| MigrationError::Malformed {
+            version: 3,
+            message: "BoM is not a list of lines".to_string(),
+        })?;
+    for line in lines {
+        let (supplier, part_number) = match line.get_mut("extra") {
+            Some(JsonValue::Object(columns)) => (
+                take_column(columns, &SUPPLIER_HEADERS),
+                take_column(columns, &PART_NUMBER_HEADERS),
+            ),
+            _ => (None, None),
+        };
+        set_member(line, "supplier", supplier.unwrap_or(JsonValue::Null));
+        set_member(line, "part_number", part_number.unwrap_or(JsonValue::Null));
+    }
+    Ok(())
+}
+
+/// Remove the first extra column whose header is one of `names` and that
+/// holds some text, returning its value.
+fn take_column(columns: &mut Map<String, JsonValue>, names: &[&str]) -> Option<JsonValue> {
+    let header = columns
+        .iter()
+        .find(|(header, value)| {
+            let header = normalize_header(header);
+            names.iter().any(|name| normalize_header(name) == header)
+                && value.as_str().is_some_and(|value| !value.trim().is_empty())
+        })?
+        .0
+        .clone();
+    let value = columns.shift_remove(&header)?;
+    let value = value
+        .as_str()
+        .map(str::trim)
+        .unwrap_or_default()
+        .to_string();
+    Some(JsonValue::String(value))
+}
+
+/// Version 5 records the purchase orders issued so far; older projects had none.
+fn add_purchase_orders(document: &mut JsonValue) -> Result<(), MigrationError> {
+    set_member(state_mut(document, 4)?, "purchase_orders", json!([]));
+    Ok(())
+}
+
+/// Version 6 keeps the total supplied in the BoM file on each line, apart
+/// from the total in use. Version 5 kept it only in the last reconciliation,
+/// so it is taken from there, or from the line's own total when the
+/// reconciliation has nothing for the line; issued revisions never had one.
+fn add_file_totals(document: &mut JsonValue) -> Result<(), MigrationError> {
+    let state = state_mut(document, 5)?;
+    let reconciled: Vec<(u64, JsonValue)> = state
+        .get("reconciliation")
+        .and_then(JsonValue::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|line| {
+            let index = line.get("index")?.as_u64()?;
+            Some((index, line.get("file_total")?.clone()))
+        })
+        .collect();
+    if let Some(bom) = state.get_mut("bom") {
+        copy_file_totals(bom, &reconciled)?;
+    }
+    let revisions = state.get_mut("revisions").and_then(JsonValue::as_array_mut);
+    for revision in revisions.into_iter().flatten() {
+        if let Some(bom) = revision.get_mut("bom") {
+            copy_file_totals(bom, &[])?;
+        }
+    }
+    Ok(())
+}
+
+fn copy_file_totals(
+    bom: &mut JsonValue,
+    reconciled: &[(u64, JsonValue)],
+) -> Result<(), MigrationError> {
+    let lines = bom
+        .as_array_mut()
+        .ok_or_else(|| MigrationError::Malformed {
+            version: 5,
+            message: "BoM is not a list of lines".to_string(),
+        })?;
+    for (index, line) in (0u64..).zip(lines) {
+        let file_total = reconciled
+            .iter()
+            .find(|(line_index, _)| *line_index == index)
+            .map(|(_, total)| total.clone())
+            .or_else(|| line.get("total_cost").cloned())
+            .unwrap_or(JsonValue::Null);
+        set_member(line, "file_total", file_total);
+    }
+    Ok(())
+}
@@ -0,0 +1,413 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// Decimal places held internally by [`Money`].
+///
+/// Four places keeps supplier unit prices such as 0.0125 exact while
+/// quoted totals are rounded to the currency's two minor-unit places.
+pub const MONEY_SCALE: u32 = 4;
+const SCALE_FACTOR: i64 = 10_i64.pow(MONEY_SCALE);
+
+/// Decimal places used when presenting amounts to customers.
+pub const DISPLAY_DECIMALS: u32 = 2;
+
+/// Three-letter ISO 4217 currency code, e.g. `GBP`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+    pub const GBP: Currency = Currency(*b"GBP");
+    pub const EUR: Currency = Currency(*b"EUR");
+    pub const USD: Currency = Currency(*b"USD");
+
+    pub fn code(&self) -> &str {
+        // Construction guarantees three ASCII uppercase letters.
+        std::str::from_utf8(&self.0).unwrap_or("???")
+    }
+
+    /// Symbol used when formatting amounts, falling back to the code.
+    pub fn symbol(&self) -> Option<&'static str> {
+        match &self.0 {
+            b"GBP" => Some("£"),
+            b"EUR" => Some("€"),
+            b"USD" => Some("$"),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Currency {
+    fn default() -> Self {
+        Currency::GBP
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for Currency {
+    type Err = MoneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.trim().to_ascii_uppercase();
+        let bytes: [u8; 3] = code
+            .as_bytes()
+            .try_into()
+            .map_err(|_| MoneyError::InvalidCurrency(s.to_string()))?;
+        if !bytes.iter().all(u8::is_ascii_uppercase) {
+            return Err(MoneyError::InvalidCurrency(s.to_string()));
+        }
+        Ok(Currency(bytes))
+    }
+}
+
+impl TryFrom<String> for Currency {
+    type Error = MoneyError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Currency> for String {
+    fn from(currency: Currency) -> Self {
+        currency.code().to_string()
+    }
+}
+
+/// How to resolve a value that falls between two representable amounts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoundingMode {
+    /// Ties round away from zero (2.345 -> 2.35).
+    #[default]
+    HalfUp,
+    /// Ties round to the nearest even digit (2.345 -> 2.34), a.k.a. bankers' rounding.
+    HalfEven,
+    /// Always round towards zero.
+    Truncate,
+}
+
+impl fmt::Display for RoundingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            RoundingMode::HalfUp => "half up",
+            RoundingMode::HalfEven => "half even (bankers')",
+            RoundingMode::Truncate => "truncate",
+        };
+        f.write_str(label)
+    }
+}
+
+/// Divide `numerator` by a positive `denominator`, rounding the quotient with `mode`.
+fn div_round(numerator: i128, denominator: i128, mode: RoundingMode) -> i128 {
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+    if remainder == 0 {
+        return quotient;
+    }
+    let away = if numerator < 0 { -1 } else { 1 };
+    let twice = remainder.abs() * 2;
+    let round_away = match mode {
+        RoundingMode::Truncate => false,
+        RoundingMode::HalfUp => twice >= denominator,
+        RoundingMode::HalfEven => match twice.cmp(&denominator) {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            Ordering::Equal => quotient % 2 != 0,
+        },
+    };
+    if round_away {
+        quotient + away
+    } else {
+        quotient
+    }
+}
+
+/// Error from parsing or combining monetary amounts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoneyError {
+    InvalidAmount(String),
+    InvalidCurrency(String),
+    TooPrecise(String),
+    CurrencyMismatch(Currency, Currency),
+    Overflow,
+}
+
+impl fmt::Display for MoneyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoneyError::InvalidAmount(raw) => write!(f, "\"{raw}\" is not a valid amount"),
+            MoneyError::InvalidCurrency(raw) => write!(f, "\"{raw}\" is not a currency code"),
+            MoneyError::TooPrecise(raw) => {
+                write!(f, "\"{raw}\" has more than {MONEY_SCALE} decimal places")
+            }
+            MoneyError::CurrencyMismatch(a, b) => {
+                write!(f, "cannot combine amounts in {a} and {b}")
+            }
+            MoneyError::Overflow => f.write_str("amount is too large"),
+        }
+    }
+}
+
+impl std::error::Error for MoneyError {}
+
+/// An exact monetary amount in a single currency.
+///
+/// Stored as a signed count of ten-thousandths of the currency unit, so
+/// additions and multiplication by quantities never drift.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "MoneyRepr", into = "MoneyRepr")]
+pub struct Money {
+    units: i64,
+    currency: Currency,
+}
+
+impl Money {
+    pub fn zero(currency: Currency) -> Self {
+        Self { units: 0, currency }
+    }
+
+    /// Amount from whole minor units, e.g. pence: `Money::from_minor(2429, GBP)` is £24.29.
+    pub fn from_minor(minor: i64, currency: Currency) -> Result<Self, MoneyError> {
+        let units = minor
+            .checked_mul(SCALE_FACTOR / 10_i64.pow(DISPLAY_DECIMALS))
+            .ok_or(MoneyError::Overflow)?;
+        Ok(Self { units, currency })
+    }
+
+    /// Parse a plain decimal string such as `"24.29"` or `"-3.5"`.
+    pub fn parse(raw: &str, currency: Currency) -> Result<Self, MoneyError> {
+        let trimmed = raw.trim();
+        let invalid = || MoneyError::InvalidAmount(raw.to_string());
+        let (negative, digits) = match trimmed.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(invalid());
+        }
+        if !whole.chars().all(|c| c.is_ascii_digit())
+            || !fraction.chars().all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.len() > MONEY_SCALE as usize {
+            return Err(MoneyError::TooPrecise(raw.to_string()));
+        }
+
+        let whole: i64 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().map_err(|_| MoneyError::Overflow)?
+        };
+        let fraction_units: i64 = if fraction.is_empty() {
+            0
+        } else {
+            let padded = format!("{fraction:0<width$}", width = MONEY_SCALE as usize);
+            padded.parse().map_err(|_| invalid())?
+        };
+        let units = whole
+            .checked_mul(SCALE_FACTOR)
+            .and_then(|w| w.checked_add(fraction_units))
+            .ok_or(MoneyError::Overflow)?;
+        Ok(Self {
+            units: if negative { -units } else { units },
+            currency,
+        })
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.units == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.units < 0
+    }
+
+    /// The raw fixed-point value in ten-thousandths of the currency unit.
+    pub fn scaled_units(&self) -> i64 {
+        self.units
+    }
+
+    fn same_currency(&self, other: &Money) -> Result<(), MoneyError> {
+        if self.currency == other.currency {
+            Ok(())
+        } else {
+            Err(MoneyError::CurrencyMismatch(self.currency, other.currency))
+        }
+    }
+
+    pub fn checked_add(self, other: Money) -> Result<Money, MoneyError> {
+        self.same_currency(&other)?;
+        let units = self
+            .units
+            .checked_add(other.units)
+            .ok_or(MoneyError::Overflow)?;
+        Ok(Money { units, ..self })
+    }
+
+    pub fn checked_sub(self, other: Money) -> Result<Money, MoneyError> {
+        self.same_currency(&other)?;
+        let units = self
+            .units
+            .checked_sub(other.units)
+            .ok_or(MoneyError::Overflow)?;
+        Ok(Money { units, ..self })
+    }
+
+    /// Exact product with a whole quantity, e.g. a line total.
+    pub fn checked_mul_quantity(self, quantity: u32) -> Result<Money, MoneyError> {
+        let units = self
+            .units
+            .checked_mul(i64::from(quantity))
+            .ok_or(MoneyError::Overflow)?;
+        Ok(Money { units, ..self })
+    }
+
+    /// Multiply by the fraction `numerator / denominator`, rounding to the internal scale.
+    ///
+    /// Used for percentages: 17.5% is `checked_mul_ratio(175, 1000, mode)`.
+    pub fn checked_mul_ratio(
+        self,
+        numerator: i64,
+        denominator: i64,
+        mode: RoundingMode,
+    ) -> Result<Money, MoneyError> {
+        if denominator == 0 {
+            return Err(MoneyError::Overflow);
+        }
+        let (numerator, denominator) = if denominator < 0 {
+            (-i128::from(numerator), -i128::from(denominator))
+        } else {
+            (i128::from(numerator), i128::from(denominator))
+        };
+        let product = i128::from(self.units) * numerator;
+        let units = div_round(product, denominator, mode);
+        let units = i64::try_from(units).map_err(|_| MoneyError::Overflow)?;
+        Ok(Money { units, ..self })
+    }
+
+    pub fn checked_neg(self) -> Result<Money, MoneyError> {
+        let units = self.units.checked_neg().ok_or(MoneyError::Overflow)?;
+        Ok(Money { units, ..self })
+    }
+
+    /// Round to `decimals` places (at most [`MONEY_SCALE`]).
+    pub fn round(self, decimals: u32, mode: RoundingMode) -> Money {
+        let decimals = decimals.min(MONEY_SCALE);
+        let step = 10_i128.pow(MONEY_SCALE - decimals);
+        let rounded = div_round(i128::from(self.units), step, mode) * step;
+        Money {
+            // Rounding to fewer places cannot grow the magnitude past i64 by more than one step.
+            units: i64::try_from(rounded).unwrap_or(self.units),
+            ..self
+        }
+    }
+
+    /// Absolute difference between two amounts in the same currency.
+    pub fn abs_diff(self, other: Money) -> Result<Money, MoneyError> {
+        let diff = self.checked_sub(other)?;
+        if diff.is_negative() {
+            diff.checked_neg()
+        } else {
+            Ok(diff)
+        }
+    }
+
+    /// Plain decimal string without symbol, e.g. `"24.29"` or `"0.0125"`.
+    ///
+    /// Always shows at least the display decimals and never loses precision.
+    pub fn amount_string(&self) -> String {
+        let sign = if self.units < 0 { "-" } else { "" };
+        let abs = self.units.unsigned_abs();
+        let whole = abs / SCALE_FACTOR as u64;
+        let fraction = format!(
+            "{:0width$}",
+            abs % SCALE_FACTOR as u64,
+            width = MONEY_SCALE as usize
+        );
+        let mut fraction = fraction.trim_end_matches('0').to_string();
+        while fraction.len() < DISPLAY_DECIMALS as usize {
+            fraction.push('0');
+        }
+        format!("{sign}{whole}.{fraction}")
+    }
+
+    /// Sum amounts, starting from zero in `currency`.
+    pub fn sum<'a, I>(currency: Currency, amounts: I) -> Result<Money, MoneyError>
+    where
+        I: IntoIterator<Item = &'a Money>,
+    {
+        amounts
+            .into_iter()
+            .try_fold(Money::zero(currency), |acc, m| acc.checked_add(*m))
+    }
+}
+
+impl PartialOrd for Money {
+    /// Amounts in different currencies are not comparable.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (self.currency == other.currency).then(|| self.units.cmp(&other.units))
+    }
+}
+
+/// Formats with the currency symbol, rounded half-up to two places, e.g. `£1,143.00`.
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rounded = self.round(DISPLAY_DECIMALS, RoundingMode::HalfUp);
+        let amount = rounded.amount_string();
+        let (sign, amount) = match amount.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", amount.as_str()),
+        };
+        let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+        let fraction = &fraction[..DISPLAY_DECIMALS as usize];
+        let mut grouped = String::new();
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+        match self.currency.symbol() {
+            Some(symbol) => write!(f, "{sign}{symbol}{grouped}.{fraction}"),
+            None => write!(f, "{sign}{grouped}.{fraction} {}", self.currency),
+        }
+    }
+}
+
+/// Serialised form: the amount as an exact decimal string plus currency code.
+#[derive(Serialize, Deserialize)]
+struct MoneyRepr {
+    amount: String,
+    currency: Currency,
+}
+
+impl From<Money> for MoneyRepr {
+    fn from(money: Money) -> Self {
+        Self {
+            amount: money.amount_string(),
+            currency: money.currency,
+        }
+    }
+}
+
+impl TryFrom<MoneyRepr> for Money {
+    type Error = MoneyError;
+
+    fn try_from(repr: MoneyRepr) -> Result<Self, Self::Error> {
+        Money::parse(&repr.amount, repr.currency)
+    }
+}
@@ -0,0 +1,87 @@
+use crate::money::{Money, MoneyError, RoundingMode, DISPLAY_DECIMALS};
+use crate::pricing::Rate;
+use crate::BomItem;
+use serde::{Deserialize, Serialize};
+
+/// When ordering more than the BoM needs deserves a second look.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderingPolicy {
+    /// Surplus above this share of the required quantity is flagged.
+    pub surplus_threshold: Rate,
+}
+
+impl Default for OrderingPolicy {
+    fn default() -> Self {
+        Self {
+            surplus_threshold: Rate::from_percent(25),
+        }
+    }
+}
+
+/// Recommended order for one BoM line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderAdvice {
+    /// Index of the line in the BoM.
+    pub index: usize,
+    pub name: String,
+    /// Quantity the BoM needs.
+    pub required: u32,
+    pub min_quantity: u32,
+    pub pack_size: u32,
+    /// How far `required` falls short of the minimum order quantity (0 if it doesn't).
+    pub moq_shortfall: u32,
+    /// Smallest quantity meeting both the requirement and the MOQ, in whole packs.
+    pub suggested: u32,
+    /// `suggested - required`.
+    pub surplus: u32,
+    /// Cost of the surplus at the line's unit cost, rounded to two places.
+    pub surplus_cost: Money,
+    /// Surplus exceeds the policy threshold.
+    pub flagged: bool,
+}
+
+/// Per-line order quantities that satisfy MOQs and pack sizes, with the
+/// extra cost of rounding up.
+///
+/// Quantities that would not fit in a `u32` after rounding up to a whole
+/// pack are capped at `u32::MAX`.
+pub fn advise_orders(
+    items: &[BomItem],
+    policy: &OrderingPolicy,
+    rounding: RoundingMode,
+) -> Result<Vec<OrderAdvice>, MoneyError> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let pack_size = item.pack_size.unwrap_or(1).max(1);
+            let needed = item.quantity.max(item.min_quantity);
+            let suggested = needed.div_ceil(pack_size).saturating_mul(pack_size);
+            let surplus = suggested - item.quantity;
+            let surplus_cost = item
+                .unit_cost
+                .checked_mul_quantity(surplus)?
+                .round(DISPLAY_DECIMALS, rounding);
+
+            // surplus / required > threshold, compared exactly in basis points.
+            let flagged = surplus > 0
+                && (item.quantity == 0
+                    || i128::from(surplus) * 10_000
+                        > i128::from(item.quantity)
+                            * i128::from(policy.surplus_threshold.basis_points));
+
+            Ok(OrderAdvice {
+                index,
+                name: item.name.clone(),
+                required: item.quantity,
+                min_quantity: item.min_quantity,
+                pack_size,
+                moq_shortfall: item.min_quantity.saturating_sub(item.quantity),
+                suggested,
+                surplus,
+                surplus_cost,
+                flagged,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,199 @@
+use crate::calendar::WorkingCalendar;
+use crate::money::Money;
+use crate::BomItem;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Inputs for turning lead times into order dates.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanningSettings {
+    /// The day materials must be on site.
+    pub installation_date: Option<NaiveDate>,
+    /// Orders due within this many working days are flagged as due soon.
+    pub due_soon_working_days: u32,
+    /// Our own calendar: orders are placed and installs happen on its working days.
+    pub calendar: WorkingCalendar,
+    /// Calendars lead times are counted in, by supplier name; suppliers
+    /// without one use `calendar`.
+    #[serde(default)]
+    pub supplier_calendars: BTreeMap<String, WorkingCalendar>,
+}
+
+impl Default for PlanningSettings {
+    fn default() -> Self {
+        Self {
+            installation_date: None,
+            due_soon_working_days: 5,
+            calendar: WorkingCalendar::default(),
+            supplier_calendars: BTreeMap::new(),
+        }
+    }
+}
+
+impl PlanningSettings {
+    /// The calendar a supplier's lead times are counted in (matched ignoring case).
+    pub fn calendar_for(&self, supplier: Option<&str>) -> &WorkingCalendar {
+        supplier
+            .and_then(|supplier| {
+                self.supplier_calendars
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(supplier))
+                    .map(|(_, calendar)| calendar)
+            })
+            .unwrap_or(&self.calendar)
+    }
+
+    /// The calendar for `supplier`, created from our own weekend if it has none yet.
+    pub fn supplier_calendar_mut(&mut self, supplier: &str) -> &mut WorkingCalendar {
+        let key = self
+            .supplier_calendars
+            .keys()
+            .find(|name| name.eq_ignore_ascii_case(supplier))
+            .cloned()
+            .unwrap_or_else(|| supplier.to_string());
+        let weekend = self.calendar.weekend.clone();
+        self.supplier_calendars
+            .entry(key)
+            .or_insert_with(|| WorkingCalendar {
+                weekend,
+                ..Default::default()
+            })
+    }
+}
+
+/// How pressing an order is, most urgent first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum OrderUrgency {
+    /// The order-by date has passed; the installation date is at risk.
+    Overdue,
+    /// Must be ordered within the due-soon window.
+    DueSoon,
+    Scheduled,
+}
+
+impl fmt::Display for OrderUrgency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            OrderUrgency::Overdue => "overdue",
+            OrderUrgency::DueSoon => "due soon",
+            OrderUrgency::Scheduled => "scheduled",
+        };
+        f.write_str(label)
+    }
+}
+
+/// When one BoM line has to be ordered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedOrder {
+    /// Index of the line in the BoM.
+    pub index: usize,
+    pub name: String,
+    pub supplier: Option<String>,
+    pub quantity: u32,
+    pub min_quantity: u32,
+    pub lead_time_days: u32,
+    /// Last working day an order can be placed and still arrive by installation.
+    pub order_by: NaiveDate,
+    /// When the order can actually go in: `order_by`, or the next working
+    /// day from today once that has passed.
+    pub order_on: NaiveDate,
+    /// Delivery date if ordered on `order_on`; after installation when overdue.
+    pub expected_delivery: NaiveDate,
+    pub total_cost: Money,
+    /// Working days from today until `order_by`; negative once overdue.
+    pub working_days_left: i64,
+    pub urgency: OrderUrgency,
+}
+
+/// Error from editing planning inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanningError {
+    InvalidDate(String),
+}
+
+impl fmt::Display for PlanningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanningError::InvalidDate(raw) => {
+                write!(f, "\"{raw}\" is not a date in the form YYYY-MM-DD")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PlanningError {}
+
+/// Parse a user-entered `YYYY-MM-DD` date.
+pub fn parse_date(raw: &str) -> Result<NaiveDate, PlanningError> {
+    NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d")
+        .map_err(|_| PlanningError::InvalidDate(raw.to_string()))
+}
+
+/// Order-by dates for every BoM line, most urgent first.
+///
+/// Lead times count working days in the supplier's calendar (see
+/// [`PlanningSettings::calendar_for`]): a line with a 10-day lead time must be
+/// ordered 10 supplier working days before installation, moved back to one of
+/// our own working days if that falls on our weekend or a holiday. Lines are
+/// sorted by order-by date, then by name. Returns nothing until
+/// an installation date has been set.
+pub fn plan_orders(
+    items: &[BomItem],
+    settings: &PlanningSettings,
+    today: NaiveDate,
+) -> Vec<PlannedOrder> {
+    let Some(installation) = settings.installation_date else {
+        return Vec::new();
+    };
+    let calendar = &settings.calendar;
+
+    let mut orders: Vec<PlannedOrder> = items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let supplier = item.supplier();
+            let supplier_calendar = settings.calendar_for(supplier);
+            let order_by = calendar.previous_working_day(
+                supplier_calendar.subtract_working_days(installation, item.lead_time_days),
+            );
+            let order_on = if order_by < today {
+                calendar.next_working_day(today)
+            } else {
+                order_by
+            };
+            let expected_delivery =
+                supplier_calendar.add_working_days(order_on, item.lead_time_days);
+            let working_days_left = calendar.working_days_between(today, order_by);
+            let urgency = if order_by < today {
+                OrderUrgency::Overdue
+            } else if working_days_left <= i64::from(settings.due_soon_working_days) {
+                OrderUrgency::DueSoon
+            } else {
+                OrderUrgency::Scheduled
+            };
+            PlannedOrder {
+                index,
+                name: item.name.clone(),
+                supplier: supplier.map(str::to_string),
+                quantity: item.quantity,
+                min_quantity: item.min_quantity,
+                lead_time_days: item.lead_time_days,
+                order_by,
+                order_on,
+                expected_delivery,
+                total_cost: item.total_cost,
+                working_days_left,
+                urgency,
+            }
+        })
+        .collect();
+
+    orders.sort_by(|a, b| {
+        a.order_by
+            .cmp(&b.order_by)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    orders
+}
@@ -0,0 +1,293 @@
+use crate::money::{Currency, Money, MoneyError, RoundingMode, DISPLAY_DECIMALS};
+use crate::BomItem;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A percentage held exactly as basis points (hundredths of a percent).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Rate {
+    pub basis_points: i64,
+}
+
+impl Rate {
+    pub fn from_percent(percent: i64) -> Self {
+        Self {
+            basis_points: percent * 100,
+        }
+    }
+
+    /// `amount × rate`, rounded to the money scale.
+    pub fn apply(&self, amount: Money, rounding: RoundingMode) -> Result<Money, MoneyError> {
+        amount.checked_mul_ratio(self.basis_points, 10_000, rounding)
+    }
+}
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.basis_points < 0 { "-" } else { "" };
+        let abs = self.basis_points.unsigned_abs();
+        let fraction = abs % 100;
+        if fraction == 0 {
+            write!(f, "{sign}{}%", abs / 100)
+        } else {
+            let fraction = format!("{fraction:02}");
+            write!(f, "{sign}{}.{}%", abs / 100, fraction.trim_end_matches('0'))
+        }
+    }
+}
+
+/// Parses `"17.5"` or `"17.5%"`; at most two decimal places.
+impl FromStr for Rate {
+    type Err = PricingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim().trim_end_matches('%').trim();
+        // Reuse the money parser for exact decimal handling, then scale to basis points.
+        let value = Money::parse(trimmed, Currency::default())
+            .map_err(|_| PricingError::InvalidRate(s.to_string()))?;
+        let units = value.scaled_units();
+        if units % 100 != 0 {
+            return Err(PricingError::InvalidRate(s.to_string()));
+        }
+        Ok(Rate {
+            basis_points: units / 100,
+        })
+    }
+}
+
+/// One step of the pricing pipeline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AdjustmentKind {
+    /// Increase the running total by a percentage (margin / overhead).
+    Markup(Rate),
+    /// Add a fixed amount (delivery, site visit, ...).
+    FixedFee(Money),
+    /// Reduce the running total by a percentage.
+    Discount(Rate),
+    /// Tax on the running total, or only on the share attributable to one category.
+    Tax {
+        rate: Rate,
+        category: Option<String>,
+    },
+}
+
+impl AdjustmentKind {
+    pub fn kind_label(&self) -> &'static str {
+        match self {
+            AdjustmentKind::Markup(_) => "Markup",
+            AdjustmentKind::FixedFee(_) => "Fixed fee",
+            AdjustmentKind::Discount(_) => "Discount",
+            AdjustmentKind::Tax { .. } => "Tax",
+        }
+    }
+
+    /// Reject values that would make the quote go backwards: negative
+    /// markups, fees and taxes, and discounts outside 0–100%.
+    pub fn check(&self) -> Result<(), PricingError> {
+        let out_of_range =
+            |value: String, expected| Err(PricingError::OutOfRange { value, expected });
+        match self {
+            AdjustmentKind::Markup(rate) if rate.basis_points < 0 => {
+                out_of_range(rate.to_string(), "a markup of 0% or more")
+            }
+            AdjustmentKind::Discount(rate) if !(0..=10_000).contains(&rate.basis_points) => {
+                out_of_range(rate.to_string(), "a discount from 0% to 100%")
+            }
+            AdjustmentKind::Tax { rate, .. } if rate.basis_points < 0 => {
+                out_of_range(rate.to_string(), "a tax rate of 0% or more")
+            }
+            AdjustmentKind::FixedFee(amount) if amount.is_negative() => {
+                out_of_range(amount.amount_string(), "a fee of zero or more")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The editable value as text: a percentage or an amount.
+    pub fn value_text(&self) -> String {
+        match self {
+            AdjustmentKind::Markup(rate)
+            | AdjustmentKind::Discount(rate)
+            | AdjustmentKind::Tax { rate, .. } => rate.to_string(),
+            AdjustmentKind::FixedFee(amount) => amount.amount_string(),
+        }
+    }
+}
+
+/// A named, ordered adjustment applied after the BoM subtotal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Adjustment {
+    pub label: String,
+    pub kind: AdjustmentKind,
+}
+
+/// Ordered adjustments turning a BoM subtotal into a quoted grand total.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PricingRules {
+    pub adjustments: Vec<Adjustment>,
+}
+
+impl PricingRules {
+    /// Typical UK rules: 15% markup, a delivery charge, then VAT at 20%.
+    pub fn standard(currency: Currency) -> Self {
+        Self {
+            adjustments: vec![
+                Adjustment {
+                    label: "Overheads & margin".to_string(),
+                    kind: AdjustmentKind::Markup(Rate::from_percent(15)),
+                },
+                Adjustment {
+                    label: "Delivery".to_string(),
+                    kind: AdjustmentKind::FixedFee(
+                        Money::from_minor(15_000, currency).expect("£150 is in range"),
+                    ),
+                },
+                Adjustment {
+                    label: "VAT".to_string(),
+                    kind: AdjustmentKind::Tax {
+                        rate: Rate::from_percent(20),
+                        category: None,
+                    },
+                },
+            ],
+        }
+    }
+}
+
+/// The effect of one adjustment on the running total.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingLine {
+    pub label: String,
+    pub kind: AdjustmentKind,
+    /// Amount added (negative for discounts), rounded to two places.
+    pub amount: Money,
+    pub running_total: Money,
+}
+
+/// Subtotal, each adjustment and the grand total for a BoM.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceBreakdown {
+    pub subtotal: Money,
+    pub lines: Vec<PricingLine>,
+    pub grand_total: Money,
+}
+
+/// Error from configuring or running the pricing pipeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PricingError {
+    InvalidRate(String),
+    InvalidAmount(String),
+    NoSuchAdjustment(usize),
+    /// Only tax adjustments can be limited to a category.
+    NotATax(usize),
+    /// A value the adjustment cannot take, with what it can.
+    OutOfRange {
+        value: String,
+        expected: &'static str,
+    },
+    Money(MoneyError),
+}
+
+impl fmt::Display for PricingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PricingError::InvalidRate(raw) => {
+                write!(f, "\"{raw}\" is not a percentage with at most two decimals")
+            }
+            PricingError::InvalidAmount(raw) => write!(f, "\"{raw}\" is not an amount"),
+            PricingError::NoSuchAdjustment(index) => write!(f, "no adjustment at {index}"),
+            PricingError::NotATax(index) => write!(f, "adjustment at {index} is not a tax"),
+            PricingError::OutOfRange { value, expected } => {
+                write!(f, "{value} is out of range (expected {expected})")
+            }
+            PricingError::Money(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for PricingError {}
+
+impl From<MoneyError> for PricingError {
+    fn from(err: MoneyError) -> Self {
+        PricingError::Money(err)
+    }
+}
+
+/// Apply `rules` to the BoM line totals.
+///
+/// Adjustments run in order against the running total. A category-specific
+/// tax is charged on that category's share of the running total, i.e. the
+/// running total scaled by (category line totals / subtotal). Every
+/// adjustment amount is rounded to two places with `rounding`.
+pub fn price_bom(
+    items: &[BomItem],
+    rules: &PricingRules,
+    currency: Currency,
+    rounding: RoundingMode,
+) -> Result<PriceBreakdown, MoneyError> {
+    let subtotal = Money::sum(currency, items.iter().map(|item| &item.total_cost))?
+        .round(DISPLAY_DECIMALS, rounding);
+    let mut running_total = subtotal;
+    let mut lines = Vec::with_capacity(rules.adjustments.len());
+
+    for adjustment in &rules.adjustments {
+        let amount = match &adjustment.kind {
+            AdjustmentKind::Markup(rate) => rate.apply(running_total, rounding)?,
+            AdjustmentKind::FixedFee(fee) => *fee,
+            AdjustmentKind::Discount(rate) => rate.apply(running_total, rounding)?.checked_neg()?,
+            AdjustmentKind::Tax { rate, category } => {
+                let base = match category {
+                    None => running_total,
+                    Some(category) => {
+                        category_share(items, category, subtotal, running_total, rounding)?
+                    }
+                };
+                rate.apply(base, rounding)?
+            }
+        }
+        .round(DISPLAY_DECIMALS, rounding);
+
+        running_total = running_total.checked_add(amount)?;
+        lines.push(PricingLine {
+            label: adjustment.label.clone(),
+            kind: adjustment.kind.clone(),
+            amount,
+            running_total,
+        });
+    }
+
+    Ok(PriceBreakdown {
+        subtotal,
+        lines,
+        grand_total: running_total,
+    })
+}
+
+fn category_share(
+    items: &[BomItem],
+    category: &str,
+    subtotal: Money,
+    running_total: Money,
+    rounding: RoundingMode,
+) -> Result<Money, MoneyError> {
+    if subtotal.is_zero() {
+        return Ok(Money::zero(subtotal.currency()));
+    }
+    let in_category = Money::sum(
+        subtotal.currency(),
+        items
+            .iter()
+            .filter(|item| {
+                item.category
+                    .as_deref()
+                    .is_some_and(|c| c.eq_ignore_ascii_case(category))
+            })
+            .map(|item| &item.total_cost),
+    )?;
+    running_total.checked_mul_ratio(
+        in_category.scaled_units(),
+        subtotal.scaled_units(),
+        rounding,
+    )
+}
@@ -0,0 +1,162 @@
+use crate::migrate::{migrate_project, MigrationError};
+use crate::AppState;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Local};
+use serde_json::{json, Value as JsonValue};
+use std::fmt;
+use std::path::Path;
+
+/// Identifies a file as an estimate project, whatever its extension.
+pub const PROJECT_FORMAT: &str = "tabs-estimate";
+
+/// Format version written by this build; older files are upgraded on
+/// reading (see [`crate::migrate_project`]).
+pub const PROJECT_VERSION: u64 = 6;
+
+/// Extension used for project files.
+pub const PROJECT_EXTENSION: &str = "tabs.json";
+
+/// Everything in a project file.
+///
+/// The file is a JSON object with the format name, schema version and save
+/// time, the persisted [`AppState`] (BoM, settings, quote details and
+/// planning inputs) and, base64-encoded, the quote PDF as it stood when the
+/// project was saved.
+#[derive(Debug, Clone)]
+pub struct ProjectFile {
+    pub state: AppState,
+    /// Format version the file was saved in, before any upgrade.
+    pub format_version: u64,
+    pub saved_at: Option<DateTime<Local>>,
+    pub quote_pdf: Option<Vec<u8>>,
+}
+
+/// Error from reading or writing a project file.
+#[derive(Debug)]
+pub enum ProjectError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    /// The file is JSON but not a project.
+    NotAProject,
+    /// The file's format version cannot be upgraded to the current one.
+    Migration(MigrationError),
+    /// The attached quote PDF is not valid base64.
+    InvalidAttachment,
+}
+
+impl fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectError::Io(err) => write!(f, "could not access project file: {err}"),
+            ProjectError::Json(err) => write!(f, "could not read project file: {err}"),
+            ProjectError::NotAProject => f.write_str("file is not an estimate project"),
+            ProjectError::Migration(err) => err.fmt(f),
+            ProjectError::InvalidAttachment => f.write_str("attached quote PDF is corrupt"),
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProjectError::Io(err) => Some(err),
+            ProjectError::Json(err) => Some(err),
+            ProjectError::Migration(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ProjectError {
+    fn from(err: std::io::Error) -> Self {
+        ProjectError::Io(err)
+    }
+}
+
+impl From<MigrationError> for ProjectError {
+    fn from(err: MigrationError) -> Self {
+        ProjectError::Migration(err)
+    }
+}
+
+impl From<serde_json::Error> for ProjectError {
+    fn from(err: serde_json::Error) -> Self {
+        ProjectError::Json(err)
+    }
+}
+
+/// Serialize `state` (and the quote PDF, if any) as project file text.
+pub fn project_to_string(
+    state: &AppState,
+    saved_at: DateTime<Local>,
+    quote_pdf: Option<&[u8]>,
+) -> Result<String, ProjectError> {
+    let document = json!({
+        "format": PROJECT_FORMAT,
+        "version": PROJECT_VERSION,
+        "saved_at": saved_at,
+        "state": serde_json::to_value(state)?,
+        "quote_pdf": quote_pdf.map(|pdf| BASE64.encode(pdf)),
+    });
+    Ok(format!("{}\n", serde_json::to_string_pretty(&document)?))
+}
+
+/// Read project file text written by [`project_to_string`] in this or any
+/// earlier format version.
+pub fn project_from_str(source: &str) -> Result<ProjectFile, ProjectError> {
+    let mut document: JsonValue = serde_json::from_str(source)?;
+    if document.get("format").and_then(JsonValue::as_str) != Some(PROJECT_FORMAT) {
+        return Err(ProjectError::NotAProject);
+    }
+    let format_version = document
+        .get("version")
+        .and_then(JsonValue::as_u64)
+        .ok_or(ProjectError::NotAProject)?;
+    migrate_project(&mut document, format_version)?;
+
+    let state = match document.get_mut("state") {
+        Some(state) => serde_json::from_value(state.take())?,
+        None => return Err(ProjectError::NotAProject),
+    };
+    let saved_at = serde_json::from_value(document["saved_at"].take())?;
+    let quote_pdf = match document.get("quote_pdf").and_then(JsonValue::as_str) {
+        Some(encoded) => Some(decode_attachment(encoded)?),
+        None => None,
+    };
+    Ok(ProjectFile {
+        state,
+        format_version,
+        saved_at,
+        quote_pdf,
+    })
+}
+
+/// Write a project file, creating its directory if needed.
+pub fn save_project(
+    path: &Path,
+    state: &AppState,
+    quote_pdf: Option<&[u8]>,
+) -> Result<(), ProjectError> {
+    let text = project_to_string(state, Local::now(), quote_pdf)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, text)?;
+    Ok(())
+}
+
+pub fn load_project(path: &Path) -> Result<ProjectFile, ProjectError> {
+    project_from_str(&std::fs::read_to_string(path)?)
+}
+
+/// Decode the attached quote PDF, ignoring any line breaks in its base64.
+fn decode_attachment(encoded: &str) -> Result<Vec<u8>, ProjectError> {
+    let symbols: String = encoded
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect();
+    BASE64
+        .decode(symbols)
+        .map_err(|_| ProjectError::InvalidAttachment)
+}
@@ -0,0 +1,590 @@
+use crate::money::{Currency, Money, MoneyError, RoundingMode, DISPLAY_DECIMALS};
+use crate::ordering::{advise_orders, OrderingPolicy};
+use crate::planning::PlanningSettings;
+use crate::quote::{
+    truncate_to_width, wrap, write_pdf, Align, Draw, Layout, QuoteDetails, BODY_SIZE, MARGIN,
+    PAGE_WIDTH, ROW_HEIGHT, TOTAL_RIGHT,
+};
+use crate::suppliers::{group_by_supplier, Supplier, SupplierCatalogue};
+use crate::BomItem;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// Columns of the order line table, in PDF points.
+const PART_X: f32 = 215.0;
+const QTY_RIGHT: f32 = 340.0;
+const UNIT_RIGHT: f32 = 410.0;
+const LINE_TOTAL_RIGHT: f32 = 480.0;
+const NAME_MAX_WIDTH: f32 = 160.0;
+const PART_MAX_WIDTH: f32 = 85.0;
+// The "Deliver to" block sits in the right half of the page.
+const DELIVER_TO_X: f32 = 320.0;
+
+/// One line of a purchase order.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PurchaseOrderLine {
+    /// Index of the line in the BoM it was ordered from.
+    pub index: usize,
+    pub name: String,
+    pub part_number: Option<String>,
+    /// Quantity the BoM still needed beyond earlier orders when this one was drafted.
+    pub required: u32,
+    /// Quantity ordered: the requirement raised to the MOQ, in whole packs.
+    pub quantity: u32,
+    pub unit_cost: Money,
+    /// `quantity` × `unit_cost`, rounded to two places.
+    pub total: Money,
+    pub lead_time_days: u32,
+    /// When the line should arrive if the order goes in on its order date.
+    pub delivery: NaiveDate,
+}
+
+/// An order for the lines bought from one supplier.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PurchaseOrder {
+    /// Quote number of the estimate the order was issued from.
+    pub quote_number: String,
+    /// 1 for the first order issued from the estimate, counting up.
+    pub number: u32,
+    /// The supplier as its lines name it.
+    pub supplier: String,
+    /// The supplier's catalogue entry when the order was drafted, if it had one.
+    pub details: Option<Supplier>,
+    pub issued_on: NaiveDate,
+    /// First of our working days on or after `issued_on`; lead times run from it.
+    pub order_on: NaiveDate,
+    pub lines: Vec<PurchaseOrderLine>,
+    pub total: Money,
+}
+
+impl PurchaseOrder {
+    /// The order number as printed, e.g. "Q-0001-PO003": unique across
+    /// estimates as long as their quote numbers are.
+    pub fn reference(&self) -> String {
+        format!("{}-PO{:03}", self.quote_number, self.number)
+    }
+
+    /// When the whole order should have arrived: its latest line delivery.
+    pub fn delivery(&self) -> Option<NaiveDate> {
+        self.lines.iter().map(|line| line.delivery).max()
+    }
+
+    /// Where the PDF (`extension` "pdf") or CSV ("csv") of the order is written in `dir`.
+    pub fn file_path(&self, dir: &Path, extension: &str) -> PathBuf {
+        dir.join(format!("{}.{extension}", self.reference()))
+    }
+}
+
+/// Why a bought line is on none of the drafted orders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LeftOutReason {
+    /// The line names no supplier to order from.
+    NoSupplier,
+    /// Issued orders already cover the whole quantity.
+    AlreadyOrdered,
+    /// The supplier invoices in another currency than the estimate is priced in.
+    SupplierCurrency(Currency),
+}
+
+impl fmt::Display for LeftOutReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LeftOutReason::NoSupplier => f.write_str("names no supplier"),
+            LeftOutReason::AlreadyOrdered => f.write_str("is already on an issued order"),
+            LeftOutReason::SupplierCurrency(currency) => {
+                write!(f, "is bought from a supplier invoicing in {currency}")
+            }
+        }
+    }
+}
+
+/// A bought line that is not on any drafted order.
+#[derive(Debug, Clone)]
+pub struct LeftOutLine {
+    /// Index of the line in the BoM.
+    pub index: usize,
+    pub name: String,
+    pub reason: LeftOutReason,
+}
+
+/// Purchase orders drafted from a BoM, and the lines left out of them.
+#[derive(Debug, Clone, Default)]
+pub struct PurchaseOrderDraft {
+    /// One per supplier, in supplier name order.
+    pub orders: Vec<PurchaseOrder>,
+    /// In supplier name order, then the lines with no supplier.
+    pub left_out: Vec<LeftOutLine>,
+}
+
+impl PurchaseOrderDraft {
+    /// How many lines were left out for each reason, in reason order.
+    pub fn left_out_counts(&self) -> Vec<(LeftOutReason, usize)> {
+        let mut counts: Vec<(LeftOutReason, usize)> = Vec::new();
+        for line in &self.left_out {
+            match counts.iter_mut().find(|(reason, _)| *reason == line.reason) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((line.reason, 1)),
+            }
+        }
+        counts.sort();
+        counts
+    }
+}
+
+/// Failure while generating purchase orders.
+#[derive(Debug)]
+pub enum PurchaseOrderError {
+    Pricing(MoneyError),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for PurchaseOrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PurchaseOrderError::Pricing(err) => {
+                write!(f, "could not price the purchase orders: {err}")
+            }
+            PurchaseOrderError::Io(err) => {
+                write!(f, "could not write the purchase orders: {err}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PurchaseOrderError {}
+
+impl From<MoneyError> for PurchaseOrderError {
+    fn from(err: MoneyError) -> Self {
+        PurchaseOrderError::Pricing(err)
+    }
+}
+
+impl From<std::io::Error> for PurchaseOrderError {
+    fn from(err: std::io::Error) -> Self {
+        PurchaseOrderError::Io(err)
+    }
+}
+
+/// Everything that shapes the purchase orders drafted from a BoM.
+#[derive(Debug, Clone, Copy)]
+pub struct PurchaseOrderInputs<'a> {
+    /// Printed in each order's reference.
+    pub quote_number: &'a str,
+    /// Orders issued earlier; what they cover is not ordered again.
+    pub issued: &'a [PurchaseOrder],
+    pub catalogue: &'a SupplierCatalogue,
+    pub planning: &'a PlanningSettings,
+    pub ordering: &'a OrderingPolicy,
+    pub currency: Currency,
+    pub rounding: RoundingMode,
+}
+
+/// Group `lines` (BoM index and line) by supplier and draft one purchase
+/// order per supplier, numbered from `first_number` in supplier name order.
+///
+/// Each line is ordered for the quantity that earlier issued orders for the
+/// same supplier, material and part number do not already cover, raised to
+/// its MOQ and rounded up to whole packs (see [`advise_orders`]). Where the
+/// BoM has a material more than once, what was issued covers its lines in
+/// BoM order, each only once. Orders go
+/// in on the first of our working days from `issued_on`, and each line is
+/// due its lead time later in the supplier's working days.
+///
+/// Lines are left out when they name no supplier, are already fully
+/// ordered, or come from a catalogue supplier invoicing in a currency other
+/// than `inputs.currency`: their prices are in the estimate's currency and
+/// must not be sent as the supplier's. Lines with nothing to order are dropped.
+pub fn draft_purchase_orders(
+    lines: impl IntoIterator<Item = (usize, BomItem)>,
+    inputs: &PurchaseOrderInputs<'_>,
+    issued_on: NaiveDate,
+    first_number: u32,
+) -> Result<PurchaseOrderDraft, MoneyError> {
+    let order_on = inputs.planning.calendar.next_working_day(issued_on);
+    let mut draft = PurchaseOrderDraft::default();
+    for group in group_by_supplier(lines, inputs.currency)? {
+        let leave_out = |lines: Vec<(usize, BomItem)>, reason| {
+            lines.into_iter().map(move |(index, item)| LeftOutLine {
+                index,
+                name: item.name,
+                reason,
+            })
+        };
+        let Some(supplier) = group.supplier else {
+            draft
+                .left_out
+                .extend(leave_out(group.lines, LeftOutReason::NoSupplier));
+            continue;
+        };
+        let details = inputs.catalogue.find(&supplier).cloned();
+        if let Some(currency) = details
+            .as_ref()
+            .map(|details| details.currency)
+            .filter(|currency| *currency != inputs.currency)
+        {
+            draft.left_out.extend(leave_out(
+                group.lines,
+                LeftOutReason::SupplierCurrency(currency),
+            ));
+            continue;
+        }
+        let calendar = inputs.planning.calendar_for(Some(&supplier));
+
+        let mut outstanding = Vec::new();
+        // What the issued orders cover of each material not yet taken up by a line.
+        let mut uncovered: BTreeMap<(String, Option<String>), u32> = BTreeMap::new();
+        for (index, mut item) in group.lines {
+            let cover = uncovered
+                .entry((item.name.clone(), item.part_number.clone()))
+                .or_insert_with(|| already_ordered(inputs.issued, &supplier, &item));
+            let ordered = (*cover).min(item.quantity);
+            *cover -= ordered;
+            if ordered == 0 {
+                outstanding.push((index, item));
+            } else if ordered >= item.quantity {
+                draft.left_out.extend(leave_out(
+                    vec![(index, item)],
+                    LeftOutReason::AlreadyOrdered,
+                ));
+            } else {
+                item.quantity -= ordered;
+                item.total_cost = item
+                    .unit_cost
+                    .checked_mul_quantity(item.quantity)?
+                    .round(DISPLAY_DECIMALS, inputs.rounding);
+                outstanding.push((index, item));
+            }
+        }
+        let (indices, items): (Vec<usize>, Vec<BomItem>) = outstanding.into_iter().unzip();
+        let advice = advise_orders(&items, inputs.ordering, inputs.rounding)?;
+
+        let mut lines = Vec::new();
+        let mut total = Money::zero(inputs.currency);
+        for (item, advice) in items.iter().zip(&advice) {
+            if advice.suggested == 0 {
+                continue;
+            }
+            let line_total = item
+                .unit_cost
+                .checked_mul_quantity(advice.suggested)?
+                .round(DISPLAY_DECIMALS, inputs.rounding);
+            total = total.checked_add(line_total)?;
+            lines.push(PurchaseOrderLine {
+                index: indices[advice.index],
+                name: item.name.clone(),
+                part_number: item.part_number.clone(),
+                required: advice.required,
+                quantity: advice.suggested,
+                unit_cost: item.unit_cost,
+                total: line_total,
+                lead_time_days: item.lead_time_days,
+                delivery: calendar.add_working_days(order_on, item.lead_time_days),
+            });
+        }
+        if lines.is_empty() {
+            continue;
+        }
+        draft.orders.push(PurchaseOrder {
+            quote_number: inputs.quote_number.to_string(),
+            number: first_number + draft.orders.len() as u32,
+            details,
+            supplier,
+            issued_on,
+            order_on,
+            lines,
+            total,
+        });
+    }
+    Ok(draft)
+}
+
+/// How much of `item` the `issued` orders to `supplier` already cover.
+fn already_ordered(issued: &[PurchaseOrder], supplier: &str, item: &BomItem) -> u32 {
+    issued
+        .iter()
+        .filter(|order| order.supplier.eq_ignore_ascii_case(supplier))
+        .flat_map(|order| &order.lines)
+        .filter(|line| line.name == item.name && line.part_number == item.part_number)
+        .fold(0, |total: u32, line| total.saturating_add(line.quantity))
+}
+
+/// Render a purchase order as PDF bytes, from us (the quote template's
+/// company) to the supplier, for delivery to the customer's site.
+pub fn render_purchase_order_pdf(order: &PurchaseOrder, quote: &QuoteDetails) -> Vec<u8> {
+    let pages = layout(order, quote);
+    let title = format!("Purchase order {}", order.reference());
+    write_pdf(&title, &quote.template.footer, &pages)
+}
+
+/// Write `order` to a new CSV file at `path`, one row per line, and return
+/// how many lines were written. An existing file is never overwritten.
+pub fn write_purchase_order_csv(order: &PurchaseOrder, path: &Path) -> std::io::Result<usize> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_new(path, &render_purchase_order_csv(order)?)?;
+    Ok(order.lines.len())
+}
+
+fn render_purchase_order_csv(order: &PurchaseOrder) -> std::io::Result<Vec<u8>> {
+    let mut out = csv::Writer::from_writer(Vec::new());
+    out.write_record([
+        "PO number",
+        "Supplier",
+        "Material",
+        "Part number",
+        "Required",
+        "Quantity",
+        "Unit cost",
+        "Line total",
+        "Lead time days",
+        "Delivery",
+    ])?;
+    for line in &order.lines {
+        out.write_record([
+            order.reference(),
+            order.supplier.clone(),
+            line.name.clone(),
+            line.part_number.clone().unwrap_or_default(),
+            line.required.to_string(),
+            line.quantity.to_string(),
+            line.unit_cost.amount_string(),
+            line.total.amount_string(),
+            line.lead_time_days.to_string(),
+            line.delivery.format("%Y-%m-%d").to_string(),
+        ])?;
+    }
+    out.into_inner().map_err(|err| err.into_error())
+}
+
+/// Write the PDF and CSV of `order` into `dir`, creating it if needed.
+///
+/// Orders already sent must never be replaced, so this fails with
+/// [`std::io::ErrorKind::AlreadyExists`], writing nothing, when a file of
+/// the same name is already there. Both files are rendered before either is
+/// written, and a PDF whose CSV cannot be written is removed again, so a
+/// failed order can be retried under the same number.
+pub fn write_purchase_order(
+    order: &PurchaseOrder,
+    quote: &QuoteDetails,
+    dir: &Path,
+) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let (pdf_path, csv_path) = (order.file_path(dir, "pdf"), order.file_path(dir, "csv"));
+    if let Some(taken) = [&pdf_path, &csv_path]
+        .into_iter()
+        .find(|path| path.exists())
+    {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("{} already exists", taken.display()),
+        ));
+    }
+    let pdf = render_purchase_order_pdf(order, quote);
+    let csv = render_purchase_order_csv(order)?;
+    write_new(&pdf_path, &pdf)?;
+    write_new(&csv_path, &csv).inspect_err(|_| {
+        // Best effort: the error that stopped the order matters more.
+        let _ = std::fs::remove_file(&pdf_path);
+    })
+}
+
+/// Write `bytes` to a new file at `path`, removing it again if the write
+/// does not complete.
+fn write_new(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    create_new(path)?.write_all(bytes).inspect_err(|_| {
+        let _ = std::fs::remove_file(path);
+    })
+}
+
+fn create_new(path: &Path) -> std::io::Result<std::fs::File> {
+    OpenOptions::new().write(true).create_new(true).open(path)
+}
+
+fn layout(order: &PurchaseOrder, quote: &QuoteDetails) -> Vec<Vec<Draw>> {
+    let template = &quote.template;
+    let customer = &quote.customer;
+    let mut page = Layout::new();
+
+    // Our details on the left, the order's identity on the right.
+    page.text(MARGIN, 18.0, true, Align::Left, &template.company_name);
+    page.text(TOTAL_RIGHT, 18.0, true, Align::Right, "Purchase order");
+    page.advance(18.0);
+    let mut identity = vec![
+        format!("Order {}", order.reference()),
+        format!("Date {}", order.issued_on.format("%d %b %Y")),
+    ];
+    if let Some(delivery) = order.delivery() {
+        identity.push(format!("Deliver by {}", delivery.format("%d %b %Y")));
+    }
+    identity.push(format!("Our reference {}", quote.number));
+    let company_lines = template
+        .company_address
+        .iter()
+        .chain(std::iter::once(&template.company_contact));
+    let mut identity_lines = identity.iter();
+    for line in company_lines {
+        page.text(MARGIN, 9.0, false, Align::Left, line);
+        if let Some(id) = identity_lines.next() {
+            page.text(TOTAL_RIGHT, 9.0, false, Align::Right, id);
+        }
+        page.advance(12.0);
+    }
+    for id in identity_lines {
+        page.text(TOTAL_RIGHT, 9.0, false, Align::Right, id);
+        page.advance(12.0);
+    }
+    page.advance(18.0);
+
+    // Supplier on the left, delivery address on the right.
+    page.text(MARGIN, 9.0, true, Align::Left, "Supplier");
+    page.text(DELIVER_TO_X, 9.0, true, Align::Left, "Deliver to");
+    page.advance(14.0);
+    page.text(MARGIN, 11.0, true, Align::Left, &order.supplier);
+    page.text(DELIVER_TO_X, 11.0, true, Align::Left, &customer.name);
+    page.advance(14.0);
+    let supplier_lines: Vec<String> = order
+        .details
+        .iter()
+        .flat_map(|details| {
+            [
+                details
+                    .contact
+                    .clone()
+                    .map(|contact| format!("Attn: {contact}")),
+                details.email.clone(),
+                details.phone.clone(),
+            ]
+        })
+        .flatten()
+        .collect();
+    let mut site_lines = customer.address.clone();
+    if !customer.reference.is_empty() {
+        site_lines.push(format!("Site: {}", customer.reference));
+    }
+    for row in 0..supplier_lines.len().max(site_lines.len()) {
+        if let Some(line) = supplier_lines.get(row) {
+            page.text(MARGIN, BODY_SIZE, false, Align::Left, line);
+        }
+        if let Some(line) = site_lines.get(row) {
+            page.text(DELIVER_TO_X, BODY_SIZE, false, Align::Left, line);
+        }
+        page.advance(13.0);
+    }
+    page.advance(16.0);
+
+    // Order lines, repeating the table header on every page.
+    table_header(&mut page);
+    for line in &order.lines {
+        if !page.fits(ROW_HEIGHT) {
+            page.new_page();
+            page.text(
+                MARGIN,
+                9.0,
+                false,
+                Align::Left,
+                format!("Purchase order {} (continued)", order.reference()),
+            );
+            page.advance(20.0);
+            table_header(&mut page);
+        }
+        page.text(
+            MARGIN,
+            BODY_SIZE,
+            false,
+            Align::Left,
+            truncate_to_width(&line.name, BODY_SIZE, NAME_MAX_WIDTH),
+        );
+        if let Some(part_number) = &line.part_number {
+            page.text(
+                PART_X,
+                BODY_SIZE,
+                false,
+                Align::Left,
+                truncate_to_width(part_number, BODY_SIZE, PART_MAX_WIDTH),
+            );
+        }
+        page.text(
+            QTY_RIGHT,
+            BODY_SIZE,
+            false,
+            Align::Right,
+            line.quantity.to_string(),
+        );
+        page.text(
+            UNIT_RIGHT,
+            BODY_SIZE,
+            false,
+            Align::Right,
+            line.unit_cost.to_string(),
+        );
+        page.text(
+            LINE_TOTAL_RIGHT,
+            BODY_SIZE,
+            false,
+            Align::Right,
+            line.total.to_string(),
+        );
+        page.text(
+            TOTAL_RIGHT,
+            BODY_SIZE,
+            false,
+            Align::Right,
+            line.delivery.format("%d %b %Y").to_string(),
+        );
+        page.advance(ROW_HEIGHT);
+    }
+
+    if !page.fits(ROW_HEIGHT * 5.0) {
+        page.new_page();
+    }
+    page.advance(4.0);
+    page.rule();
+    page.advance(14.0);
+    page.text(UNIT_RIGHT, BODY_SIZE, true, Align::Right, "Total");
+    page.text(
+        LINE_TOTAL_RIGHT,
+        BODY_SIZE,
+        true,
+        Align::Right,
+        order.total.to_string(),
+    );
+    page.advance(ROW_HEIGHT + 20.0);
+
+    let note = format!(
+        "Please quote {} on all delivery notes and invoices, and tell us straight away if any line cannot be delivered by the date shown.",
+        order.reference()
+    );
+    for line in wrap(&note, 9.0, PAGE_WIDTH - 2.0 * MARGIN) {
+        if !page.fits(12.0) {
+            page.new_page();
+        }
+        page.text(MARGIN, 9.0, false, Align::Left, line);
+        page.advance(12.0);
+    }
+
+    page.pages
+}
+
+fn table_header(page: &mut Layout) {
+    page.text(MARGIN, BODY_SIZE, true, Align::Left, "Item");
+    page.text(PART_X, BODY_SIZE, true, Align::Left, "Part no.");
+    page.text(QTY_RIGHT, BODY_SIZE, true, Align::Right, "Qty");
+    page.text(UNIT_RIGHT, BODY_SIZE, true, Align::Right, "Unit price");
+    page.text(
+        LINE_TOTAL_RIGHT,
+        BODY_SIZE,
+        true,
+        Align::Right,
+        "Line total",
+    );
+    page.text(TOTAL_RIGHT, BODY_SIZE, true, Align::Right, "Delivery");
+    page.advance(5.0);
+    page.rule();
+    page.advance(ROW_HEIGHT - 3.0);
+}
@@ -0,0 +1,230 @@
+use crate::columns::BomField;
+use crate::money::Money;
+use crate::reconcile::{LineReconciliation, LineStatus};
+use crate::BomItem;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Column a BoM view can be ordered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BomSortKey {
+    Field(BomField),
+    /// Reconciliation outcome, mismatches last, then by size of the difference.
+    TotalCheck,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+/// Which BoM lines to show or export, and in what order.
+///
+/// Range bounds are inclusive and either end may be left open. Lines
+/// whose costs are in another currency than a bound never match it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BomQuery {
+    /// Case-insensitive text to look for in the material name; empty matches everything.
+    pub name: String,
+    pub min_total: Option<Money>,
+    pub max_total: Option<Money>,
+    pub min_lead_time_days: Option<u32>,
+    pub max_lead_time_days: Option<u32>,
+    /// `None` keeps the BoM's own order.
+    pub sort: Option<(BomSortKey, SortDirection)>,
+}
+
+impl BomQuery {
+    pub fn matches(&self, item: &BomItem) -> bool {
+        let name = self.name.trim();
+        let within = |value: Money, bound: Option<Money>, ordering: Ordering| {
+            bound.is_none_or(|bound| {
+                value
+                    .partial_cmp(&bound)
+                    .is_some_and(|found| found == Ordering::Equal || found == ordering)
+            })
+        };
+        (name.is_empty() || item.name.to_lowercase().contains(&name.to_lowercase()))
+            && within(item.total_cost, self.min_total, Ordering::Greater)
+            && within(item.total_cost, self.max_total, Ordering::Less)
+            && self
+                .min_lead_time_days
+                .is_none_or(|min| item.lead_time_days >= min)
+            && self
+                .max_lead_time_days
+                .is_none_or(|max| item.lead_time_days <= max)
+    }
+
+    /// Whether any line could be hidden.
+    pub fn is_filtered(&self) -> bool {
+        !self.name.trim().is_empty()
+            || self.min_total.is_some()
+            || self.max_total.is_some()
+            || self.min_lead_time_days.is_some()
+            || self.max_lead_time_days.is_some()
+    }
+
+    /// Step the sort for a click on `key`'s column header: ascending, then
+    /// descending, then back to the BoM's own order.
+    pub fn toggle_sort(&mut self, key: BomSortKey) {
+        self.sort = match self.sort {
+            Some((current, SortDirection::Ascending)) if current == key => {
+                Some((key, SortDirection::Descending))
+            }
+            Some((current, SortDirection::Descending)) if current == key => None,
+            _ => Some((key, SortDirection::Ascending)),
+        };
+    }
+
+    /// Indices into `items` of the matching lines, in view order. Lines
+    /// that sort equal keep their BoM order.
+    ///
+    /// `reconciliation` is only used to sort by [`BomSortKey::TotalCheck`].
+    pub fn apply(&self, items: &[BomItem], reconciliation: &[LineReconciliation]) -> Vec<usize> {
+        let mut indices: Vec<usize> = items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| self.matches(item))
+            .map(|(index, _)| index)
+            .collect();
+        if let Some((key, direction)) = self.sort {
+            // Text is lowercased once per line rather than on every comparison.
+            let text: Vec<String> = match key {
+                BomSortKey::Field(BomField::Name) => {
+                    items.iter().map(|item| item.name.to_lowercase()).collect()
+                }
+                BomSortKey::Field(BomField::Category) => items
+                    .iter()
+                    .map(|item| item.category.as_deref().unwrap_or("").to_lowercase())
+                    .collect(),
+                BomSortKey::Field(BomField::Supplier) => items
+                    .iter()
+                    .map(|item| item.supplier().unwrap_or("").to_lowercase())
+                    .collect(),
+                BomSortKey::Field(BomField::PartNumber) => items
+                    .iter()
+                    .map(|item| item.part_number.as_deref().unwrap_or("").to_lowercase())
+                    .collect(),
+                _ => Vec::new(),
+            };
+            let mut checks = vec![None; items.len()];
+            if key == BomSortKey::TotalCheck {
+                for line in reconciliation {
+                    if let Some(check) = checks.get_mut(line.index) {
+                        *check = Some(check_key(line));
+                    }
+                }
+            }
+            indices.sort_by(|&a, &b| {
+                let ordering = compare(key, a, b, items, &text, &checks);
+                match direction {
+                    SortDirection::Ascending => ordering,
+                    SortDirection::Descending => ordering.reverse(),
+                }
+            });
+        }
+        indices
+    }
+}
+
+fn compare(
+    key: BomSortKey,
+    a: usize,
+    b: usize,
+    items: &[BomItem],
+    text: &[String],
+    checks: &[Option<(u8, u64)>],
+) -> Ordering {
+    let (x, y) = (&items[a], &items[b]);
+    // Amounts group by currency code first, so the order stays total when
+    // lines are priced in more than one currency.
+    let money = |x: Money, y: Money| {
+        (x.currency(), x.scaled_units()).cmp(&(y.currency(), y.scaled_units()))
+    };
+    match key {
+        // Lines without a category, supplier or part number sort with an empty one, first.
+        BomSortKey::Field(
+            BomField::Name | BomField::Category | BomField::Supplier | BomField::PartNumber,
+        ) => text[a].cmp(&text[b]),
+        BomSortKey::Field(BomField::Quantity) => x.quantity.cmp(&y.quantity),
+        BomSortKey::Field(BomField::UnitCost) => money(x.unit_cost, y.unit_cost),
+        BomSortKey::Field(BomField::TotalCost) => money(x.total_cost, y.total_cost),
+        BomSortKey::Field(BomField::LeadTimeDays) => x.lead_time_days.cmp(&y.lead_time_days),
+        BomSortKey::Field(BomField::MinQuantity) => x.min_quantity.cmp(&y.min_quantity),
+        // Lines without a pack size come first.
+        BomSortKey::Field(BomField::PackSize) => x.pack_size.cmp(&y.pack_size),
+        BomSortKey::Field(BomField::Level) => x.level.cmp(&y.level),
+        BomSortKey::TotalCheck => checks[a].cmp(&checks[b]),
+    }
+}
+
+/// Orders reconciled lines by outcome, then by how far off they are.
+fn check_key(line: &LineReconciliation) -> (u8, u64) {
+    let rank = match line.status {
+        LineStatus::Matches => 0,
+        LineStatus::WithinTolerance => 1,
+        LineStatus::Mismatch => 2,
+    };
+    (rank, line.difference.scaled_units().unsigned_abs())
+}
+
+/// Write `items` to `path` as CSV, with headers the importer reads back.
+/// Levels are written as they are, so `items` should be in BoM order. Extra columns from the source
+/// file follow the standard ones. Returns how many lines were written.
+pub fn export_bom_csv<'a>(
+    path: &Path,
+    items: impl IntoIterator<Item = &'a BomItem>,
+) -> std::io::Result<usize> {
+    let items: Vec<&BomItem> = items.into_iter().collect();
+    let extra: BTreeSet<&str> = items
+        .iter()
+        .flat_map(|item| item.extra.keys().map(String::as_str))
+        .collect();
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut out = csv::Writer::from_path(path)?;
+    out.write_record(
+        [
+            "Material",
+            "Quantity",
+            "Unit cost",
+            "Line total",
+            "Lead time days",
+            "Min quantity",
+            "Category",
+            "Pack size",
+            "Level",
+            "Supplier",
+            "Part number",
+        ]
+        .into_iter()
+        .chain(extra.iter().copied()),
+    )?;
+    for item in &items {
+        let standard = [
+            item.name.clone(),
+            item.quantity.to_string(),
+            item.unit_cost.amount_string(),
+            item.total_cost.amount_string(),
+            item.lead_time_days.to_string(),
+            item.min_quantity.to_string(),
+            item.category.clone().unwrap_or_default(),
+            item.pack_size
+                .map(|size| size.to_string())
+                .unwrap_or_default(),
+            item.level.to_string(),
+            item.supplier.clone().unwrap_or_default(),
+            item.part_number.clone().unwrap_or_default(),
+        ];
+        let extra = extra
+            .iter()
+            .map(|header| item.extra.get(*header).cloned().unwrap_or_default());
+        out.write_record(standard.into_iter().chain(extra))?;
+    }
+    out.flush()?;
+    Ok(items.len())
+}
@@ -0,0 +1,596 @@
+use crate::pricing::{AdjustmentKind, PriceBreakdown};
+use crate::BomItem;
+use chrono::{Days, NaiveDate};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+// A4 portrait, in PDF points.
+pub(crate) const PAGE_WIDTH: f32 = 595.0;
+pub(crate) const PAGE_HEIGHT: f32 = 842.0;
+pub(crate) const MARGIN: f32 = 50.0;
+const FOOTER_HEIGHT: f32 = 40.0;
+pub(crate) const ROW_HEIGHT: f32 = 16.0;
+pub(crate) const BODY_SIZE: f32 = 10.0;
+
+// Right edges of the numeric columns in the line-item table.
+const QTY_RIGHT: f32 = 345.0;
+const UNIT_RIGHT: f32 = 440.0;
+pub(crate) const TOTAL_RIGHT: f32 = PAGE_WIDTH - MARGIN;
+const NAME_MAX_WIDTH: f32 = 260.0;
+
+/// Fixed wording and branding for generated quotes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QuoteTemplate {
+    pub title: String,
+    pub company_name: String,
+    pub company_address: Vec<String>,
+    pub company_contact: String,
+    /// Paragraphs printed under "Terms & conditions".
+    pub terms: Vec<String>,
+    /// Days from the issue date until the quote expires.
+    pub validity_days: u32,
+    pub footer: String,
+}
+
+impl Default for QuoteTemplate {
+    fn default() -> Self {
+        Self {
+            title: "Quotation".to_string(),
+            company_name: "Example Build Co.".to_string(),
+            company_address: vec![
+                "Unit 4, Riverside Works".to_string(),
+                "Leeds LS1 4AB".to_string(),
+            ],
+            company_contact: "estimating@example.com · 0113 000 0000".to_string(),
+            terms: vec![
+                "Prices are based on the quantities listed and may change if the scope changes."
+                    .to_string(),
+                "Materials are ordered on acceptance; lead times run from the order date."
+                    .to_string(),
+                "Payment is due within 30 days of invoice.".to_string(),
+            ],
+            validity_days: 30,
+            footer: "Example Build Co. · Registered in England & Wales".to_string(),
+        }
+    }
+}
+
+/// Who the quote is addressed to.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CustomerDetails {
+    pub name: String,
+    pub address: Vec<String>,
+    /// Customer's own reference, e.g. a site or job number.
+    pub reference: String,
+}
+
+/// Quote metadata kept alongside the BoM.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QuoteDetails {
+    pub number: String,
+    pub customer: CustomerDetails,
+    pub template: QuoteTemplate,
+}
+
+impl Default for QuoteDetails {
+    fn default() -> Self {
+        Self {
+            number: "Q-0001".to_string(),
+            customer: CustomerDetails {
+                name: "Sample Customer Ltd".to_string(),
+                address: vec!["1 High Street".to_string(), "York YO1 7HH".to_string()],
+                reference: "Site 12 refurbishment".to_string(),
+            },
+            template: QuoteTemplate::default(),
+        }
+    }
+}
+
+/// Everything needed to lay out one quote.
+#[derive(Debug, Clone)]
+pub struct QuoteDocument<'a> {
+    pub details: &'a QuoteDetails,
+    pub issue_date: NaiveDate,
+    /// Quote revision printed under the quote number.
+    pub revision: u32,
+    pub items: &'a [BomItem],
+    pub breakdown: &'a PriceBreakdown,
+}
+
+/// Failure while writing a quote to disk.
+#[derive(Debug)]
+pub enum QuoteError {
+    Pricing(crate::MoneyError),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for QuoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuoteError::Pricing(err) => write!(f, "could not price the quote: {err}"),
+            QuoteError::Io(err) => write!(f, "could not write the quote: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for QuoteError {}
+
+impl From<crate::MoneyError> for QuoteError {
+    fn from(err: crate::MoneyError) -> Self {
+        QuoteError::Pricing(err)
+    }
+}
+
+impl From<std::io::Error> for QuoteError {
+    fn from(err: std::io::Error) -> Self {
+        QuoteError::Io(err)
+    }
+}
+
+/// Render a quote as PDF bytes, flowing line items over as many pages as needed.
+pub fn render_quote_pdf(doc: &QuoteDocument<'_>) -> Vec<u8> {
+    let pages = layout(doc);
+    let title = format!("{} {}", doc.details.template.title, doc.details.number);
+    write_pdf(&title, &doc.details.template.footer, &pages)
+}
+
+/// Render a quote and write it to `path`, creating parent directories.
+pub fn write_quote_pdf(doc: &QuoteDocument<'_>, path: &Path) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, render_quote_pdf(doc))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Align {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Draw {
+    Text {
+        x: f32,
+        y: f32,
+        size: f32,
+        bold: bool,
+        align: Align,
+        text: String,
+    },
+    Rule {
+        y: f32,
+    },
+}
+
+/// Accumulates drawing commands page by page, breaking when space runs out.
+pub(crate) struct Layout {
+    pub(crate) pages: Vec<Vec<Draw>>,
+    y: f32,
+}
+
+impl Layout {
+    pub(crate) fn new() -> Self {
+        Self {
+            pages: vec![Vec::new()],
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    pub(crate) fn text(
+        &mut self,
+        x: f32,
+        size: f32,
+        bold: bool,
+        align: Align,
+        text: impl Into<String>,
+    ) {
+        let y = self.y;
+        self.current().push(Draw::Text {
+            x,
+            y,
+            size,
+            bold,
+            align,
+            text: text.into(),
+        });
+    }
+
+    pub(crate) fn rule(&mut self) {
+        let y = self.y;
+        self.current().push(Draw::Rule { y });
+    }
+
+    fn current(&mut self) -> &mut Vec<Draw> {
+        self.pages.last_mut().expect("layout always has a page")
+    }
+
+    pub(crate) fn advance(&mut self, by: f32) {
+        self.y -= by;
+    }
+
+    pub(crate) fn fits(&self, height: f32) -> bool {
+        self.y - height >= MARGIN + FOOTER_HEIGHT
+    }
+
+    pub(crate) fn new_page(&mut self) {
+        self.pages.push(Vec::new());
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+}
+
+fn layout(doc: &QuoteDocument<'_>) -> Vec<Vec<Draw>> {
+    let template = &doc.details.template;
+    let customer = &doc.details.customer;
+    let mut page = Layout::new();
+
+    // Company header on the left, quote identity on the right.
+    page.text(MARGIN, 18.0, true, Align::Left, &template.company_name);
+    page.text(TOTAL_RIGHT, 18.0, true, Align::Right, &template.title);
+    page.advance(18.0);
+    let valid_until = doc
+        .issue_date
+        .checked_add_days(Days::new(u64::from(template.validity_days)))
+        .unwrap_or(doc.issue_date);
+    let identity = [
+        format!("Quote {}", doc.details.number),
+        format!("Rev {}", doc.revision),
+        format!("Date {}", doc.issue_date.format("%d %b %Y")),
+        format!("Valid until {}", valid_until.format("%d %b %Y")),
+    ];
+    let company_lines = template
+        .company_address
+        .iter()
+        .chain(std::iter::once(&template.company_contact));
+    let mut identity_lines = identity.iter();
+    for line in company_lines {
+        page.text(MARGIN, 9.0, false, Align::Left, line);
+        if let Some(id) = identity_lines.next() {
+            page.text(TOTAL_RIGHT, 9.0, false, Align::Right, id);
+        }
+        page.advance(12.0);
+    }
+    for id in identity_lines {
+        page.text(TOTAL_RIGHT, 9.0, false, Align::Right, id);
+        page.advance(12.0);
+    }
+    page.advance(18.0);
+
+    // Customer block.
+    page.text(MARGIN, 9.0, true, Align::Left, "Prepared for");
+    page.advance(14.0);
+    page.text(MARGIN, 11.0, true, Align::Left, &customer.name);
+    page.advance(14.0);
+    for line in &customer.address {
+        page.text(MARGIN, BODY_SIZE, false, Align::Left, line);
+        page.advance(13.0);
+    }
+    if !customer.reference.is_empty() {
+        page.text(
+            MARGIN,
+            BODY_SIZE,
+            false,
+            Align::Left,
+            format!("Your reference: {}", customer.reference),
+        );
+        page.advance(13.0);
+    }
+    page.advance(16.0);
+
+    // Line items, repeating the table header on every page.
+    table_header(&mut page);
+    for item in doc.items {
+        if !page.fits(ROW_HEIGHT) {
+            page.new_page();
+            page.text(
+                MARGIN,
+                9.0,
+                false,
+                Align::Left,
+                format!("{} {} (continued)", template.title, doc.details.number),
+            );
+            page.advance(20.0);
+            table_header(&mut page);
+        }
+        page.text(
+            MARGIN,
+            BODY_SIZE,
+            false,
+            Align::Left,
+            truncate_to_width(&item.name, BODY_SIZE, NAME_MAX_WIDTH),
+        );
+        page.text(
+            QTY_RIGHT,
+            BODY_SIZE,
+            false,
+            Align::Right,
+            item.quantity.to_string(),
+        );
+        page.text(
+            UNIT_RIGHT,
+            BODY_SIZE,
+            false,
+            Align::Right,
+            item.unit_cost.to_string(),
+        );
+        page.text(
+            TOTAL_RIGHT,
+            BODY_SIZE,
+            false,
+            Align::Right,
+            item.total_cost.to_string(),
+        );
+        page.advance(ROW_HEIGHT);
+    }
+
+    // Totals are kept together on one page.
+    let totals_height = ROW_HEIGHT * (doc.breakdown.lines.len() as f32 + 3.0);
+    if !page.fits(totals_height) {
+        page.new_page();
+    }
+    page.advance(4.0);
+    page.rule();
+    page.advance(14.0);
+    totals_row(
+        &mut page,
+        false,
+        "Subtotal",
+        doc.breakdown.subtotal.to_string(),
+    );
+    for line in &doc.breakdown.lines {
+        let label = match &line.kind {
+            AdjustmentKind::Markup(rate)
+            | AdjustmentKind::Discount(rate)
+            | AdjustmentKind::Tax { rate, .. } => format!("{} ({rate})", line.label),
+            AdjustmentKind::FixedFee(_) => line.label.clone(),
+        };
+        totals_row(&mut page, false, &label, line.amount.to_string());
+    }
+    totals_row(
+        &mut page,
+        true,
+        "Total",
+        doc.breakdown.grand_total.to_string(),
+    );
+    page.advance(20.0);
+
+    // Terms, wrapped to the page width.
+    if !template.terms.is_empty() {
+        if !page.fits(ROW_HEIGHT * 2.0) {
+            page.new_page();
+        }
+        page.text(MARGIN, 11.0, true, Align::Left, "Terms & conditions");
+        page.advance(16.0);
+        for paragraph in &template.terms {
+            for line in wrap(paragraph, 9.0, PAGE_WIDTH - 2.0 * MARGIN) {
+                if !page.fits(12.0) {
+                    page.new_page();
+                }
+                page.text(MARGIN, 9.0, false, Align::Left, line);
+                page.advance(12.0);
+            }
+            page.advance(4.0);
+        }
+    }
+
+    page.pages
+}
+
+fn table_header(page: &mut Layout) {
+    page.text(MARGIN, BODY_SIZE, true, Align::Left, "Item");
+    page.text(QTY_RIGHT, BODY_SIZE, true, Align::Right, "Qty");
+    page.text(UNIT_RIGHT, BODY_SIZE, true, Align::Right, "Unit price");
+    page.text(TOTAL_RIGHT, BODY_SIZE, true, Align::Right, "Line total");
+    page.advance(5.0);
+    page.rule();
+    page.advance(ROW_HEIGHT - 3.0);
+}
+
+fn totals_row(page: &mut Layout, bold: bool, label: &str, amount: String) {
+    page.text(UNIT_RIGHT, BODY_SIZE, bold, Align::Right, label);
+    page.text(TOTAL_RIGHT, BODY_SIZE, bold, Align::Right, amount);
+    page.advance(ROW_HEIGHT);
+}
+
+/// Assemble laid-out pages into a PDF, with `footer` and page numbers on
+/// every page.
+pub(crate) fn write_pdf(title: &str, footer: &str, pages: &[Vec<Draw>]) -> Vec<u8> {
+    let mut pdf = Pdf::new();
+    let mut next_id = 1;
+    let mut alloc = || {
+        let id = Ref::new(next_id);
+        next_id += 1;
+        id
+    };
+
+    let catalog_id = alloc();
+    let tree_id = alloc();
+    let info_id = alloc();
+    let regular_id = alloc();
+    let bold_id = alloc();
+    let page_ids: Vec<(Ref, Ref)> = pages.iter().map(|_| (alloc(), alloc())).collect();
+
+    pdf.catalog(catalog_id).pages(tree_id);
+    pdf.pages(tree_id)
+        .kids(page_ids.iter().map(|(page, _)| *page))
+        .count(page_ids.len() as i32);
+    pdf.document_info(info_id)
+        .title(TextStr(title))
+        .producer(TextStr("tabs_backend"));
+    pdf.type1_font(regular_id)
+        .base_font(Name(b"Helvetica"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+    pdf.type1_font(bold_id)
+        .base_font(Name(b"Helvetica-Bold"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+
+    let page_count = pages.len();
+    for (number, (draws, (page_id, content_id))) in pages.iter().zip(&page_ids).enumerate() {
+        let mut page = pdf.page(*page_id);
+        page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT))
+            .parent(tree_id)
+            .contents(*content_id);
+        let mut resources = page.resources();
+        let mut fonts = resources.fonts();
+        fonts.pair(Name(b"F1"), regular_id);
+        fonts.pair(Name(b"F2"), bold_id);
+        fonts.finish();
+        resources.finish();
+        page.finish();
+
+        let mut content = Content::new();
+        for draw in draws {
+            draw_onto(&mut content, draw);
+        }
+        let footer_y = MARGIN - 20.0;
+        draw_onto(
+            &mut content,
+            &Draw::Text {
+                x: MARGIN,
+                y: footer_y,
+                size: 8.0,
+                bold: false,
+                align: Align::Left,
+                text: footer.to_string(),
+            },
+        );
+        draw_onto(
+            &mut content,
+            &Draw::Text {
+                x: TOTAL_RIGHT,
+                y: footer_y,
+                size: 8.0,
+                bold: false,
+                align: Align::Right,
+                text: format!("Page {} of {}", number + 1, page_count),
+            },
+        );
+        pdf.stream(*content_id, &content.finish());
+    }
+
+    pdf.finish()
+}
+
+fn draw_onto(content: &mut Content, draw: &Draw) {
+    match draw {
+        Draw::Text {
+            x,
+            y,
+            size,
+            bold,
+            align,
+            text,
+        } => {
+            let x = match align {
+                Align::Left => *x,
+                Align::Right => x - text_width(text, *size),
+            };
+            let font = if *bold { Name(b"F2") } else { Name(b"F1") };
+            content
+                .begin_text()
+                .set_font(font, *size)
+                .next_line(x, *y)
+                .show(Str(&win_ansi(text)))
+                .end_text();
+        }
+        Draw::Rule { y } => {
+            content
+                .set_line_width(0.5)
+                .move_to(MARGIN, *y)
+                .line_to(PAGE_WIDTH - MARGIN, *y)
+                .stroke();
+        }
+    }
+}
+
+/// Encode text for the standard fonts' WinAnsi encoding, replacing anything unsupported.
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            ' '..='~' => c as u8,
+            '€' => 0x80,
+            '–' => 0x96,
+            '—' => 0x97,
+            '\u{a0}'..='\u{ff}' => c as u32 as u8,
+            _ => b'?',
+        })
+        .collect()
+}
+
+/// Approximate rendered width using Helvetica's metrics (in 1/1000 em).
+///
+/// Bold glyphs are slightly wider, but digits — which matter for
+/// right-aligned amounts — have the same 556 width in both faces.
+pub(crate) fn text_width(text: &str, size: f32) -> f32 {
+    let units: u32 = win_ansi(text).iter().map(|&b| glyph_width(b)).sum();
+    units as f32 * size / 1000.0
+}
+
+fn glyph_width(byte: u8) -> u32 {
+    match byte {
+        b' ' | b'!' | b',' | b'.' | b'/' | b':' | b';' | b'I' | b'[' | b'\\' | b']' | b'f'
+        | b't' => 278,
+        b'"' => 355,
+        b'\'' => 191,
+        b'(' | b')' | b'-' | b'`' | b'r' => 333,
+        b'*' => 389,
+        b'+' | b'<' | b'=' | b'>' | b'~' => 584,
+        b'%' => 889,
+        b'&' | b'A' | b'B' | b'E' | b'K' | b'P' | b'S' | b'V' | b'X' | b'Y' => 667,
+        b'@' => 1015,
+        b'C' | b'D' | b'H' | b'N' | b'R' | b'U' | b'w' => 722,
+        b'F' | b'T' | b'Z' => 611,
+        b'G' | b'O' | b'Q' => 778,
+        b'J' | b'c' | b'k' | b's' | b'v' | b'x' | b'y' | b'z' => 500,
+        b'M' | b'm' => 833,
+        b'W' => 944,
+        b'^' => 469,
+        b'i' | b'j' | b'l' => 222,
+        b'{' | b'}' => 334,
+        b'|' => 260,
+        0x80 => 556,
+        0x96 => 556,
+        0x97 => 1000,
+        0xB7 => 278,
+        0xD7 => 584,
+        _ => 556,
+    }
+}
+
+pub(crate) fn truncate_to_width(text: &str, size: f32, max_width: f32) -> String {
+    if text_width(text, size) <= max_width {
+        return text.to_string();
+    }
+    let mut truncated = String::new();
+    for c in text.chars() {
+        let candidate = format!("{truncated}{c}...");
+        if text_width(&candidate, size) > max_width {
+            break;
+        }
+        truncated.push(c);
+    }
+    format!("{}...", truncated.trim_end())
+}
+
+pub(crate) fn wrap(paragraph: &str, size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in paragraph.split_whitespace() {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{line} {word}")
+        };
+        if !line.is_empty() && text_width(&candidate, size) > max_width {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        } else {
+            line = candidate;
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
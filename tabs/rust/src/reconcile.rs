@@ -0,0 +1,120 @@
+use crate::money::{Money, MoneyError, RoundingMode, DISPLAY_DECIMALS};
+use crate::BomItem;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Which line total wins when the file and the computed value disagree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TotalSource {
+    /// Keep the total exactly as supplied in the BoM file.
+    #[default]
+    File,
+    /// Replace the total with quantity × unit cost, rounded to two places.
+    Computed,
+}
+
+impl fmt::Display for TotalSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            TotalSource::File => "file value",
+            TotalSource::Computed => "quantity × unit cost",
+        };
+        f.write_str(label)
+    }
+}
+
+/// How line totals are checked against quantity × unit cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReconcilePolicy {
+    pub authoritative: TotalSource,
+    /// Largest difference, in minor units (e.g. pence), treated as a rounding artefact.
+    pub tolerance_minor: i64,
+}
+
+impl Default for ReconcilePolicy {
+    fn default() -> Self {
+        Self {
+            authoritative: TotalSource::File,
+            tolerance_minor: 1,
+        }
+    }
+}
+
+/// Result of checking one line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LineStatus {
+    Matches,
+    /// Differs by no more than the tolerance.
+    WithinTolerance,
+    /// Differs by more than the tolerance; someone should look at this row.
+    Mismatch,
+}
+
+/// Reconciliation outcome for a single BoM line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineReconciliation {
+    /// Index of the line in the BoM.
+    pub index: usize,
+    /// `None` for a line with no supplied total, which always matches.
+    pub file_total: Option<Money>,
+    pub computed_total: Money,
+    /// `file_total - computed_total`.
+    pub difference: Money,
+    pub status: LineStatus,
+    /// The total now stored on the line.
+    pub applied: TotalSource,
+}
+
+/// Recompute every line total and compare it with the supplied value.
+///
+/// Each line's `total_cost` is set to its supplied `file_total` or to the
+/// computed value, rounded with `rounding`, as `policy.authoritative` says;
+/// lines with no supplied total always take the computed one. The supplied
+/// totals themselves are left alone, so running this again under another
+/// policy starts from the same values.
+pub fn reconcile_bom(
+    items: &mut [BomItem],
+    policy: &ReconcilePolicy,
+    rounding: RoundingMode,
+) -> Result<Vec<LineReconciliation>, MoneyError> {
+    items
+        .iter_mut()
+        .enumerate()
+        .map(|(index, item)| {
+            let computed_total = item
+                .unit_cost
+                .checked_mul_quantity(item.quantity)?
+                .round(DISPLAY_DECIMALS, rounding);
+            let supplied = item.file_total.unwrap_or(computed_total);
+            let difference = supplied.checked_sub(computed_total)?;
+            let tolerance = Money::from_minor(policy.tolerance_minor, supplied.currency())?;
+            let distance = supplied.abs_diff(computed_total)?;
+
+            let status = if difference.is_zero() {
+                LineStatus::Matches
+            } else if distance <= tolerance {
+                LineStatus::WithinTolerance
+            } else {
+                LineStatus::Mismatch
+            };
+
+            let applied = match item.file_total {
+                Some(_) => policy.authoritative,
+                None => TotalSource::Computed,
+            };
+            item.total_cost = match applied {
+                TotalSource::File => supplied,
+                TotalSource::Computed => computed_total,
+            };
+
+            Ok(LineReconciliation {
+                index,
+                file_total: item.file_total,
+                computed_total,
+                difference,
+                status,
+                applied,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,187 @@
+use crate::assembly::BomTree;
+use crate::money::{Currency, Money, MoneyError};
+use crate::BomItem;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A quote as issued to the customer, kept so re-issues can be compared with it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuoteRevision {
+    /// 1 for the first issue, counting up with each re-issue.
+    pub number: u32,
+    pub issued_on: NaiveDate,
+    /// Why it was issued, e.g. what the customer asked to change.
+    pub note: String,
+    /// The BoM as quoted.
+    pub bom: Vec<BomItem>,
+    /// Quoted total after pricing adjustments.
+    pub grand_total: Money,
+}
+
+/// Error from comparing quote revisions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RevisionError {
+    NoSuchRevision(u32),
+    Money(MoneyError),
+}
+
+impl fmt::Display for RevisionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RevisionError::NoSuchRevision(number) => write!(f, "no quote revision {number}"),
+            RevisionError::Money(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for RevisionError {}
+
+impl From<MoneyError> for RevisionError {
+    fn from(err: MoneyError) -> Self {
+        RevisionError::Money(err)
+    }
+}
+
+/// One line that differs between two BoMs.
+#[derive(Debug, Clone)]
+pub enum LineDiff {
+    Added(BomItem),
+    Removed(BomItem),
+    /// In both BoMs with a different quantity or price.
+    Changed {
+        before: BomItem,
+        after: BomItem,
+    },
+}
+
+impl LineDiff {
+    pub fn name(&self) -> &str {
+        match self {
+            LineDiff::Added(item) | LineDiff::Removed(item) => &item.name,
+            LineDiff::Changed { after, .. } => &after.name,
+        }
+    }
+
+    pub fn before(&self) -> Option<&BomItem> {
+        match self {
+            LineDiff::Added(_) => None,
+            LineDiff::Removed(before) | LineDiff::Changed { before, .. } => Some(before),
+        }
+    }
+
+    pub fn after(&self) -> Option<&BomItem> {
+        match self {
+            LineDiff::Removed(_) => None,
+            LineDiff::Added(after) | LineDiff::Changed { after, .. } => Some(after),
+        }
+    }
+
+    pub fn quantity_changed(&self) -> bool {
+        matches!(self, LineDiff::Changed { before, after } if before.quantity != after.quantity)
+    }
+
+    /// Whether the unit cost, or the line total other than through the quantity, changed.
+    pub fn price_changed(&self) -> bool {
+        matches!(self, LineDiff::Changed { before, after } if price_differs(before, after))
+    }
+
+    /// Change in the line total; an added line counts in full, a removed one negatively.
+    pub fn total_delta(&self) -> Result<Money, MoneyError> {
+        match self {
+            LineDiff::Added(after) => Ok(after.total_cost),
+            LineDiff::Removed(before) => before.total_cost.checked_neg(),
+            LineDiff::Changed { before, after } => after.total_cost.checked_sub(before.total_cost),
+        }
+    }
+}
+
+fn price_differs(before: &BomItem, after: &BomItem) -> bool {
+    before.unit_cost != after.unit_cost
+        || (before.quantity == after.quantity && before.total_cost != after.total_cost)
+}
+
+/// What changed between two BoMs.
+#[derive(Debug, Clone)]
+pub struct BomDiff {
+    /// Added, removed and re-quantified or re-priced lines, in the order
+    /// of the later BoM with removed lines last. Lines that only changed in
+    /// other ways (lead time, category, …) are not listed.
+    pub lines: Vec<LineDiff>,
+    /// Total of each BoM, counting parts once per assembly built.
+    pub before_total: Money,
+    pub after_total: Money,
+}
+
+impl BomDiff {
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    pub fn total_delta(&self) -> Result<Money, MoneyError> {
+        self.after_total.checked_sub(self.before_total)
+    }
+
+    pub fn added(&self) -> usize {
+        self.count(|line| matches!(line, LineDiff::Added(_)))
+    }
+
+    pub fn removed(&self) -> usize {
+        self.count(|line| matches!(line, LineDiff::Removed(_)))
+    }
+
+    /// Lines in both BoMs whose quantity or price changed.
+    pub fn changed(&self) -> usize {
+        self.count(|line| matches!(line, LineDiff::Changed { .. }))
+    }
+
+    fn count(&self, kind: impl Fn(&LineDiff) -> bool) -> usize {
+        self.lines.iter().filter(|line| kind(line)).count()
+    }
+}
+
+/// Compare two BoMs, pairing lines by name (ignoring case and surrounding
+/// spaces). Lines with the same name are paired in the order they appear.
+pub fn diff_bom(
+    before: &[BomItem],
+    after: &[BomItem],
+    currency: Currency,
+) -> Result<BomDiff, MoneyError> {
+    let key = |item: &BomItem| item.name.trim().to_lowercase();
+    let mut unmatched: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, item) in before.iter().enumerate().rev() {
+        unmatched.entry(key(item)).or_default().push(index);
+    }
+
+    let mut matched = vec![false; before.len()];
+    let mut lines = Vec::new();
+    for item in after {
+        match unmatched.get_mut(&key(item)).and_then(Vec::pop) {
+            Some(index) => {
+                matched[index] = true;
+                let earlier = &before[index];
+                if earlier.quantity != item.quantity || price_differs(earlier, item) {
+                    lines.push(LineDiff::Changed {
+                        before: earlier.clone(),
+                        after: item.clone(),
+                    });
+                }
+            }
+            None => lines.push(LineDiff::Added(item.clone())),
+        }
+    }
+    lines.extend(
+        before
+            .iter()
+            .zip(&matched)
+            .filter(|(_, matched)| !**matched)
+            .map(|(item, _)| LineDiff::Removed(item.clone())),
+    );
+
+    Ok(BomDiff {
+        lines,
+        before_total: BomTree::build(before)?.total(currency)?,
+        after_total: BomTree::build(after)?.total(currency)?,
+    })
+}
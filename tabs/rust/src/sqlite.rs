@@ -0,0 +1,438 @@
+use crate::money::{Currency, Money};
+use crate::project::ProjectFile;
+use crate::storage::{
+    parse_document, PricePoint, ProjectId, ProjectRepository, Snapshot, StorageError,
+    StoredProject, StoredRevision,
+};
+use crate::AppState;
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+
+/// Database layout version, kept in SQLite's `user_version`.
+const DATABASE_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE projects (
+    id INTEGER PRIMARY KEY,
+    quote_number TEXT NOT NULL,
+    customer TEXT NOT NULL,
+    reference TEXT NOT NULL,
+    saved_at TEXT NOT NULL,
+    line_count INTEGER NOT NULL,
+    total TEXT,
+    currency TEXT NOT NULL
+);
+CREATE TABLE bom_lines (
+    project_id INTEGER NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    quantity INTEGER NOT NULL,
+    unit_cost TEXT NOT NULL,
+    total_cost TEXT NOT NULL,
+    currency TEXT NOT NULL,
+    lead_time_days INTEGER NOT NULL,
+    supplier TEXT,
+    PRIMARY KEY (project_id, position)
+);
+CREATE TABLE project_saves (
+    project_id INTEGER NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
+    revision INTEGER NOT NULL,
+    saved_at TEXT NOT NULL,
+    quote_number TEXT NOT NULL,
+    total TEXT,
+    currency TEXT NOT NULL,
+    document TEXT NOT NULL,
+    PRIMARY KEY (project_id, revision)
+);
+CREATE TABLE price_history (
+    id INTEGER PRIMARY KEY,
+    project_id INTEGER REFERENCES projects (id) ON DELETE SET NULL,
+    part TEXT NOT NULL COLLATE NOCASE,
+    quote_number TEXT NOT NULL,
+    recorded_at TEXT NOT NULL,
+    unit_cost TEXT NOT NULL,
+    currency TEXT NOT NULL,
+    supplier TEXT
+);
+CREATE INDEX price_history_part ON price_history (part);
+";
+
+/// Stores projects in an embedded SQLite database.
+///
+/// Alongside each revision's project document, the database keeps the
+/// current BoM lines of every project and the price history in plain
+/// tables, so they can also be queried with other SQLite tools.
+pub struct SqliteRepository {
+    connection: Connection,
+}
+
+impl SqliteRepository {
+    /// Open the database at `path`, creating it (and its directory) if needed.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, StorageError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|err| StorageError::Backend(format!("{}: {err}", parent.display())))?;
+        }
+        Self::with_connection(Connection::open(path).map_err(backend)?)
+    }
+
+    /// A database that lives only as long as the repository.
+    pub fn open_in_memory() -> Result<Self, StorageError> {
+        Self::with_connection(Connection::open_in_memory().map_err(backend)?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self, StorageError> {
+        connection
+            .pragma_update(None, "foreign_keys", true)
+            .map_err(backend)?;
+        let version: i64 = connection
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .map_err(backend)?;
+        match version {
+            0 => {
+                connection.execute_batch(SCHEMA).map_err(backend)?;
+                connection
+                    .pragma_update(None, "user_version", DATABASE_VERSION)
+                    .map_err(backend)?;
+            }
+            DATABASE_VERSION => {}
+            newer => {
+                return Err(StorageError::Backend(format!(
+                    "database layout version {newer} is newer than this build supports ({DATABASE_VERSION})"
+                )))
+            }
+        }
+        Ok(Self { connection })
+    }
+
+    fn document(&self, id: ProjectId, revision: Option<u32>) -> Result<String, StorageError> {
+        let document = match revision {
+            Some(number) => self
+                .connection
+                .query_row(
+                    "SELECT document FROM project_saves WHERE project_id = ?1 AND revision = ?2",
+                    params![id, number],
+                    |row| row.get(0),
+                )
+                .optional(),
+            None => self
+                .connection
+                .query_row(
+                    "SELECT document FROM project_saves WHERE project_id = ?1
+                     ORDER BY revision DESC LIMIT 1",
+                    params![id],
+                    |row| row.get(0),
+                )
+                .optional(),
+        };
+        document.map_err(backend)?.ok_or(match revision {
+            Some(number) => StorageError::NoSuchRevision(id, number),
+            None => StorageError::NoSuchProject(id),
+        })
+    }
+
+    fn exists(&self, id: ProjectId) -> Result<bool, StorageError> {
+        self.connection
+            .query_row("SELECT 1 FROM projects WHERE id = ?1", params![id], |_| {
+                Ok(())
+            })
+            .optional()
+            .map(|found| found.is_some())
+            .map_err(backend)
+    }
+}
+
+impl ProjectRepository for SqliteRepository {
+    fn find_projects(&self, search: &str) -> Result<Vec<StoredProject>, StorageError> {
+        let pattern = format!("%{}%", escape_like(search.trim()));
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT p.id, p.quote_number, p.customer, p.saved_at, p.line_count, p.total,
+                        p.currency, (SELECT MAX(revision) FROM project_saves r WHERE r.project_id = p.id)
+                 FROM projects p
+                 WHERE p.quote_number LIKE ?1 ESCAPE '\\'
+                    OR p.customer LIKE ?1 ESCAPE '\\'
+                    OR p.reference LIKE ?1 ESCAPE '\\'
+                    OR EXISTS (SELECT 1 FROM bom_lines l
+                               WHERE l.project_id = p.id AND l.name LIKE ?1 ESCAPE '\\')
+                 ORDER BY p.saved_at DESC, p.id DESC",
+            )
+            .map_err(backend)?;
+        let rows = statement
+            .query_map(params![pattern], |row| {
+                Ok(StoredProject {
+                    id: row.get(0)?,
+                    quote_number: row.get(1)?,
+                    customer: row.get(2)?,
+                    saved_at: timestamp(row, 3)?,
+                    lines: row.get(4)?,
+                    total: optional_money(row, 5, 6)?,
+                    revision: row.get::<_, Option<u32>>(7)?.unwrap_or(0),
+                })
+            })
+            .map_err(backend)?;
+        rows.collect::<Result<_, _>>().map_err(backend)
+    }
+
+    fn save_project(
+        &mut self,
+        id: Option<ProjectId>,
+        state: &AppState,
+        quote_pdf: Option<&[u8]>,
+    ) -> Result<ProjectId, StorageError> {
+        let snapshot = Snapshot::of(state, quote_pdf)?;
+        if let Some(id) = id {
+            if !self.exists(id)? {
+                return Err(StorageError::NoSuchProject(id));
+            }
+        }
+        let saved_at = stored_time(snapshot.saved_at);
+        let currency = state.settings.currency.to_string();
+        let total = snapshot.total.map(|total| total.amount_string());
+
+        let transaction = self.connection.transaction().map_err(backend)?;
+        let id = match id {
+            Some(id) => {
+                transaction
+                    .execute(
+                        "UPDATE projects SET quote_number = ?2, customer = ?3, reference = ?4,
+                                saved_at = ?5, line_count = ?6, total = ?7, currency = ?8
+                         WHERE id = ?1",
+                        params![
+                            id,
+                            snapshot.quote_number,
+                            snapshot.customer,
+                            snapshot.reference,
+                            saved_at,
+                            state.bom.len(),
+                            total,
+                            currency
+                        ],
+                    )
+                    .map_err(backend)?;
+                transaction
+                    .execute("DELETE FROM bom_lines WHERE project_id = ?1", params![id])
+                    .map_err(backend)?;
+                id
+            }
+            None => {
+                transaction
+                    .execute(
+                        "INSERT INTO projects
+                             (quote_number, customer, reference, saved_at, line_count, total, currency)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        params![
+                            snapshot.quote_number,
+                            snapshot.customer,
+                            snapshot.reference,
+                            saved_at,
+                            state.bom.len(),
+                            total,
+                            currency
+                        ],
+                    )
+                    .map_err(backend)?;
+                transaction.last_insert_rowid()
+            }
+        };
+
+        for (position, item) in state.bom.iter().enumerate() {
+            transaction
+                .execute(
+                    "INSERT INTO bom_lines (project_id, position, name, quantity, unit_cost,
+                                            total_cost, currency, lead_time_days, supplier)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        id,
+                        position,
+                        item.name,
+                        item.quantity,
+                        item.unit_cost.amount_string(),
+                        item.total_cost.amount_string(),
+                        item.unit_cost.currency().to_string(),
+                        item.lead_time_days,
+                        item.supplier()
+                    ],
+                )
+                .map_err(backend)?;
+
+            let last: Option<(String, String)> = transaction
+                .query_row(
+                    "SELECT unit_cost, currency FROM price_history
+                     WHERE project_id = ?1 AND part = ?2 ORDER BY id DESC LIMIT 1",
+                    params![id, item.name],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()
+                .map_err(backend)?;
+            let unit_cost = item.unit_cost.amount_string();
+            let unit_currency = item.unit_cost.currency().to_string();
+            if last.as_ref() == Some(&(unit_cost.clone(), unit_currency.clone())) {
+                continue;
+            }
+            transaction
+                .execute(
+                    "INSERT INTO price_history
+                         (project_id, part, quote_number, recorded_at, unit_cost, currency, supplier)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        id,
+                        item.name,
+                        snapshot.quote_number,
+                        saved_at,
+                        unit_cost,
+                        unit_currency,
+                        item.supplier()
+                    ],
+                )
+                .map_err(backend)?;
+        }
+
+        transaction
+            .execute(
+                "INSERT INTO project_saves
+                     (project_id, revision, saved_at, quote_number, total, currency, document)
+                 VALUES (?1,
+                         (SELECT COALESCE(MAX(revision), 0) + 1 FROM project_saves WHERE project_id = ?1),
+                         ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    id,
+                    saved_at,
+                    snapshot.quote_number,
+                    total,
+                    currency,
+                    snapshot.document
+                ],
+            )
+            .map_err(backend)?;
+        transaction.commit().map_err(backend)?;
+        Ok(id)
+    }
+
+    fn load_project(&self, id: ProjectId) -> Result<ProjectFile, StorageError> {
+        parse_document(&self.document(id, None)?)
+    }
+
+    fn delete_project(&mut self, id: ProjectId) -> Result<(), StorageError> {
+        let deleted = self
+            .connection
+            .execute("DELETE FROM projects WHERE id = ?1", params![id])
+            .map_err(backend)?;
+        if deleted == 0 {
+            return Err(StorageError::NoSuchProject(id));
+        }
+        Ok(())
+    }
+
+    fn stored_revisions(&self, id: ProjectId) -> Result<Vec<StoredRevision>, StorageError> {
+        if !self.exists(id)? {
+            return Err(StorageError::NoSuchProject(id));
+        }
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT revision, saved_at, quote_number, total, currency FROM project_saves
+                 WHERE project_id = ?1 ORDER BY revision",
+            )
+            .map_err(backend)?;
+        let rows = statement
+            .query_map(params![id], |row| {
+                Ok(StoredRevision {
+                    number: row.get(0)?,
+                    saved_at: timestamp(row, 1)?,
+                    quote_number: row.get(2)?,
+                    total: optional_money(row, 3, 4)?,
+                })
+            })
+            .map_err(backend)?;
+        rows.collect::<Result<_, _>>().map_err(backend)
+    }
+
+    fn load_revision(&self, id: ProjectId, number: u32) -> Result<ProjectFile, StorageError> {
+        if !self.exists(id)? {
+            return Err(StorageError::NoSuchProject(id));
+        }
+        parse_document(&self.document(id, Some(number))?)
+    }
+
+    fn price_history(&self, part: &str) -> Result<Vec<PricePoint>, StorageError> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT project_id, quote_number, recorded_at, unit_cost, currency, supplier
+                 FROM price_history WHERE part = ?1 ORDER BY id",
+            )
+            .map_err(backend)?;
+        let rows = statement
+            .query_map(params![part.trim()], |row| {
+                Ok(PricePoint {
+                    project: row.get(0)?,
+                    quote_number: row.get(1)?,
+                    recorded_at: timestamp(row, 2)?,
+                    unit_cost: money(row, 3, 4)?,
+                    supplier: row.get(5)?,
+                })
+            })
+            .map_err(backend)?;
+        rows.collect::<Result<_, _>>().map_err(backend)
+    }
+}
+
+fn backend(err: rusqlite::Error) -> StorageError {
+    StorageError::Backend(err.to_string())
+}
+
+/// Escape `%`, `_` and the escape character itself for a `LIKE … ESCAPE '\'` pattern.
+fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Error for a stored value that does not read back as its Rust type.
+fn invalid_column(
+    index: usize,
+    err: impl std::error::Error + Send + Sync + 'static,
+) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(err))
+}
+
+/// Times are stored in UTC with a fixed number of digits, so that ordering
+/// by the text orders by time whatever the local offset was when saving.
+fn stored_time(time: DateTime<Local>) -> String {
+    time.with_timezone(&Utc)
+        .to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+fn timestamp(row: &Row<'_>, index: usize) -> rusqlite::Result<DateTime<Local>> {
+    let text: String = row.get(index)?;
+    DateTime::parse_from_rfc3339(&text)
+        .map(|time| time.with_timezone(&Local))
+        .map_err(|err| invalid_column(index, err))
+}
+
+fn money(row: &Row<'_>, amount: usize, currency: usize) -> rusqlite::Result<Money> {
+    let code: String = row.get(currency)?;
+    let currency: Currency = code.parse().map_err(|err| invalid_column(currency, err))?;
+    let text: String = row.get(amount)?;
+    Money::parse(&text, currency).map_err(|err| invalid_column(amount, err))
+}
+
+fn optional_money(
+    row: &Row<'_>,
+    amount: usize,
+    currency: usize,
+) -> rusqlite::Result<Option<Money>> {
+    match row.get::<_, Option<String>>(amount)? {
+        Some(_) => money(row, amount, currency).map(Some),
+        None => Ok(None),
+    }
+}
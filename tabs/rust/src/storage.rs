@@ -0,0 +1,345 @@
+use crate::money::Money;
+use crate::project::{project_from_str, project_to_string, ProjectError, ProjectFile};
+use crate::AppState;
+use chrono::{DateTime, Local};
+use std::fmt;
+
+/// Key of a project in a [`ProjectRepository`].
+pub type ProjectId = i64;
+
+/// A project as listed in the project picker.
+#[derive(Debug, Clone)]
+pub struct StoredProject {
+    pub id: ProjectId,
+    pub quote_number: String,
+    pub customer: String,
+    pub saved_at: DateTime<Local>,
+    pub lines: usize,
+    /// Grand total when last saved; `None` if the estimate could not be priced.
+    pub total: Option<Money>,
+    /// Number of the latest revision; every save adds one.
+    pub revision: u32,
+}
+
+/// One save of a project in a repository, numbered from 1.
+#[derive(Debug, Clone)]
+pub struct StoredRevision {
+    pub number: u32,
+    pub saved_at: DateTime<Local>,
+    pub quote_number: String,
+    pub total: Option<Money>,
+}
+
+/// The unit cost of a part as saved in a project.
+#[derive(Debug, Clone)]
+pub struct PricePoint {
+    /// `None` once the project has been deleted; its prices are kept.
+    pub project: Option<ProjectId>,
+    pub quote_number: String,
+    pub recorded_at: DateTime<Local>,
+    pub unit_cost: Money,
+    pub supplier: Option<String>,
+}
+
+/// Error from a [`ProjectRepository`].
+#[derive(Debug)]
+pub enum StorageError {
+    NoSuchProject(ProjectId),
+    NoSuchRevision(ProjectId, u32),
+    /// A stored project document could not be written or read back.
+    Project(ProjectError),
+    /// The storage itself failed, e.g. the database could not be opened.
+    Backend(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::NoSuchProject(id) => write!(f, "no stored project {id}"),
+            StorageError::NoSuchRevision(id, number) => {
+                write!(f, "stored project {id} has no revision {number}")
+            }
+            StorageError::Project(err) => err.fmt(f),
+            StorageError::Backend(message) => write!(f, "project storage failed: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StorageError::Project(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ProjectError> for StorageError {
+    fn from(err: ProjectError) -> Self {
+        StorageError::Project(err)
+    }
+}
+
+/// Somewhere estimates can be kept, searched and reopened.
+///
+/// Projects are stored as project documents (see [`crate::project_to_string`]),
+/// so older ones are upgraded on loading exactly like project files. Each
+/// save adds a numbered revision and records the unit costs that changed,
+/// building a price history per part across projects.
+pub trait ProjectRepository {
+    /// Projects whose quote number, customer or any BoM line name contains
+    /// `search` (ignoring case), most recently saved first. An empty search
+    /// lists everything.
+    fn find_projects(&self, search: &str) -> Result<Vec<StoredProject>, StorageError>;
+
+    /// Save `state` as a new revision of project `id`, or as a new project
+    /// when `id` is `None`, with the quote PDF attached if given.
+    fn save_project(
+        &mut self,
+        id: Option<ProjectId>,
+        state: &AppState,
+        quote_pdf: Option<&[u8]>,
+    ) -> Result<ProjectId, StorageError>;
+
+    /// The latest revision of project `id`.
+    fn load_project(&self, id: ProjectId) -> Result<ProjectFile, StorageError>;
+
+    /// Remove a project and its revisions; its price history is kept.
+    fn delete_project(&mut self, id: ProjectId) -> Result<(), StorageError>;
+
+    /// Saves of project `id`, oldest first. These are storage revisions,
+    /// not the quote revisions issued to the customer.
+    fn stored_revisions(&self, id: ProjectId) -> Result<Vec<StoredRevision>, StorageError>;
+
+    fn load_revision(&self, id: ProjectId, number: u32) -> Result<ProjectFile, StorageError>;
+
+    /// Unit costs saved for parts named `part` (ignoring case) across all
+    /// projects, oldest first.
+    fn price_history(&self, part: &str) -> Result<Vec<PricePoint>, StorageError>;
+}
+
+/// What a repository stores for one save of a project.
+pub(crate) struct Snapshot {
+    pub document: String,
+    pub saved_at: DateTime<Local>,
+    pub quote_number: String,
+    pub customer: String,
+    pub reference: String,
+    pub total: Option<Money>,
+}
+
+impl Snapshot {
+    pub fn of(state: &AppState, quote_pdf: Option<&[u8]>) -> Result<Self, StorageError> {
+        let saved_at = Local::now();
+        Ok(Self {
+            document: project_to_string(state, saved_at, quote_pdf)?,
+            saved_at,
+            quote_number: state.quote.number.clone(),
+            customer: state.quote.customer.name.clone(),
+            reference: state.quote.customer.reference.clone(),
+            total: state
+                .price_breakdown()
+                .ok()
+                .map(|breakdown| breakdown.grand_total),
+        })
+    }
+}
+
+/// Read back a stored project document.
+pub(crate) fn parse_document(document: &str) -> Result<ProjectFile, StorageError> {
+    Ok(project_from_str(document)?)
+}
+
+/// Keeps projects in memory for the life of the process; used when no
+/// database is configured, and as a reference for other repositories.
+///
+/// Text is matched ignoring ASCII case only, as SQLite's `LIKE` and
+/// `NOCASE` do.
+#[derive(Debug, Default)]
+pub struct MemoryRepository {
+    projects: Vec<MemoryProject>,
+    prices: Vec<(String, PricePoint)>,
+    next_id: ProjectId,
+}
+
+#[derive(Debug)]
+struct MemoryProject {
+    id: ProjectId,
+    customer: String,
+    /// Lowercased quote number, customer, reference and line names.
+    search_text: Vec<String>,
+    lines: usize,
+    /// Revisions oldest first, each with its project document.
+    revisions: Vec<(StoredRevision, String)>,
+}
+
+impl MemoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn project(&self, id: ProjectId) -> Result<&MemoryProject, StorageError> {
+        self.projects
+            .iter()
+            .find(|project| project.id == id)
+            .ok_or(StorageError::NoSuchProject(id))
+    }
+
+    /// Record each line's unit cost unless it is unchanged since this
+    /// project's last save.
+    fn record_prices(&mut self, id: ProjectId, state: &AppState, snapshot: &Snapshot) {
+        for item in &state.bom {
+            let part = item.name.to_ascii_lowercase();
+            let last = self
+                .prices
+                .iter()
+                .rev()
+                .find(|(name, point)| *name == part && point.project == Some(id));
+            if last.is_some_and(|(_, point)| point.unit_cost == item.unit_cost) {
+                continue;
+            }
+            self.prices.push((
+                part,
+                PricePoint {
+                    project: Some(id),
+                    quote_number: snapshot.quote_number.clone(),
+                    recorded_at: snapshot.saved_at,
+                    unit_cost: item.unit_cost,
+                    supplier: item.supplier().map(str::to_string),
+                },
+            ));
+        }
+    }
+}
+
+impl ProjectRepository for MemoryRepository {
+    fn find_projects(&self, search: &str) -> Result<Vec<StoredProject>, StorageError> {
+        let search = search.trim().to_ascii_lowercase();
+        let mut found: Vec<StoredProject> = self
+            .projects
+            .iter()
+            .filter(|project| {
+                project
+                    .search_text
+                    .iter()
+                    .any(|text| text.contains(&search))
+            })
+            .filter_map(|project| {
+                let (latest, _) = project.revisions.last()?;
+                Some(StoredProject {
+                    id: project.id,
+                    quote_number: latest.quote_number.clone(),
+                    customer: project.customer.clone(),
+                    saved_at: latest.saved_at,
+                    lines: project.lines,
+                    total: latest.total,
+                    revision: latest.number,
+                })
+            })
+            .collect();
+        found.sort_by_key(|project| std::cmp::Reverse(project.saved_at));
+        Ok(found)
+    }
+
+    fn save_project(
+        &mut self,
+        id: Option<ProjectId>,
+        state: &AppState,
+        quote_pdf: Option<&[u8]>,
+    ) -> Result<ProjectId, StorageError> {
+        let snapshot = Snapshot::of(state, quote_pdf)?;
+        let id = match id {
+            Some(id) => {
+                self.project(id)?;
+                id
+            }
+            None => {
+                self.next_id += 1;
+                self.projects.push(MemoryProject {
+                    id: self.next_id,
+                    customer: String::new(),
+                    search_text: Vec::new(),
+                    lines: 0,
+                    revisions: Vec::new(),
+                });
+                self.next_id
+            }
+        };
+        self.record_prices(id, state, &snapshot);
+
+        let project = self
+            .projects
+            .iter_mut()
+            .find(|project| project.id == id)
+            .ok_or(StorageError::NoSuchProject(id))?;
+        project.search_text = [
+            &snapshot.quote_number,
+            &snapshot.customer,
+            &snapshot.reference,
+        ]
+        .into_iter()
+        .cloned()
+        .chain(state.bom.iter().map(|item| item.name.clone()))
+        .map(|text| text.to_ascii_lowercase())
+        .collect();
+        project.customer = snapshot.customer;
+        project.lines = state.bom.len();
+        let revision = StoredRevision {
+            number: project.revisions.len() as u32 + 1,
+            saved_at: snapshot.saved_at,
+            quote_number: snapshot.quote_number,
+            total: snapshot.total,
+        };
+        project.revisions.push((revision, snapshot.document));
+        Ok(id)
+    }
+
+    fn load_project(&self, id: ProjectId) -> Result<ProjectFile, StorageError> {
+        let (_, document) = self
+            .project(id)?
+            .revisions
+            .last()
+            .ok_or(StorageError::NoSuchProject(id))?;
+        parse_document(document)
+    }
+
+    fn delete_project(&mut self, id: ProjectId) -> Result<(), StorageError> {
+        self.project(id)?;
+        self.projects.retain(|project| project.id != id);
+        for (_, point) in &mut self.prices {
+            if point.project == Some(id) {
+                point.project = None;
+            }
+        }
+        Ok(())
+    }
+
+    fn stored_revisions(&self, id: ProjectId) -> Result<Vec<StoredRevision>, StorageError> {
+        Ok(self
+            .project(id)?
+            .revisions
+            .iter()
+            .map(|(revision, _)| revision.clone())
+            .collect())
+    }
+
+    fn load_revision(&self, id: ProjectId, number: u32) -> Result<ProjectFile, StorageError> {
+        let (_, document) = self
+            .project(id)?
+            .revisions
+            .iter()
+            .find(|(revision, _)| revision.number == number)
+            .ok_or(StorageError::NoSuchRevision(id, number))?;
+        parse_document(document)
+    }
+
+    fn price_history(&self, part: &str) -> Result<Vec<PricePoint>, StorageError> {
+        let part = part.trim().to_ascii_lowercase();
+        Ok(self
+            .prices
+            .iter()
+            .filter(|(name, _)| *name == part)
+            .map(|(_, point)| point.clone())
+            .collect())
+    }
+}
@@ -0,0 +1,391 @@
+use crate::calendar::{LEAD_TIME_TOO_LONG, MAX_LEAD_TIME_DAYS};
+use crate::columns::normalize_header;
+use crate::import::{ExpectedType, ImportDiagnostic, Severity};
+use crate::money::{Currency, Money, MoneyError};
+use crate::BomItem;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Read;
+use std::path::Path;
+
+/// A company BoM lines are bought from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Supplier {
+    /// Matched against [`BomItem::supplier`] ignoring ASCII case only, as
+    /// SQLite's `NOCASE` and `LIKE` do, so that the catalogue, supplier
+    /// groups and every repository agree on which names are the same.
+    pub name: String,
+    /// Person orders go to.
+    pub contact: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    /// Lead time a line takes on when it is given this supplier and has
+    /// none of its own.
+    pub default_lead_time_days: Option<u32>,
+    /// Currency the supplier quotes and invoices in.
+    pub currency: Currency,
+}
+
+/// The suppliers an estimate knows the details of. BoM lines may still
+/// name suppliers that are not in it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SupplierCatalogue {
+    /// In the order they were added.
+    pub suppliers: Vec<Supplier>,
+}
+
+impl SupplierCatalogue {
+    pub fn find(&self, name: &str) -> Option<&Supplier> {
+        let name = name.trim();
+        self.suppliers
+            .iter()
+            .find(|supplier| supplier.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The catalogue entry for the supplier `item` names, if any.
+    pub fn for_item(&self, item: &BomItem) -> Option<&Supplier> {
+        item.supplier().and_then(|name| self.find(name))
+    }
+
+    /// Add `suppliers`, replacing entries of the same name in place.
+    /// Returns how many were new.
+    pub fn merge(&mut self, suppliers: impl IntoIterator<Item = Supplier>) -> usize {
+        let mut added = 0;
+        for supplier in suppliers {
+            match self
+                .suppliers
+                .iter_mut()
+                .find(|known| known.name.eq_ignore_ascii_case(&supplier.name))
+            {
+                Some(known) => *known = supplier,
+                None => {
+                    self.suppliers.push(supplier);
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+}
+
+/// Failure that prevents a catalogue file from being read at all.
+///
+/// Problems with individual rows are reported as diagnostics instead.
+#[derive(Debug)]
+pub enum CatalogueError {
+    Io(std::io::Error),
+    Csv(csv::Error),
+}
+
+impl fmt::Display for CatalogueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CatalogueError::Io(err) => write!(f, "could not read supplier catalogue: {err}"),
+            CatalogueError::Csv(err) => write!(f, "could not parse supplier catalogue: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for CatalogueError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CatalogueError::Io(err) => Some(err),
+            CatalogueError::Csv(err) => Some(err),
+        }
+    }
+}
+
+impl From<std::io::Error> for CatalogueError {
+    fn from(err: std::io::Error) -> Self {
+        CatalogueError::Io(err)
+    }
+}
+
+impl From<csv::Error> for CatalogueError {
+    fn from(err: csv::Error) -> Self {
+        CatalogueError::Csv(err)
+    }
+}
+
+/// Outcome of reading a supplier catalogue file.
+#[derive(Debug, Clone, Default)]
+pub struct SupplierImportReport {
+    /// Where the rows were read from, for display purposes.
+    pub source: String,
+    pub suppliers: Vec<Supplier>,
+    pub diagnostics: Vec<ImportDiagnostic>,
+    /// Non-blank data rows seen in the file (excluding the header row).
+    pub rows_read: usize,
+    pub rows_rejected: usize,
+}
+
+impl SupplierImportReport {
+    /// The most severe diagnostic in the report, or `Info` when there are none.
+    pub fn severity(&self) -> Severity {
+        self.diagnostics
+            .iter()
+            .map(|d| d.severity)
+            .max()
+            .unwrap_or(Severity::Info)
+    }
+
+    /// Short human-readable outcome, e.g. "4 suppliers read, 1 row rejected".
+    pub fn summary(&self) -> String {
+        let read = self.suppliers.len();
+        let mut summary = format!("{read} {} read", plural(read, "supplier"));
+        if self.rows_rejected > 0 {
+            summary.push_str(&format!(
+                ", {} {} rejected",
+                self.rows_rejected,
+                plural(self.rows_rejected, "row")
+            ));
+        }
+        summary
+    }
+}
+
+fn plural(count: usize, word: &str) -> String {
+    if count == 1 {
+        word.to_string()
+    } else {
+        format!("{word}s")
+    }
+}
+
+// Catalogue headers, compared after `normalize_header`.
+const NAME_HEADERS: [&str; 4] = ["supplier", "name", "supplier name", "vendor"];
+const CONTACT_HEADERS: [&str; 3] = ["contact", "contact name", "account manager"];
+const EMAIL_HEADERS: [&str; 3] = ["email", "e-mail", "contact email"];
+const PHONE_HEADERS: [&str; 3] = ["phone", "telephone", "tel"];
+const LEAD_TIME_HEADERS: [&str; 4] = [
+    "default lead time",
+    "lead time days",
+    "lead time",
+    "lead days",
+];
+const CURRENCY_HEADERS: [&str; 2] = ["currency", "ccy"];
+
+/// Read a supplier catalogue CSV with a Supplier column and optional
+/// Contact, Email, Phone, Lead time and Currency columns, in any order.
+/// Suppliers without a currency of their own get `currency`.
+pub fn import_suppliers_from_csv<P: AsRef<Path>>(
+    path: P,
+    currency: Currency,
+) -> Result<SupplierImportReport, CatalogueError> {
+    let path = path.as_ref();
+    let file = std::fs::File::open(path)?;
+    let mut report = import_suppliers_from_reader(file, currency)?;
+    report.source = path.display().to_string();
+    Ok(report)
+}
+
+/// Read a supplier catalogue from any CSV reader. See [`import_suppliers_from_csv`].
+pub fn import_suppliers_from_reader<R: Read>(
+    reader: R,
+    currency: Currency,
+) -> Result<SupplierImportReport, CatalogueError> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let headers: Vec<String> = rdr.headers()?.iter().map(str::to_string).collect();
+    let column = |names: &[&str]| {
+        headers.iter().position(|header| {
+            let header = normalize_header(header);
+            names.iter().any(|name| normalize_header(name) == header)
+        })
+    };
+    let mut report = SupplierImportReport::default();
+    let Some(name_column) = column(&NAME_HEADERS) else {
+        report.diagnostics.push(ImportDiagnostic {
+            line: Some(1),
+            column: None,
+            raw_value: None,
+            expected: None,
+            severity: Severity::Error,
+            message: "no column found for: supplier".to_string(),
+        });
+        return Ok(report);
+    };
+    let (contact_column, email_column, phone_column) = (
+        column(&CONTACT_HEADERS),
+        column(&EMAIL_HEADERS),
+        column(&PHONE_HEADERS),
+    );
+    let (lead_time_column, currency_column) =
+        (column(&LEAD_TIME_HEADERS), column(&CURRENCY_HEADERS));
+
+    for result in rdr.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(err) => {
+                report.rows_read += 1;
+                report.rows_rejected += 1;
+                report.diagnostics.push(ImportDiagnostic {
+                    line: err.position().map(|p| p.line()),
+                    column: None,
+                    raw_value: None,
+                    expected: None,
+                    severity: Severity::Error,
+                    message: format!("unreadable row: {err}"),
+                });
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line());
+        if record.iter().all(str::is_empty) {
+            continue;
+        }
+        report.rows_read += 1;
+
+        let cell = |column: Option<usize>| {
+            let value = record.get(column?)?;
+            (!value.is_empty()).then(|| value.to_string())
+        };
+        let mut problems = Vec::new();
+        let mut reject = |column: usize, expected: Option<ExpectedType>, message: String| {
+            problems.push(ImportDiagnostic {
+                line,
+                column: Some(headers[column].clone()),
+                raw_value: record.get(column).map(str::to_string),
+                expected,
+                severity: Severity::Error,
+                message,
+            });
+        };
+
+        let name = cell(Some(name_column));
+        if name.is_none() {
+            reject(
+                name_column,
+                Some(ExpectedType::Text),
+                "value is empty".to_string(),
+            );
+        }
+        let default_lead_time_days = match (lead_time_column, cell(lead_time_column)) {
+            (Some(column), Some(raw)) => match raw.parse() {
+                Ok(days) if days > MAX_LEAD_TIME_DAYS => {
+                    reject(
+                        column,
+                        Some(ExpectedType::WholeNumber),
+                        format!("{LEAD_TIME_TOO_LONG} (got \"{raw}\")"),
+                    );
+                    None
+                }
+                Ok(days) => Some(days),
+                Err(_) => {
+                    reject(
+                        column,
+                        Some(ExpectedType::WholeNumber),
+                        format!("not a whole number (got \"{raw}\", expected whole number)"),
+                    );
+                    None
+                }
+            },
+            _ => None,
+        };
+        let supplier_currency = match (currency_column, cell(currency_column)) {
+            (Some(column), Some(raw)) => match raw.parse() {
+                Ok(code) => code,
+                Err(err) => {
+                    reject(column, None, format!("{err}"));
+                    currency
+                }
+            },
+            _ => currency,
+        };
+
+        if !problems.is_empty() {
+            report.diagnostics.append(&mut problems);
+            report.rows_rejected += 1;
+            continue;
+        }
+        let supplier = Supplier {
+            name: name.unwrap_or_default(),
+            contact: cell(contact_column),
+            email: cell(email_column),
+            phone: cell(phone_column),
+            default_lead_time_days,
+            currency: supplier_currency,
+        };
+        match report
+            .suppliers
+            .iter_mut()
+            .find(|earlier| earlier.name.eq_ignore_ascii_case(&supplier.name))
+        {
+            Some(earlier) => {
+                report.diagnostics.push(ImportDiagnostic {
+                    line,
+                    column: Some(headers[name_column].clone()),
+                    raw_value: Some(supplier.name.clone()),
+                    expected: None,
+                    severity: Severity::Warning,
+                    message: format!(
+                        "{} is listed more than once; this row replaces the earlier one",
+                        supplier.name
+                    ),
+                });
+                *earlier = supplier;
+            }
+            None => report.suppliers.push(supplier),
+        }
+    }
+
+    if report.rows_read == 0 {
+        report.diagnostics.push(ImportDiagnostic {
+            line: None,
+            column: None,
+            raw_value: None,
+            expected: None,
+            severity: Severity::Warning,
+            message: "file contains no data rows".to_string(),
+        });
+    }
+    Ok(report)
+}
+
+/// The bought lines from one supplier.
+#[derive(Debug, Clone)]
+pub struct SupplierGroup {
+    /// The supplier as the first of its lines names it; `None` for lines
+    /// that name no supplier.
+    pub supplier: Option<String>,
+    /// Each line with its BoM index, in BoM order.
+    pub lines: Vec<(usize, BomItem)>,
+    pub subtotal: Money,
+}
+
+/// Group `lines` by the supplier they name, ignoring ASCII case as the
+/// catalogue does: suppliers in name order, then the lines with no supplier.
+pub fn group_by_supplier(
+    lines: impl IntoIterator<Item = (usize, BomItem)>,
+    currency: Currency,
+) -> Result<Vec<SupplierGroup>, MoneyError> {
+    let mut named: BTreeMap<String, SupplierGroup> = BTreeMap::new();
+    let mut unassigned = SupplierGroup {
+        supplier: None,
+        lines: Vec::new(),
+        subtotal: Money::zero(currency),
+    };
+    for (index, item) in lines {
+        let group = match item.supplier() {
+            Some(name) => named
+                .entry(name.to_ascii_lowercase())
+                .or_insert_with(|| SupplierGroup {
+                    supplier: Some(name.to_string()),
+                    lines: Vec::new(),
+                    subtotal: Money::zero(currency),
+                }),
+            None => &mut unassigned,
+        };
+        group.subtotal = group.subtotal.checked_add(item.total_cost)?;
+        group.lines.push((index, item));
+    }
+    let mut groups: Vec<SupplierGroup> = named.into_values().collect();
+    if !unassigned.lines.is_empty() {
+        groups.push(unassigned);
+    }
+    Ok(groups)
+}
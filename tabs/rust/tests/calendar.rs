@@ -0,0 +1,56 @@
+//! Working-day arithmetic must agree with counting day by day, and must
+//! neither hang nor panic on lead times that never come from a real supplier.
+
+use chrono::{Days, NaiveDate, Weekday};
+use tabs_backend::{WorkingCalendar, MAX_LEAD_TIME_DAYS};
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+fn calendars() -> Vec<WorkingCalendar> {
+    let mut with_holidays = WorkingCalendar::default();
+    with_holidays.add_holidays([date(2026, 6, 1), date(2026, 12, 25), date(2027, 1, 1)]);
+    let mut four_day_week = with_holidays.clone();
+    four_day_week.set_weekend_day(Weekday::Fri, true);
+    vec![WorkingCalendar::default(), with_holidays, four_day_week]
+}
+
+#[test]
+fn skipping_whole_weeks_matches_counting_day_by_day() {
+    for calendar in calendars() {
+        for offset in 0..40 {
+            let start = date(2026, 5, 1) + Days::new(offset * 9);
+            for days in [0, 1, 4, 5, 6, 11, 57, 400] {
+                let mut later = calendar.next_working_day(start);
+                let mut earlier = calendar.previous_working_day(start);
+                for _ in 0..days {
+                    later = calendar.next_working_day(later + Days::new(1));
+                    earlier = calendar.previous_working_day(earlier - Days::new(1));
+                }
+                assert_eq!(
+                    calendar.add_working_days(start, days),
+                    later,
+                    "{start} + {days}"
+                );
+                assert_eq!(
+                    calendar.subtract_working_days(start, days),
+                    earlier,
+                    "{start} - {days}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn lead_times_past_the_calendar_stop_at_its_ends() {
+    let calendar = &calendars()[1];
+    let start = date(2026, 5, 1);
+    assert_eq!(calendar.add_working_days(start, u32::MAX), NaiveDate::MAX);
+    assert_eq!(
+        calendar.subtract_working_days(start, u32::MAX),
+        NaiveDate::MIN
+    );
+    assert!(calendar.add_working_days(start, MAX_LEAD_TIME_DAYS) < date(2035, 1, 1));
+}
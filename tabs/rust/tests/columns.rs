@@ -0,0 +1,121 @@
+//! Headers from real supplier exports resolve onto BoM fields however they
+//! are spelled, cased or punctuated.
+
+use tabs_backend::{normalize_header, BomField, ColumnMapping};
+
+fn headers(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn normalizing_keeps_only_lowercase_letters_and_digits() {
+    for (header, normalized) in [
+        ("Min QTY.", "minqty"),
+        ("min_qty", "minqty"),
+        ("  MIN  QTY ", "minqty"),
+        ("Lead-time (days)", "leadtimedays"),
+        ("Part No.", "partno"),
+        ("D(Δ)", "dδ"),
+        ("Größe", "größe"),
+        ("Qty2", "qty2"),
+        ("", ""),
+        ("#/*", ""),
+    ] {
+        assert_eq!(normalize_header(header), normalized, "{header:?}");
+    }
+}
+
+#[test]
+fn headers_resolve_through_their_aliases() {
+    let mapping = ColumnMapping::standard();
+    for (header, field) in [
+        ("Material", Some(BomField::Name)),
+        ("DESCRIPTION", Some(BomField::Name)),
+        ("Qty", Some(BomField::Quantity)),
+        ("Unit Price", Some(BomField::UnitCost)),
+        ("unit_cost", Some(BomField::UnitCost)),
+        ("Extended cost", Some(BomField::TotalCost)),
+        ("Line total", Some(BomField::TotalCost)),
+        ("Lead time (days)", Some(BomField::LeadTimeDays)),
+        ("Min QTY.", Some(BomField::MinQuantity)),
+        ("MOQ", Some(BomField::MinQuantity)),
+        ("Tax category", Some(BomField::Category)),
+        ("Order multiple", Some(BomField::PackSize)),
+        ("BoM level", Some(BomField::Level)),
+        ("Vendor", Some(BomField::Supplier)),
+        ("Part no.", Some(BomField::PartNumber)),
+        ("S.K.U.", Some(BomField::PartNumber)),
+        ("Colour", None),
+        ("Notes", None),
+        ("", None),
+    ] {
+        assert_eq!(mapping.field_for(header), field, "{header:?}");
+    }
+}
+
+#[test]
+fn added_aliases_are_matched_the_same_way() {
+    let mapping = ColumnMapping::standard().with_alias(BomField::Quantity, "Stück");
+    assert_eq!(mapping.field_for("STÜCK"), Some(BomField::Quantity));
+    assert_eq!(mapping.field_for("qty"), Some(BomField::Quantity));
+}
+
+#[test]
+fn columns_resolve_in_any_order_with_duplicates_and_extras_kept_apart() {
+    let columns = ColumnMapping::standard().resolve(&headers(&[
+        "Qty",
+        "Colour",
+        "Material",
+        "Price",
+        "Total",
+        "Lead",
+        "MOQ",
+        "Description",
+        "Unit cost",
+    ]));
+    assert_eq!(
+        columns.fields,
+        [
+            (BomField::Quantity, 0),
+            (BomField::Name, 2),
+            (BomField::UnitCost, 3),
+            (BomField::TotalCost, 4),
+            (BomField::LeadTimeDays, 5),
+            (BomField::MinQuantity, 6),
+        ]
+    );
+    assert_eq!(
+        columns.duplicates,
+        [(BomField::Name, 7), (BomField::UnitCost, 8)]
+    );
+    assert_eq!(columns.extra, [1]);
+    assert_eq!(columns.position(BomField::Name), Some(2));
+    assert_eq!(columns.position(BomField::Supplier), None);
+    assert!(columns.missing().is_empty());
+}
+
+#[test]
+fn only_required_fields_are_reported_missing() {
+    for (row, missing) in [
+        (&[][..], &BomField::ALL[..6]),
+        (
+            &["Material", "Qty", "Supplier", "Level"][..],
+            &[
+                BomField::UnitCost,
+                BomField::TotalCost,
+                BomField::LeadTimeDays,
+                BomField::MinQuantity,
+            ][..],
+        ),
+        (
+            &["Material", "Qty", "Price", "Total", "Lead", "MOQ"][..],
+            &[][..],
+        ),
+    ] {
+        assert_eq!(
+            ColumnMapping::standard().resolve(&headers(row)).missing(),
+            missing,
+            "{row:?}"
+        );
+    }
+}
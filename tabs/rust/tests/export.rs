@@ -0,0 +1,94 @@
+//! An exported BoM view reads back as the same assemblies, however the view
+//! was sorted or filtered.
+
+use std::path::PathBuf;
+use tabs_backend::{AppState, BomField, BomQuery, BomSortKey, BomTree, SortDirection};
+
+/// Two assemblies, the second nested in the first, and a loose part.
+const BOM: &str = "\
+Material,QTY,Unit cost,Line total,Lead time,Min QTY,Level
+Frame kit,2,10.00,20.00,5,1,0
+Bolt,8,0.20,1.60,3,1,1
+Bracket set,1,4.00,4.00,7,1,1
+Bracket,4,0.50,2.00,9,1,2
+Cord grip,50,0.02,1.00,2,1,0
+";
+
+fn scratch(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("tabs-export-{}-{name}.csv", std::process::id()))
+}
+
+fn estimate(name: &str) -> AppState {
+    let path = scratch(name);
+    std::fs::write(&path, BOM).unwrap();
+    let state = AppState::demo_with_bom_path(&path);
+    std::fs::remove_file(&path).unwrap();
+    state
+}
+
+/// Export `query`'s view and read it back as each line's name and the name
+/// of its assembly.
+fn round_trip(state: &mut AppState, name: &str, query: &BomQuery) -> Vec<(String, Option<String>)> {
+    let path = scratch(&format!("{name}-out"));
+    state.export_bom(&path, query).unwrap();
+    let reread = AppState::demo_with_bom_path(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(
+        reread.bom_import.diagnostics.is_empty(),
+        "{:?}",
+        reread.bom_import.diagnostics
+    );
+    parents(&reread)
+}
+
+fn parents(state: &AppState) -> Vec<(String, Option<String>)> {
+    let tree = BomTree::build(state.bom()).unwrap();
+    state
+        .bom()
+        .iter()
+        .zip(&tree.nodes)
+        .map(|(item, node)| {
+            (
+                item.name.clone(),
+                node.parent.map(|parent| state.bom()[parent].name.clone()),
+            )
+        })
+        .collect()
+}
+
+fn line(name: &str, parent: Option<&str>) -> (String, Option<String>) {
+    (name.to_string(), parent.map(str::to_string))
+}
+
+#[test]
+fn a_sorted_view_exports_the_same_assemblies() {
+    let mut state = estimate("sorted");
+    let original = parents(&state);
+    let query = BomQuery {
+        sort: Some((
+            BomSortKey::Field(BomField::LeadTimeDays),
+            SortDirection::Descending,
+        )),
+        ..BomQuery::default()
+    };
+    assert_ne!(state.query_bom(&query), [0, 1, 2, 3, 4]);
+    assert_eq!(round_trip(&mut state, "sorted", &query), original);
+}
+
+#[test]
+fn parts_of_a_filtered_out_assembly_move_up_to_the_nearest_kept() {
+    let mut state = estimate("filtered");
+    let query = BomQuery {
+        name: "b".to_string(),
+        sort: Some((BomSortKey::Field(BomField::Name), SortDirection::Ascending)),
+        ..BomQuery::default()
+    };
+    assert_eq!(
+        round_trip(&mut state, "filtered", &query),
+        [
+            line("Bolt", None),
+            line("Bracket set", None),
+            line("Bracket", Some("Bracket set")),
+        ]
+    );
+}
@@ -0,0 +1,180 @@
+//! Undo and redo, one step per user action however many changes it makes.
+
+use std::path::PathBuf;
+use tabs_backend::{AppState, BomField, Change, History, Rate};
+
+fn threshold(before: i64, after: i64) -> Change {
+    Change::SurplusThreshold {
+        before: Rate::from_percent(before),
+        after: Rate::from_percent(after),
+    }
+}
+
+fn thresholds(changes: &[Change]) -> Vec<(i64, i64)> {
+    changes
+        .iter()
+        .map(|change| match change {
+            Change::SurplusThreshold { before, after } => {
+                (before.basis_points / 100, after.basis_points / 100)
+            }
+            other => panic!("unexpected change {other}"),
+        })
+        .collect()
+}
+
+fn example(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../examples")
+        .join(name)
+}
+
+#[test]
+fn a_group_undoes_and_redoes_as_one_step() {
+    let mut history = History::default();
+    history.record(threshold(0, 5));
+    history.begin_group("tidy thresholds");
+    history.record(threshold(5, 10));
+    history.record(threshold(10, 15));
+    history.end_group();
+
+    assert_eq!(history.undo_label(), Some("tidy thresholds"));
+    let step = history.take_undo().unwrap();
+    assert_eq!(thresholds(&step.changes), [(5, 10), (10, 15)]);
+    assert_eq!(history.undo_label(), Some("set surplus threshold"));
+    assert_eq!(history.redo_label(), Some("tidy thresholds"));
+
+    let step = history.take_redo().unwrap();
+    assert_eq!(thresholds(&step.changes), [(5, 10), (10, 15)]);
+    assert_eq!(history.undo_label(), Some("tidy thresholds"));
+    assert_eq!(history.redo_label(), None);
+}
+
+#[test]
+fn nested_groups_make_one_step_under_the_outer_label() {
+    let mut history = History::default();
+    history.begin_group("outer");
+    history.record(threshold(0, 5));
+    history.begin_group("inner");
+    history.record(threshold(5, 10));
+    history.end_group();
+    assert_eq!(
+        history.undo_label(),
+        None,
+        "still collecting the outer group"
+    );
+    assert!(history.is_modified());
+    history.record(threshold(10, 15));
+    history.end_group();
+
+    let step = history.take_undo().unwrap();
+    assert_eq!(step.label, "outer");
+    assert_eq!(thresholds(&step.changes), [(0, 5), (5, 10), (10, 15)]);
+    assert!(history.take_undo().is_none());
+}
+
+#[test]
+fn an_empty_group_leaves_no_step() {
+    let mut history = History::default();
+    history.begin_group("nothing");
+    history.end_group();
+    assert_eq!(history.undo_label(), None);
+    assert!(!history.is_modified());
+}
+
+#[test]
+fn recording_clears_the_redo_stack() {
+    let mut history = History::default();
+    history.record(threshold(0, 5));
+    history.record(threshold(5, 10));
+    history.take_undo().unwrap();
+    assert!(history.redo_label().is_some());
+
+    history.record(threshold(5, 20));
+    assert_eq!(history.redo_label(), None);
+
+    history.take_undo().unwrap();
+    history.begin_group("grouped");
+    history.record(threshold(5, 30));
+    assert_eq!(
+        history.redo_label(),
+        None,
+        "cleared as soon as the group records"
+    );
+    history.end_group();
+    assert!(history.take_redo().is_none());
+}
+
+#[test]
+fn a_supplier_edit_and_the_lead_time_it_fills_undo_together() {
+    let mut state = AppState::demo_with_bom_path(example("bom.csv"));
+    state.import_suppliers(example("suppliers.csv")).unwrap();
+    state.set_bom_cell(0, BomField::LeadTimeDays, "0").unwrap();
+    let supplier = state.bom()[0].supplier().map(str::to_string);
+
+    state
+        .set_bom_cell(0, BomField::Supplier, "Fastfix Supplies")
+        .unwrap();
+    assert_eq!(state.bom()[0].supplier(), Some("Fastfix Supplies"));
+    assert_eq!(state.bom()[0].lead_time_days, 7);
+    assert_eq!(
+        state.history().undo_label(),
+        Some("set supplier and lead time on line 1")
+    );
+
+    state.undo().unwrap();
+    assert_eq!(state.bom()[0].supplier(), supplier.as_deref());
+    assert_eq!(state.bom()[0].lead_time_days, 0);
+    assert_eq!(
+        state.history().undo_label(),
+        Some("edit lead time on line 1")
+    );
+
+    state.redo().unwrap();
+    assert_eq!(state.bom()[0].supplier(), Some("Fastfix Supplies"));
+    assert_eq!(state.bom()[0].lead_time_days, 7);
+}
+
+#[test]
+fn a_typed_cell_undoes_as_one_step_and_is_logged_once() {
+    let mut state = AppState::demo_with_bom_path(example("bom.csv"));
+    let logged = |state: &AppState| {
+        state
+            .log()
+            .events()
+            .filter(|event| {
+                event.message == "line edited" || event.message == "line totals checked"
+            })
+            .count()
+    };
+    let before = logged(&state);
+
+    state.begin_cell_edit(0, BomField::Quantity);
+    for value in ["1", "12", "120"] {
+        state.set_bom_cell(0, BomField::Quantity, value).unwrap();
+        assert_eq!(state.bom()[0].quantity.to_string(), value);
+    }
+    assert_eq!(logged(&state), before, "nothing logged while typing");
+    assert!(state.end_cell_edit());
+    assert_eq!(logged(&state), before + 2);
+    let edited = state
+        .log()
+        .events()
+        .rev()
+        .find(|event| event.message == "line edited")
+        .unwrap();
+    assert!(edited
+        .fields
+        .contains(&("value".to_string(), "120".to_string())));
+
+    assert_eq!(
+        state.history().undo_label(),
+        Some("edit quantity on line 1")
+    );
+    state.undo().unwrap();
+    assert_eq!(state.bom()[0].quantity, 10);
+    assert_eq!(state.history().undo_label(), None);
+
+    state.begin_cell_edit(1, BomField::Name);
+    assert!(!state.end_cell_edit(), "an edit that set nothing");
+    assert_eq!(state.history().undo_label(), None);
+}
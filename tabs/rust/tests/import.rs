@@ -0,0 +1,172 @@
+//! Bad rows are rejected with a diagnostic pointing at the cell, and never
+//! imported with zeroed fields.
+
+use tabs_backend::{
+    import_bom_from_reader, BomImportOptions, BomImportReport, ExpectedType, Severity,
+};
+
+const HEADER: &str = "Material,QTY,Unit cost,Line total,Lead time,Min QTY\n";
+
+fn import(body: &[u8]) -> BomImportReport {
+    let file = [HEADER.as_bytes(), body].concat();
+    import_bom_from_reader(&file[..], &BomImportOptions::default()).unwrap()
+}
+
+/// A diagnostic's line, column, raw value, expected type and severity.
+type Cell<'a> = (
+    Option<u64>,
+    Option<&'a str>,
+    Option<&'a str>,
+    Option<ExpectedType>,
+    Severity,
+);
+
+fn cells(report: &BomImportReport) -> Vec<Cell<'_>> {
+    report
+        .diagnostics
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic.line,
+                diagnostic.column.as_deref(),
+                diagnostic.raw_value.as_deref(),
+                diagnostic.expected,
+                diagnostic.severity,
+            )
+        })
+        .collect()
+}
+
+fn names(report: &BomImportReport) -> Vec<&str> {
+    report.items.iter().map(|item| item.name.as_str()).collect()
+}
+
+#[test]
+fn bad_number_and_amount_cells_reject_their_row() {
+    let report = import(
+        b"Bolt,ten,0.20,2.00,3,1\n\
+          Nut,40,\xc2\xa30.05,2.00,3,1\n\
+          Washer,5,0.10,0.500001,3,1\n\
+          Screw,5,0.10,0.50,3.5,-1\n\
+          Rivet,5,0.10,0.50,3,1\n",
+    );
+    assert_eq!(names(&report), ["Rivet"]);
+    assert_eq!((report.rows_read, report.rows_rejected), (5, 4));
+    assert_eq!(report.rows_imported(), 1);
+    use ExpectedType::{Money, WholeNumber};
+    use Severity::Error;
+    assert_eq!(
+        cells(&report),
+        [
+            (Some(2), Some("QTY"), Some("ten"), Some(WholeNumber), Error),
+            (
+                Some(3),
+                Some("Unit cost"),
+                Some("£0.05"),
+                Some(Money),
+                Error
+            ),
+            (
+                Some(4),
+                Some("Line total"),
+                Some("0.500001"),
+                Some(Money),
+                Error
+            ),
+            (
+                Some(5),
+                Some("Lead time"),
+                Some("3.5"),
+                Some(WholeNumber),
+                Error
+            ),
+            (
+                Some(5),
+                Some("Min QTY"),
+                Some("-1"),
+                Some(WholeNumber),
+                Error
+            ),
+        ]
+    );
+    assert!(report.diagnostics[2]
+        .message
+        .starts_with("too many decimal places"));
+    assert_eq!(report.summary(), "1 row imported, 4 rows rejected");
+}
+
+#[test]
+fn short_rows_are_rejected_for_each_missing_value() {
+    let report = import(b"Bolt,10,0.20,2.00\nNut\nWasher,5,0.10,0.50,3,1\n");
+    assert_eq!(names(&report), ["Washer"]);
+    assert_eq!((report.rows_read, report.rows_rejected), (3, 2));
+    let missing: Vec<_> = report
+        .diagnostics
+        .iter()
+        .map(|diagnostic| {
+            assert_eq!(diagnostic.raw_value, None);
+            assert!(diagnostic.message.starts_with("value is missing"));
+            (diagnostic.line, diagnostic.column.as_deref())
+        })
+        .collect();
+    assert_eq!(
+        missing,
+        [
+            (Some(2), Some("Lead time")),
+            (Some(2), Some("Min QTY")),
+            (Some(3), Some("QTY")),
+            (Some(3), Some("Unit cost")),
+            (Some(3), Some("Line total")),
+            (Some(3), Some("Lead time")),
+            (Some(3), Some("Min QTY")),
+        ]
+    );
+}
+
+#[test]
+fn blank_rows_are_skipped_without_counting() {
+    let report = import(b"Bolt,10,0.20,2.00,3,1\n,,,,,\n  ,\t,,,,\n\nNut,40,0.05,2.00,3,1\n");
+    assert_eq!(names(&report), ["Bolt", "Nut"]);
+    assert_eq!((report.rows_read, report.rows_rejected), (2, 0));
+    assert!(report.diagnostics.is_empty());
+    assert!(report.is_clean());
+}
+
+#[test]
+fn unreadable_rows_are_counted_as_rejected() {
+    let report =
+        import(b"Bolt,10,0.20,2.00,3,1\nN\xffut,40,0.05,2.00,3,1\nWasher,5,0.10,0.50,3,1\n");
+    assert_eq!(names(&report), ["Bolt", "Washer"]);
+    assert_eq!((report.rows_read, report.rows_rejected), (3, 1));
+    assert_eq!(report.diagnostics.len(), 1);
+    let diagnostic = &report.diagnostics[0];
+    assert_eq!(diagnostic.line, Some(3));
+    assert_eq!(diagnostic.column, None);
+    assert_eq!(diagnostic.severity, Severity::Error);
+    assert!(diagnostic.message.starts_with("unreadable row"));
+}
+
+#[test]
+fn a_header_only_file_warns_that_it_has_no_rows() {
+    let report = import(b"");
+    assert!(report.items.is_empty());
+    assert_eq!((report.rows_read, report.rows_rejected), (0, 0));
+    assert_eq!(report.severity(), Severity::Warning);
+    assert_eq!(report.diagnostics.len(), 1);
+    assert_eq!(report.diagnostics[0].message, "file contains no data rows");
+    assert_eq!(report.summary(), "0 rows imported");
+}
+
+#[test]
+fn a_missing_required_column_rejects_the_whole_file() {
+    let file = b"Material,QTY,Unit cost\nBolt,10,0.20\n";
+    let report = import_bom_from_reader(&file[..], &BomImportOptions::default()).unwrap();
+    assert!(report.items.is_empty());
+    assert_eq!(report.rows_read, 0);
+    assert_eq!(report.diagnostics.len(), 1);
+    assert_eq!(report.diagnostics[0].line, Some(1));
+    assert_eq!(
+        report.diagnostics[0].message,
+        "no column found for: line total, lead time, minimum quantity"
+    );
+}
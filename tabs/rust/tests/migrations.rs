@@ -0,0 +1,171 @@
+//! Every project format version ever released must still open.
+//!
+//! `tests/fixtures/project_v<N>.tabs.json` is the same small estimate as
+//! saved by the build that wrote format version N. When the format changes,
+//! add a fixture saved by the last build of the old version; never edit the
+//! existing ones.
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde_json::{json, Value as JsonValue};
+use std::path::PathBuf;
+use tabs_backend::{
+    load_project, migrate_project, project_from_str, project_to_string, MigrationError,
+    ProjectError, ProjectFile, OLDEST_PROJECT_VERSION, PROJECT_VERSION,
+};
+
+fn fixture_path(version: u64) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("project_v{version}.tabs.json"))
+}
+
+fn load_fixture(version: u64) -> ProjectFile {
+    load_project(&fixture_path(version))
+        .unwrap_or_else(|err| panic!("format version {version} fixture: {err}"))
+}
+
+fn fixture_document(version: u64) -> JsonValue {
+    let text = std::fs::read_to_string(fixture_path(version)).unwrap();
+    serde_json::from_str(&text).unwrap()
+}
+
+/// The estimate every fixture holds, checked field by field.
+fn assert_fixture_estimate(project: &ProjectFile) {
+    let state = &project.state;
+    let lines: Vec<_> = state
+        .bom
+        .iter()
+        .map(|item| {
+            (
+                item.name.as_str(),
+                item.quantity,
+                item.unit_cost.amount_string(),
+                item.total_cost.amount_string(),
+                item.lead_time_days,
+                item.supplier(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        lines,
+        [
+            (
+                "Cable tray 3m",
+                12,
+                "18.50".into(),
+                "222.00".into(),
+                10,
+                Some("Acme")
+            ),
+            ("Junction box", 40, "3.25".into(), "130.00".into(), 5, None),
+            (
+                "Mounting bracket",
+                25,
+                "1.10".into(),
+                "27.50".into(),
+                15,
+                Some("Brackets Ltd")
+            ),
+        ]
+    );
+    assert!(state.bom.iter().all(|item| item.level == 0));
+    // Supplied totals used to be kept only in the reconciliation.
+    assert!(state
+        .bom
+        .iter()
+        .all(|item| item.file_total == Some(item.total_cost)));
+    // The supplier used to be kept with the other unrecognised columns.
+    assert!(state.bom.iter().all(|item| item.extra.is_empty()));
+    assert!(state.bom.iter().all(|item| item.part_number.is_none()));
+    assert!(state.suppliers.suppliers.is_empty());
+    assert_eq!(state.bom[1].category.as_deref(), Some("Electrical"));
+    assert_eq!(state.bom[2].pack_size, Some(10));
+
+    assert_eq!(
+        state.planning.installation_date,
+        NaiveDate::from_ymd_opt(2026, 3, 2)
+    );
+    // Acme does not work Fridays; our own calendar does.
+    let friday = NaiveDate::from_ymd_opt(2026, 1, 16).unwrap();
+    assert_eq!(friday.weekday(), Weekday::Fri);
+    assert!(!state.planning.supplier_calendars["Acme"].is_working_day(friday));
+    assert!(state.planning.calendar.is_working_day(friday));
+    assert!(state.revisions.is_empty());
+    assert!(state.purchase_orders.is_empty());
+    assert!(project.saved_at.is_some());
+    assert!(project.quote_pdf.is_none());
+}
+
+#[test]
+fn every_format_version_has_a_fixture_that_opens() {
+    for version in OLDEST_PROJECT_VERSION..=PROJECT_VERSION {
+        let project = load_fixture(version);
+        assert_eq!(project.format_version, version);
+        assert_fixture_estimate(&project);
+    }
+}
+
+#[test]
+fn upgrades_apply_one_step_per_version() {
+    for version in OLDEST_PROJECT_VERSION..=PROJECT_VERSION {
+        let mut document = fixture_document(version);
+        let steps = migrate_project(&mut document, version).unwrap();
+        let froms: Vec<u64> = steps.iter().map(|step| step.from).collect();
+        assert_eq!(froms, (version..PROJECT_VERSION).collect::<Vec<_>>());
+        assert_eq!(
+            document.get("version").and_then(JsonValue::as_u64),
+            Some(PROJECT_VERSION)
+        );
+    }
+}
+
+#[test]
+fn upgraded_projects_save_in_the_current_format() {
+    for version in OLDEST_PROJECT_VERSION..=PROJECT_VERSION {
+        let project = load_fixture(version);
+        let text = project_to_string(&project.state, project.saved_at.unwrap(), None).unwrap();
+        let reread = project_from_str(&text).unwrap();
+        assert_eq!(reread.format_version, PROJECT_VERSION);
+        assert_fixture_estimate(&reread);
+    }
+}
+
+#[test]
+fn quote_pdf_attachment_survives_a_save() {
+    let project = load_fixture(PROJECT_VERSION);
+    let pdf = b"%PDF-1.7\n\x00\xff binary body\n%%EOF";
+    let text = project_to_string(&project.state, project.saved_at.unwrap(), Some(pdf)).unwrap();
+    assert_eq!(
+        project_from_str(&text).unwrap().quote_pdf.as_deref(),
+        Some(&pdf[..])
+    );
+
+    let mut document: JsonValue = serde_json::from_str(&text).unwrap();
+    document["quote_pdf"] = json!("not base64!");
+    assert!(matches!(
+        project_from_str(&document.to_string()),
+        Err(ProjectError::InvalidAttachment)
+    ));
+}
+
+#[test]
+fn rejects_versions_it_cannot_upgrade() {
+    let with_version = |version: u64| {
+        let mut document = fixture_document(PROJECT_VERSION);
+        document["version"] = json!(version);
+        project_from_str(&document.to_string())
+    };
+
+    match with_version(PROJECT_VERSION + 1) {
+        Err(ProjectError::Migration(MigrationError::TooNew(version))) => {
+            assert_eq!(version, PROJECT_VERSION + 1)
+        }
+        other => panic!("expected TooNew, got {other:?}"),
+    }
+    match with_version(OLDEST_PROJECT_VERSION - 1) {
+        Err(ProjectError::Migration(MigrationError::TooOld(version))) => {
+            assert_eq!(version, OLDEST_PROJECT_VERSION - 1)
+        }
+        other => panic!("expected TooOld, got {other:?}"),
+    }
+}
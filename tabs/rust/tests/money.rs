@@ -0,0 +1,126 @@
+//! Amounts parse, round and print exactly, and report overflow instead of
+//! wrapping.
+
+use tabs_backend::{Currency, Money, MoneyError, RoundingMode};
+
+fn gbp(amount: &str) -> Money {
+    Money::parse(amount, Currency::GBP).unwrap()
+}
+
+#[test]
+fn parses_plain_decimals_exactly() {
+    for (raw, expected) in [
+        ("24.29", "24.29"),
+        (" 24.29 ", "24.29"),
+        ("+3.5", "3.50"),
+        ("-3.5", "-3.50"),
+        ("0.0125", "0.0125"),
+        (".5", "0.50"),
+        ("7.", "7.00"),
+        ("1.23450000", "1.2345"),
+        ("922337203685477.5807", "922337203685477.5807"),
+    ] {
+        assert_eq!(gbp(raw).amount_string(), expected, "{raw:?}");
+    }
+}
+
+#[test]
+fn rejects_what_is_not_an_amount() {
+    for raw in ["", "-", ".", "£3", "1,000", "1.2.3", "1e3", "--1", " 1 2"] {
+        assert_eq!(
+            Money::parse(raw, Currency::GBP),
+            Err(MoneyError::InvalidAmount(raw.to_string())),
+            "{raw:?}"
+        );
+    }
+    assert_eq!(
+        Money::parse("0.00001", Currency::GBP),
+        Err(MoneyError::TooPrecise("0.00001".to_string()))
+    );
+    for raw in ["922337203685477.5808", "99999999999999999999"] {
+        assert_eq!(
+            Money::parse(raw, Currency::GBP),
+            Err(MoneyError::Overflow),
+            "{raw:?}"
+        );
+    }
+}
+
+#[test]
+fn rounds_ties_and_negative_amounts_by_mode() {
+    let rounded = |amount: &str, mode| gbp(amount).round(2, mode).amount_string();
+    for (amount, half_up, half_even, truncate) in [
+        ("2.345", "2.35", "2.34", "2.34"),
+        ("2.355", "2.36", "2.36", "2.35"),
+        ("2.3449", "2.34", "2.34", "2.34"),
+        ("2.3451", "2.35", "2.35", "2.34"),
+        ("-2.345", "-2.35", "-2.34", "-2.34"),
+        ("-2.3451", "-2.35", "-2.35", "-2.34"),
+        ("2.34", "2.34", "2.34", "2.34"),
+    ] {
+        assert_eq!(rounded(amount, RoundingMode::HalfUp), half_up, "{amount}");
+        assert_eq!(
+            rounded(amount, RoundingMode::HalfEven),
+            half_even,
+            "{amount}"
+        );
+        assert_eq!(
+            rounded(amount, RoundingMode::Truncate),
+            truncate,
+            "{amount}"
+        );
+    }
+}
+
+#[test]
+fn displays_with_symbol_grouping_and_two_places() {
+    for (money, shown) in [
+        (gbp("1143"), "£1,143.00"),
+        (gbp("1234567.891"), "£1,234,567.89"),
+        (gbp("-0.005"), "-£0.01"),
+        (gbp("999.995"), "£1,000.00"),
+        (gbp("0"), "£0.00"),
+        (Money::parse("12.5", Currency::EUR).unwrap(), "€12.50"),
+        (
+            Money::parse("12.5", "CHF".parse().unwrap()).unwrap(),
+            "12.50 CHF",
+        ),
+    ] {
+        assert_eq!(money.to_string(), shown);
+    }
+}
+
+#[test]
+fn minor_units_convert_and_overflow_is_reported() {
+    assert_eq!(Money::from_minor(2429, Currency::GBP), Ok(gbp("24.29")));
+    assert_eq!(Money::from_minor(-5, Currency::GBP), Ok(gbp("-0.05")));
+    assert_eq!(
+        Money::from_minor(i64::MAX / 10, Currency::GBP),
+        Err(MoneyError::Overflow)
+    );
+    assert_eq!(
+        Money::from_minor(i64::MIN, Currency::GBP),
+        Err(MoneyError::Overflow)
+    );
+
+    let largest = gbp("922337203685477.5807");
+    let smallest = gbp("-922337203685477.5807")
+        .checked_sub(gbp("0.0001"))
+        .unwrap();
+    assert_eq!(
+        largest.checked_add(gbp("0.0001")),
+        Err(MoneyError::Overflow)
+    );
+    assert_eq!(
+        smallest.checked_sub(gbp("0.0001")),
+        Err(MoneyError::Overflow)
+    );
+    assert_eq!(largest.checked_mul_quantity(2), Err(MoneyError::Overflow));
+    assert_eq!(smallest.checked_neg(), Err(MoneyError::Overflow));
+    assert_eq!(smallest.abs_diff(gbp("0")), Err(MoneyError::Overflow));
+    assert_eq!(largest.checked_neg().unwrap().checked_neg(), Ok(largest));
+    assert_eq!(
+        gbp("1").checked_add(Money::parse("1", Currency::EUR).unwrap()),
+        Err(MoneyError::CurrencyMismatch(Currency::GBP, Currency::EUR))
+    );
+}
@@ -0,0 +1,137 @@
+//! The standard UK pricing rules, and adjustments that would take the quote
+//! below what it costs.
+
+use tabs_backend::{
+    price_bom, Adjustment, AdjustmentKind, AppState, BomItem, Currency, Money, PricingError,
+    PricingRules, Rate, RoundingMode,
+};
+
+fn line(total: &str, category: Option<&str>) -> BomItem {
+    BomItem {
+        total_cost: Money::parse(total, Currency::GBP).unwrap(),
+        category: category.map(str::to_string),
+        ..BomItem::blank(Currency::GBP)
+    }
+}
+
+/// Each adjustment's amount and the running total after it, then the grand total.
+fn priced(items: &[BomItem], rules: &PricingRules) -> (Vec<(String, String)>, String) {
+    let breakdown = price_bom(items, rules, Currency::GBP, RoundingMode::HalfUp).unwrap();
+    let lines = breakdown
+        .lines
+        .iter()
+        .map(|line| {
+            (
+                line.amount.amount_string(),
+                line.running_total.amount_string(),
+            )
+        })
+        .collect();
+    (lines, breakdown.grand_total.amount_string())
+}
+
+fn pair(amount: &str, running_total: &str) -> (String, String) {
+    (amount.to_string(), running_total.to_string())
+}
+
+#[test]
+fn standard_rules_add_markup_then_delivery_then_vat() {
+    let rules = PricingRules::standard(Currency::GBP);
+    let labels: Vec<_> = rules
+        .adjustments
+        .iter()
+        .map(|adjustment| adjustment.label.as_str())
+        .collect();
+    assert_eq!(labels, ["Overheads & margin", "Delivery", "VAT"]);
+
+    let (lines, total) = priced(&[line("600", None), line("400", None)], &rules);
+    assert_eq!(
+        lines,
+        [
+            pair("150.00", "1150.00"),
+            pair("150.00", "1300.00"),
+            pair("260.00", "1560.00"),
+        ]
+    );
+    assert_eq!(total, "1560.00");
+
+    // 15% of 333.33 is 49.9995, rounded to 50.00 before VAT is charged on it.
+    let (lines, total) = priced(&[line("333.33", None)], &rules);
+    assert_eq!(
+        lines,
+        [
+            pair("50.00", "383.33"),
+            pair("150.00", "533.33"),
+            pair("106.67", "640.00"),
+        ]
+    );
+    assert_eq!(total, "640.00");
+}
+
+#[test]
+fn an_empty_bom_is_still_charged_delivery_and_its_vat() {
+    let (lines, total) = priced(&[], &PricingRules::standard(Currency::GBP));
+    assert_eq!(
+        lines,
+        [
+            pair("0.00", "0.00"),
+            pair("150.00", "150.00"),
+            pair("30.00", "180.00"),
+        ]
+    );
+    assert_eq!(total, "180.00");
+}
+
+#[test]
+fn a_category_tax_is_charged_on_that_category_s_share() {
+    let mut rules = PricingRules::standard(Currency::GBP);
+    rules.adjustments[2].kind = AdjustmentKind::Tax {
+        rate: Rate::from_percent(20),
+        category: Some("electrical".to_string()),
+    };
+    let items = [line("750", Some("Electrical")), line("250", None)];
+    // Three quarters of the 1300.00 running total is electrical.
+    let (lines, total) = priced(&items, &rules);
+    assert_eq!(lines[2], pair("195.00", "1495.00"));
+    assert_eq!(total, "1495.00");
+}
+
+#[test]
+fn values_that_would_lower_the_price_below_cost_are_rejected() {
+    let mut state = AppState::blank();
+    let before = state.settings().pricing.clone();
+    for (index, value) in [(0, "-5"), (1, "-10.00"), (2, "-20%")] {
+        assert!(
+            matches!(
+                state.set_adjustment_value(index, value),
+                Err(PricingError::OutOfRange { .. })
+            ),
+            "{value} at {index}"
+        );
+    }
+    assert_eq!(state.settings().pricing, before);
+    assert_eq!(state.history().undo_label(), None);
+
+    let discount = |percent| Adjustment {
+        label: "Discount".to_string(),
+        kind: AdjustmentKind::Discount(Rate::from_percent(percent)),
+    };
+    let err = state.add_adjustment(discount(150)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "150% is out of range (expected a discount from 0% to 100%)"
+    );
+    assert!(state.add_adjustment(discount(-1)).is_err());
+    assert_eq!(state.settings().pricing, before);
+
+    state.add_adjustment(discount(100)).unwrap();
+    state.set_adjustment_value(3, "12.5").unwrap();
+    assert!(state.set_adjustment_value(3, "100.01").is_err());
+    assert_eq!(
+        state.settings().pricing.adjustments[3].kind,
+        AdjustmentKind::Discount("12.5".parse().unwrap())
+    );
+    for (index, value) in [(0, "0"), (1, "0"), (2, "0")] {
+        state.set_adjustment_value(index, value).unwrap();
+    }
+}
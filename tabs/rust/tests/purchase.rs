@@ -0,0 +1,125 @@
+//! Purchase orders cover only what earlier orders have not, and a failed
+//! write leaves nothing behind that would stop the order being issued again.
+
+use chrono::NaiveDate;
+use std::path::PathBuf;
+use tabs_backend::{write_purchase_order, AppState, BomField, LeftOutReason};
+
+const BOM: &str = "\
+Material,QTY,Unit cost,Line total,Lead time,Min QTY,Supplier,Part no.
+Bolt,10,0.20,2.00,3,1,Acme,B-1
+Nut,40,0.05,2.00,3,1,Acme,N-1
+Washer,5,0.10,0.50,3,1,,W-1
+";
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("tabs-purchase-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn estimate(dir: &std::path::Path) -> AppState {
+    let path = dir.join("bom.csv");
+    std::fs::write(&path, BOM).unwrap();
+    let mut state = AppState::demo_with_bom_path(&path);
+    state.quote.number = "Q-0042".to_string();
+    state
+}
+
+fn monday() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 3, 2).unwrap()
+}
+
+#[test]
+fn later_orders_cover_only_what_earlier_ones_did_not() {
+    let dir = scratch("cover");
+    let mut state = estimate(&dir);
+    let first = state
+        .issue_purchase_orders(dir.join("orders"), monday())
+        .unwrap();
+    assert_eq!(first.len(), 1);
+    assert_eq!(first[0].reference(), "Q-0042-PO001");
+
+    // More nuts, and a second line for the same bolt.
+    state.set_bom_cell(1, BomField::Quantity, "50").unwrap();
+    let index = state.add_bom_line();
+    for (field, value) in [
+        (BomField::Name, "Bolt"),
+        (BomField::Quantity, "6"),
+        (BomField::UnitCost, "0.20"),
+        (BomField::Supplier, "Acme"),
+        (BomField::PartNumber, "B-1"),
+    ] {
+        state.set_bom_cell(index, field, value).unwrap();
+    }
+
+    let draft = state.draft_purchase_orders(monday()).unwrap();
+    assert_eq!(draft.orders.len(), 1);
+    let order = &draft.orders[0];
+    assert_eq!(order.number, 2);
+    let lines: Vec<_> = order
+        .lines
+        .iter()
+        .map(|line| (line.index, line.name.as_str(), line.required, line.quantity))
+        .collect();
+    assert_eq!(lines, [(1, "Nut", 10, 10), (3, "Bolt", 6, 6)]);
+    assert_eq!(order.total.amount_string(), "1.70");
+    let left_out: Vec<_> = draft
+        .left_out
+        .iter()
+        .map(|line| (line.index, line.reason))
+        .collect();
+    assert_eq!(
+        left_out,
+        [
+            (0, LeftOutReason::AlreadyOrdered),
+            (2, LeftOutReason::NoSupplier),
+        ]
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn the_csv_lists_each_line_of_the_order() {
+    let dir = scratch("csv");
+    let mut state = estimate(&dir);
+    let orders = dir.join("orders");
+    let issued = state.issue_purchase_orders(&orders, monday()).unwrap();
+
+    let csv = std::fs::read_to_string(issued[0].file_path(&orders, "csv")).unwrap();
+    assert_eq!(
+        csv.lines().collect::<Vec<_>>(),
+        [
+            "PO number,Supplier,Material,Part number,Required,Quantity,Unit cost,Line total,Lead time days,Delivery",
+            "Q-0042-PO001,Acme,Bolt,B-1,10,10,0.20,2.00,3,2026-03-05",
+            "Q-0042-PO001,Acme,Nut,N-1,40,40,0.05,2.00,3,2026-03-05",
+        ]
+    );
+    assert!(std::fs::read(issued[0].file_path(&orders, "pdf"))
+        .unwrap()
+        .starts_with(b"%PDF"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn a_failed_csv_write_leaves_no_pdf_behind() {
+    let dir = scratch("failed");
+    let state = estimate(&dir);
+    let order = state.draft_purchase_orders(monday()).unwrap().orders[0].clone();
+    let orders = dir.join("orders");
+    std::fs::create_dir_all(&orders).unwrap();
+    // A dangling link passes the up-front check but cannot be created anew.
+    let csv = order.file_path(&orders, "csv");
+    std::os::unix::fs::symlink(dir.join("nowhere.csv"), &csv).unwrap();
+
+    assert!(write_purchase_order(&order, state.quote(), &orders).is_err());
+    assert!(!order.file_path(&orders, "pdf").exists());
+
+    std::fs::remove_file(&csv).unwrap();
+    write_purchase_order(&order, state.quote(), &orders).unwrap();
+    assert!(order.file_path(&orders, "pdf").exists());
+    assert!(csv.exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,133 @@
+//! Supplied line totals survive edits, policy switches and undo, however
+//! many times the BoM is reconciled.
+
+use tabs_backend::{AppState, BomField, LineStatus, ReconcilePolicy, TotalSource};
+
+/// One line whose supplied total disagrees with quantity × unit cost, and
+/// one that agrees.
+const BOM: &str = "\
+Material,QTY,Unit cost,Line total,Lead time,Min QTY
+Bolt,10,1.00,12.00,14,1
+Nut,4,0.50,2.00,14,1
+";
+
+fn estimate(name: &str) -> AppState {
+    let path =
+        std::env::temp_dir().join(format!("tabs-reconcile-{}-{name}.csv", std::process::id()));
+    std::fs::write(&path, BOM).unwrap();
+    let state = AppState::demo_with_bom_path(&path);
+    std::fs::remove_file(&path).unwrap();
+    state
+}
+
+fn policy(authoritative: TotalSource) -> ReconcilePolicy {
+    ReconcilePolicy {
+        authoritative,
+        ..ReconcilePolicy::default()
+    }
+}
+
+/// Each line's total in use and its status.
+fn totals(state: &AppState) -> Vec<(String, LineStatus)> {
+    state
+        .bom()
+        .iter()
+        .zip(state.reconciliation())
+        .map(|(item, line)| (item.total_cost.amount_string(), line.status))
+        .collect()
+}
+
+fn supplied(state: &AppState) -> Vec<Option<String>> {
+    state
+        .bom()
+        .iter()
+        .map(|item| item.file_total.map(|total| total.amount_string()))
+        .collect()
+}
+
+#[test]
+fn switching_policy_after_an_edit_restores_supplied_totals() {
+    let mut state = estimate("switch");
+    let file = [
+        ("12.00".to_string(), LineStatus::Mismatch),
+        ("2.00".to_string(), LineStatus::Matches),
+    ];
+    let computed = [
+        ("10.00".to_string(), LineStatus::Mismatch),
+        ("2.00".to_string(), LineStatus::Matches),
+    ];
+    assert_eq!(totals(&state), file);
+
+    state.set_reconcile_policy(policy(TotalSource::Computed));
+    assert_eq!(totals(&state), computed);
+    state.set_bom_cell(0, BomField::LeadTimeDays, "5").unwrap();
+    assert_eq!(totals(&state), computed, "still a mismatch after the edit");
+    state.set_reconcile_policy(policy(TotalSource::File));
+    assert_eq!(totals(&state), file);
+    assert_eq!(
+        supplied(&state),
+        [Some("12.00".to_string()), Some("2.00".to_string())]
+    );
+
+    state.undo().unwrap();
+    assert_eq!(totals(&state), computed);
+    state.undo().unwrap();
+    state.undo().unwrap();
+    assert_eq!(totals(&state), file);
+    for _ in 0..3 {
+        state.redo().unwrap();
+    }
+    assert_eq!(totals(&state), file);
+    assert_eq!(state.bom()[0].lead_time_days, 5);
+}
+
+#[test]
+fn editing_quantity_or_unit_cost_drops_the_supplied_total_until_undone() {
+    let mut state = estimate("edit");
+    state.set_bom_cell(0, BomField::Quantity, "12").unwrap();
+    assert_eq!(supplied(&state)[0], None);
+    assert_eq!(
+        totals(&state)[0],
+        ("12.00".to_string(), LineStatus::Matches)
+    );
+    assert_eq!(state.reconciliation()[0].applied, TotalSource::Computed);
+
+    state.undo().unwrap();
+    assert_eq!(supplied(&state)[0].as_deref(), Some("12.00"));
+    assert_eq!(
+        totals(&state)[0],
+        ("12.00".to_string(), LineStatus::Mismatch)
+    );
+    assert_eq!(state.reconciliation()[0].applied, TotalSource::File);
+}
+
+#[test]
+fn lines_added_in_the_app_use_their_computed_total() {
+    let mut state = estimate("added");
+    let index = state.add_bom_line();
+    state.set_bom_cell(index, BomField::Quantity, "3").unwrap();
+    state
+        .set_bom_cell(index, BomField::UnitCost, "2.50")
+        .unwrap();
+    assert_eq!(
+        totals(&state)[index],
+        ("7.50".to_string(), LineStatus::Matches)
+    );
+    assert_eq!(supplied(&state)[index], None);
+}
+
+#[test]
+fn choosing_the_policy_in_force_leaves_no_undo_step() {
+    let mut state = estimate("same");
+    state.set_reconcile_policy(policy(TotalSource::File));
+    assert_eq!(state.history().undo_label(), None);
+    assert!(!state.history().is_modified());
+
+    state.set_reconcile_policy(policy(TotalSource::Computed));
+    let label = state.history().undo_label().map(str::to_string);
+    assert!(label.is_some());
+    state.set_reconcile_policy(policy(TotalSource::Computed));
+    assert_eq!(state.history().undo_label(), label.as_deref());
+    state.undo().unwrap();
+    assert_eq!(state.history().undo_label(), None);
+}
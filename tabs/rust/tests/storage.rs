@@ -0,0 +1,220 @@
+//! Every repository keeps projects, their revisions and the price history
+//! the same way; each check runs against each implementation.
+
+use std::path::PathBuf;
+use std::time::Duration;
+use tabs_backend::{AppState, BomField, MemoryRepository, ProjectRepository, StorageError};
+
+fn estimate(quote_number: &str, customer: &str) -> AppState {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../examples/bom.csv");
+    let mut state = AppState::demo_with_bom_path(path);
+    state.quote.number = quote_number.to_string();
+    state.quote.customer.name = customer.to_string();
+    state
+}
+
+/// Saves within one clock tick would tie on their save time.
+fn tick() {
+    std::thread::sleep(Duration::from_millis(5));
+}
+
+fn unit_costs(repository: &impl ProjectRepository, part: &str) -> Vec<String> {
+    repository
+        .price_history(part)
+        .unwrap()
+        .iter()
+        .map(|point| point.unit_cost.amount_string())
+        .collect()
+}
+
+fn saves_revisions_and_reloads_each(repository: &mut impl ProjectRepository) {
+    let mut state = estimate("Q-0100", "Harbour Works");
+    let id = repository.save_project(None, &state, None).unwrap();
+    state.set_bom_cell(0, BomField::Quantity, "12").unwrap();
+    tick();
+    assert_eq!(
+        repository
+            .save_project(Some(id), &state, Some(b"%PDF"))
+            .unwrap(),
+        id
+    );
+
+    let revisions = repository.stored_revisions(id).unwrap();
+    assert_eq!(
+        revisions
+            .iter()
+            .map(|revision| revision.number)
+            .collect::<Vec<_>>(),
+        [1, 2]
+    );
+    assert!(revisions[0].saved_at < revisions[1].saved_at);
+    assert!(revisions
+        .iter()
+        .all(|revision| revision.quote_number == "Q-0100"));
+
+    let first = repository.load_revision(id, 1).unwrap();
+    assert_eq!(first.state.bom[0].quantity, 10);
+    assert!(first.quote_pdf.is_none());
+    let latest = repository.load_project(id).unwrap();
+    assert_eq!(latest.state.bom[0].quantity, 12);
+    assert_eq!(latest.quote_pdf.as_deref(), Some(&b"%PDF"[..]));
+
+    assert!(matches!(
+        repository.load_revision(id, 3),
+        Err(StorageError::NoSuchRevision(found, 3)) if found == id
+    ));
+    assert!(matches!(
+        repository.stored_revisions(id + 100),
+        Err(StorageError::NoSuchProject(_))
+    ));
+    assert!(matches!(
+        repository.load_revision(id + 100, 1),
+        Err(StorageError::NoSuchProject(_))
+    ));
+}
+
+fn keeps_changed_prices_across_projects(repository: &mut impl ProjectRepository) {
+    let mut state = estimate("Q-0200", "Harbour Works");
+    let first = repository.save_project(None, &state, None).unwrap();
+    // An unchanged price is not recorded again.
+    repository.save_project(Some(first), &state, None).unwrap();
+    state.set_bom_cell(0, BomField::UnitCost, "47.10").unwrap();
+    repository.save_project(Some(first), &state, None).unwrap();
+    let second = repository
+        .save_project(None, &estimate("Q-0201", "Millbank"), None)
+        .unwrap();
+
+    assert_eq!(
+        unit_costs(repository, "material item 1"),
+        ["45.40", "47.10", "45.40"]
+    );
+    let history = repository.price_history("  Material Item 1 ").unwrap();
+    assert_eq!(
+        history
+            .iter()
+            .map(|point| point.project)
+            .collect::<Vec<_>>(),
+        [Some(first), Some(first), Some(second)]
+    );
+    assert_eq!(history[2].quote_number, "Q-0201");
+    assert_eq!(history[0].supplier.as_deref(), Some("Northfield Steel"));
+    assert!(unit_costs(repository, "no such part").is_empty());
+
+    repository.delete_project(first).unwrap();
+    let history = repository.price_history("Material Item 1").unwrap();
+    assert_eq!(
+        history
+            .iter()
+            .map(|point| point.project)
+            .collect::<Vec<_>>(),
+        [None, None, Some(second)],
+        "a deleted project's prices are kept"
+    );
+    assert!(matches!(
+        repository.load_project(first),
+        Err(StorageError::NoSuchProject(_))
+    ));
+}
+
+fn lists_projects_most_recently_saved_first(repository: &mut impl ProjectRepository) {
+    let older = estimate("Q-0300", "Harbour Works");
+    let newer = estimate("Q-0301", "Millbank 100%");
+    let older_id = repository.save_project(None, &older, None).unwrap();
+    tick();
+    let newer_id = repository.save_project(None, &newer, None).unwrap();
+
+    let ids = |search: &str| -> Vec<_> {
+        repository
+            .find_projects(search)
+            .unwrap()
+            .iter()
+            .map(|project| project.id)
+            .collect()
+    };
+    assert_eq!(ids(""), [newer_id, older_id]);
+    assert_eq!(ids("harbour"), [older_id]);
+    assert_eq!(ids("100%"), [newer_id]);
+    assert_eq!(ids("material item 3").len(), 2);
+
+    tick();
+    repository
+        .save_project(Some(older_id), &older, None)
+        .unwrap();
+    let found = repository.find_projects("").unwrap();
+    assert_eq!(found[0].id, older_id);
+    assert_eq!(found[0].revision, 2);
+}
+
+/// Only ASCII letters match whatever their case, as in SQLite.
+fn matches_text_ignoring_ascii_case_only(repository: &mut impl ProjectRepository) {
+    let mut state = estimate("Q-0400", "Harbour Works");
+    state
+        .set_bom_cell(0, BomField::Name, "Ölfilter Typ B")
+        .unwrap();
+    let id = repository.save_project(None, &state, None).unwrap();
+
+    let found = |search: &str| -> Vec<_> {
+        repository
+            .find_projects(search)
+            .unwrap()
+            .iter()
+            .map(|project| project.id)
+            .collect()
+    };
+    assert_eq!(found("ÖLFILTER"), [id]);
+    assert!(found("ölfilter").is_empty());
+    assert_eq!(repository.price_history("Ölfilter TYP b").unwrap().len(), 1);
+    assert!(repository
+        .price_history("ölfilter typ b")
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn memory_repository_saves_revisions_and_reloads_each() {
+    saves_revisions_and_reloads_each(&mut MemoryRepository::new());
+}
+
+#[test]
+fn memory_repository_keeps_changed_prices_across_projects() {
+    keeps_changed_prices_across_projects(&mut MemoryRepository::new());
+}
+
+#[test]
+fn memory_repository_lists_projects_most_recently_saved_first() {
+    lists_projects_most_recently_saved_first(&mut MemoryRepository::new());
+}
+
+#[test]
+fn memory_repository_matches_text_ignoring_ascii_case_only() {
+    matches_text_ignoring_ascii_case_only(&mut MemoryRepository::new());
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use tabs_backend::SqliteRepository;
+
+    fn repository() -> SqliteRepository {
+        SqliteRepository::open_in_memory().unwrap()
+    }
+
+    #[test]
+    fn saves_revisions_and_reloads_each() {
+        super::saves_revisions_and_reloads_each(&mut repository());
+    }
+
+    #[test]
+    fn keeps_changed_prices_across_projects() {
+        super::keeps_changed_prices_across_projects(&mut repository());
+    }
+
+    #[test]
+    fn lists_projects_most_recently_saved_first() {
+        super::lists_projects_most_recently_saved_first(&mut repository());
+    }
+
+    #[test]
+    fn matches_text_ignoring_ascii_case_only() {
+        super::matches_text_ignoring_ascii_case_only(&mut repository());
+    }
+}
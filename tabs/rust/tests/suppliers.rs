@@ -0,0 +1,62 @@
+//! Supplier names are the same name whatever their ASCII case, and only
+//! then, wherever they are compared.
+
+use tabs_backend::{group_by_supplier, BomItem, Currency, Supplier, SupplierCatalogue};
+
+fn supplier(name: &str) -> Supplier {
+    Supplier {
+        name: name.to_string(),
+        contact: None,
+        email: None,
+        phone: None,
+        default_lead_time_days: None,
+        currency: Currency::GBP,
+    }
+}
+
+fn bought_from(name: &str) -> BomItem {
+    BomItem {
+        supplier: Some(name.to_string()),
+        ..BomItem::blank(Currency::GBP)
+    }
+}
+
+const NAMES: [&str; 4] = ["Acme", "ACME", "Öko Bau", "öko bau"];
+
+#[test]
+fn the_catalogue_and_supplier_groups_fold_case_alike() {
+    let mut catalogue = SupplierCatalogue::default();
+    let added = catalogue.merge(NAMES.map(supplier));
+    assert_eq!(added, 3);
+    let known: Vec<_> = catalogue
+        .suppliers
+        .iter()
+        .map(|supplier| supplier.name.as_str())
+        .collect();
+    assert_eq!(known, ["ACME", "Öko Bau", "öko bau"]);
+    assert_eq!(catalogue.find(" acme ").unwrap().name, "ACME");
+    assert_eq!(catalogue.find("ÖKO BAU").unwrap().name, "Öko Bau");
+    assert_eq!(catalogue.find("öKO BAU").unwrap().name, "öko bau");
+
+    let groups = group_by_supplier(
+        NAMES.map(bought_from).into_iter().enumerate(),
+        Currency::GBP,
+    )
+    .unwrap();
+    let grouped: Vec<_> = groups
+        .iter()
+        .map(|group| {
+            let indices: Vec<_> = group.lines.iter().map(|(index, _)| *index).collect();
+            (group.supplier.as_deref(), indices)
+        })
+        .collect();
+    assert_eq!(
+        grouped,
+        [
+            (Some("Acme"), vec![0, 1]),
+            (Some("Öko Bau"), vec![2]),
+            (Some("öko bau"), vec![3]),
+        ]
+    );
+    assert_eq!(groups.len(), catalogue.suppliers.len());
+}
@@ -0,0 +1,535 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tabs_backend::Severity;
+
+/// Name of the config file looked for in the working directory and the `tabs` directory.
+const CONFIG_FILE_NAME: &str = "tabs.conf";
+
+/// Where a setting's value came from, highest precedence last.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    Default,
+    File(PathBuf),
+    Environment(&'static str),
+    CommandLine,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Default => f.write_str("default"),
+            ConfigSource::File(path) => write!(f, "{}", path.display()),
+            ConfigSource::Environment(var) => write!(f, "${var}"),
+            ConfigSource::CommandLine => f.write_str("command line"),
+        }
+    }
+}
+
+/// One configurable path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathSetting {
+    BomCsv,
+    QuotePdf,
+    PreviewCache,
+    PdfiumLib,
+    LogExport,
+    BomExport,
+    Holidays,
+    SupplierCalendars,
+    SupplierCatalogue,
+    PurchaseOrders,
+    Projects,
+    Database,
+}
+
+impl PathSetting {
+    pub const ALL: [PathSetting; 12] = [
+        PathSetting::BomCsv,
+        PathSetting::QuotePdf,
+        PathSetting::PreviewCache,
+        PathSetting::PdfiumLib,
+        PathSetting::LogExport,
+        PathSetting::BomExport,
+        PathSetting::Holidays,
+        PathSetting::SupplierCalendars,
+        PathSetting::SupplierCatalogue,
+        PathSetting::PurchaseOrders,
+        PathSetting::Projects,
+        PathSetting::Database,
+    ];
+
+    /// Key in the config file; the CLI flag is the same with `-` for `_`.
+    pub fn key(&self) -> &'static str {
+        match self {
+            PathSetting::BomCsv => "bom_csv",
+            PathSetting::QuotePdf => "quote_pdf",
+            PathSetting::PreviewCache => "preview_cache",
+            PathSetting::PdfiumLib => "pdfium_lib",
+            PathSetting::LogExport => "log_export",
+            PathSetting::BomExport => "bom_export",
+            PathSetting::Holidays => "holidays",
+            PathSetting::SupplierCalendars => "supplier_calendars",
+            PathSetting::SupplierCatalogue => "supplier_catalogue",
+            PathSetting::PurchaseOrders => "purchase_orders",
+            PathSetting::Projects => "projects",
+            PathSetting::Database => "database",
+        }
+    }
+
+    pub fn env_var(&self) -> &'static str {
+        match self {
+            PathSetting::BomCsv => "TABS_BOM_CSV",
+            PathSetting::QuotePdf => "TABS_QUOTE_PDF",
+            PathSetting::PreviewCache => "TABS_PREVIEW_CACHE",
+            PathSetting::PdfiumLib => "TABS_PDFIUM_LIB",
+            PathSetting::LogExport => "TABS_LOG_EXPORT",
+            PathSetting::BomExport => "TABS_BOM_EXPORT",
+            PathSetting::Holidays => "TABS_HOLIDAYS",
+            PathSetting::SupplierCalendars => "TABS_SUPPLIER_CALENDARS",
+            PathSetting::SupplierCatalogue => "TABS_SUPPLIER_CATALOGUE",
+            PathSetting::PurchaseOrders => "TABS_PURCHASE_ORDERS",
+            PathSetting::Projects => "TABS_PROJECTS",
+            PathSetting::Database => "TABS_DATABASE",
+        }
+    }
+
+    fn flag(&self) -> String {
+        format!("--{}", self.key().replace('_', "-"))
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PathSetting::BomCsv => "BoM file",
+            PathSetting::QuotePdf => "Quote PDF",
+            PathSetting::PreviewCache => "Preview cache",
+            PathSetting::PdfiumLib => "Pdfium library",
+            PathSetting::LogExport => "Log export",
+            PathSetting::BomExport => "BoM export",
+            PathSetting::Holidays => "Holidays",
+            PathSetting::SupplierCalendars => "Supplier calendars",
+            PathSetting::SupplierCatalogue => "Supplier catalogue",
+            PathSetting::PurchaseOrders => "Purchase orders",
+            PathSetting::Projects => "Projects",
+            PathSetting::Database => "Project database",
+        }
+    }
+
+    /// Built-in location, relative to the `tabs` directory (see [`tabs_dir`]).
+    fn default_path(&self) -> PathBuf {
+        let relative = match self {
+            PathSetting::BomCsv => "examples/bom.csv",
+            PathSetting::QuotePdf => "gen/quote.pdf",
+            PathSetting::PreviewCache => "gen/preview-cache",
+            PathSetting::PdfiumLib => "rust/lib",
+            PathSetting::LogExport => "gen/tabs.log",
+            PathSetting::BomExport => "gen/bom-view.csv",
+            PathSetting::Holidays => "examples/holidays.csv",
+            PathSetting::SupplierCalendars => "examples/supplier-calendars",
+            PathSetting::SupplierCatalogue => "examples/suppliers.csv",
+            PathSetting::PurchaseOrders => "gen/purchase-orders",
+            PathSetting::Projects => "gen/projects",
+            PathSetting::Database => "gen/estimates.sqlite3",
+        };
+        tabs_dir().join(relative)
+    }
+}
+
+/// A resolved path and the layer that supplied it.
+#[derive(Debug, Clone)]
+pub struct ResolvedPath {
+    pub path: PathBuf,
+    pub source: ConfigSource,
+}
+
+/// Something wrong with the configuration, reported in the Settings tab.
+#[derive(Debug, Clone)]
+pub struct ConfigProblem {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// Paths the UI reads and writes, resolved from (lowest to highest precedence)
+/// built-in defaults, a config file, `TABS_*` environment variables and
+/// command-line flags.
+///
+/// The config file holds `key = path` lines (`#` starts a comment). It is
+/// `--config <file>` or `$TABS_CONFIG` if given, otherwise `tabs.conf` in the
+/// working directory or the `tabs` directory when present. Relative paths in
+/// the file are relative to the file; on the command line and in the
+/// environment they are relative to the working directory.
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    paths: Vec<(PathSetting, ResolvedPath)>,
+    pub config_file: Option<PathBuf>,
+    pub problems: Vec<ConfigProblem>,
+}
+
+impl AppConfig {
+    /// Resolve from this process's arguments and environment, then validate.
+    pub fn from_environment() -> Self {
+        let mut config = Self::load(std::env::args().skip(1), |var| std::env::var(var).ok());
+        config.validate();
+        config
+    }
+
+    /// Resolve every path from the given arguments and environment lookup.
+    pub fn load(
+        args: impl IntoIterator<Item = String>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let mut config = AppConfig {
+            paths: PathSetting::ALL
+                .into_iter()
+                .map(|setting| {
+                    let resolved = ResolvedPath {
+                        path: setting.default_path(),
+                        source: ConfigSource::Default,
+                    };
+                    (setting, resolved)
+                })
+                .collect(),
+            config_file: None,
+            problems: Vec::new(),
+        };
+
+        let (cli_config, cli_paths) = config.parse_args(args);
+
+        let explicit_file = cli_config
+            .map(|path| (path, "--config"))
+            .or_else(|| env("TABS_CONFIG").map(|path| (PathBuf::from(path), "$TABS_CONFIG")));
+        match explicit_file {
+            Some((path, origin)) if !path.is_file() => config.problem(
+                Severity::Error,
+                format!("config file {} from {origin} not found", path.display()),
+            ),
+            Some((path, _)) => config.read_file(&path),
+            None => {
+                let candidates = [
+                    PathBuf::from(CONFIG_FILE_NAME),
+                    tabs_dir().join(CONFIG_FILE_NAME),
+                ];
+                if let Some(path) = candidates.into_iter().find(|path| path.is_file()) {
+                    config.read_file(&path);
+                }
+            }
+        }
+
+        for setting in PathSetting::ALL {
+            if let Some(value) = env(setting.env_var()).filter(|value| !value.trim().is_empty()) {
+                config.set(
+                    setting,
+                    PathBuf::from(value.trim()),
+                    ConfigSource::Environment(setting.env_var()),
+                );
+            }
+        }
+        for (setting, path) in cli_paths {
+            config.set(setting, path, ConfigSource::CommandLine);
+        }
+        config
+    }
+
+    pub fn path(&self, setting: PathSetting) -> &Path {
+        &self.resolved(setting).path
+    }
+
+    pub fn resolved(&self, setting: PathSetting) -> &ResolvedPath {
+        self.paths
+            .iter()
+            .find(|(s, _)| *s == setting)
+            .map(|(_, resolved)| resolved)
+            .expect("every path setting has a value")
+    }
+
+    /// Check that inputs exist and outputs can be written; problems are recorded, not fatal.
+    pub fn validate(&mut self) {
+        let bom = self.path(PathSetting::BomCsv).to_path_buf();
+        if !bom.is_file() {
+            self.problem(
+                Severity::Error,
+                format!(
+                    "BoM file {} does not exist; using the built-in demo materials",
+                    bom.display()
+                ),
+            );
+        }
+
+        let quote = self.path(PathSetting::QuotePdf).to_path_buf();
+        if quote.is_dir() {
+            self.problem(
+                Severity::Error,
+                format!("quote PDF path {} is a directory", quote.display()),
+            );
+        } else if let Some(blocked) = quote.parent().and_then(blocking_file) {
+            self.problem(
+                Severity::Error,
+                format!(
+                    "cannot write the quote PDF: {} is a file, not a directory",
+                    blocked.display()
+                ),
+            );
+        }
+
+        let cache = self.path(PathSetting::PreviewCache).to_path_buf();
+        if let Some(blocked) = blocking_file(&cache) {
+            self.problem(
+                Severity::Warning,
+                format!(
+                    "preview cache disabled: {} is a file, not a directory",
+                    blocked.display()
+                ),
+            );
+        }
+
+        let log = self.path(PathSetting::LogExport).to_path_buf();
+        if log.is_dir() {
+            self.problem(
+                Severity::Warning,
+                format!("log export path {} is a directory", log.display()),
+            );
+        } else if let Some(blocked) = log.parent().and_then(blocking_file) {
+            self.problem(
+                Severity::Warning,
+                format!(
+                    "cannot export logs: {} is a file, not a directory",
+                    blocked.display()
+                ),
+            );
+        }
+
+        let export = self.path(PathSetting::BomExport).to_path_buf();
+        if export.is_dir() {
+            self.problem(
+                Severity::Warning,
+                format!("BoM export path {} is a directory", export.display()),
+            );
+        } else if let Some(blocked) = export.parent().and_then(blocking_file) {
+            self.problem(
+                Severity::Warning,
+                format!(
+                    "cannot export the BoM: {} is a file, not a directory",
+                    blocked.display()
+                ),
+            );
+        }
+
+        let orders = self.path(PathSetting::PurchaseOrders).to_path_buf();
+        if let Some(blocked) = blocking_file(&orders) {
+            self.problem(
+                Severity::Warning,
+                format!(
+                    "cannot write purchase orders: {} is a file, not a directory",
+                    blocked.display()
+                ),
+            );
+        }
+
+        let projects = self.path(PathSetting::Projects).to_path_buf();
+        if let Some(blocked) = blocking_file(&projects) {
+            self.problem(
+                Severity::Warning,
+                format!(
+                    "cannot save projects: {} is a file, not a directory",
+                    blocked.display()
+                ),
+            );
+        }
+
+        let database = self.path(PathSetting::Database).to_path_buf();
+        if database.is_dir() {
+            self.problem(
+                Severity::Warning,
+                format!("project database {} is a directory", database.display()),
+            );
+        } else if let Some(blocked) = database.parent().and_then(blocking_file) {
+            self.problem(
+                Severity::Warning,
+                format!(
+                    "cannot store projects: {} is a file, not a directory",
+                    blocked.display()
+                ),
+            );
+        }
+
+        // Calendars are optional: only complain when one was asked for explicitly.
+        let holidays = self.resolved(PathSetting::Holidays).clone();
+        if holidays.source != ConfigSource::Default && !holidays.path.is_file() {
+            self.problem(
+                Severity::Warning,
+                format!(
+                    "holiday file {} does not exist; only weekends are non-working",
+                    holidays.path.display()
+                ),
+            );
+        }
+        let suppliers = self.resolved(PathSetting::SupplierCalendars).clone();
+        if suppliers.source != ConfigSource::Default && !suppliers.path.is_dir() {
+            self.problem(
+                Severity::Warning,
+                format!(
+                    "supplier calendar directory {} does not exist",
+                    suppliers.path.display()
+                ),
+            );
+        }
+        let catalogue = self.resolved(PathSetting::SupplierCatalogue).clone();
+        if catalogue.source != ConfigSource::Default && !catalogue.path.is_file() {
+            self.problem(
+                Severity::Warning,
+                format!(
+                    "supplier catalogue {} does not exist",
+                    catalogue.path.display()
+                ),
+            );
+        }
+
+        let lib = self.path(PathSetting::PdfiumLib).to_path_buf();
+        if !lib.is_dir() {
+            self.problem(
+                Severity::Error,
+                format!(
+                    "pdfium directory {} does not exist; the quote preview is unavailable",
+                    lib.display()
+                ),
+            );
+        } else if !PDFIUM_LIBRARY_NAMES
+            .iter()
+            .any(|name| lib.join(name).is_file())
+        {
+            self.problem(
+                Severity::Error,
+                format!(
+                    "no pdfium library ({}) in {}; the quote preview is unavailable",
+                    PDFIUM_LIBRARY_NAMES.join(", "),
+                    lib.display()
+                ),
+            );
+        }
+    }
+
+    fn problem(&mut self, severity: Severity, message: String) {
+        self.problems.push(ConfigProblem { severity, message });
+    }
+
+    fn set(&mut self, setting: PathSetting, path: PathBuf, source: ConfigSource) {
+        if let Some((_, resolved)) = self.paths.iter_mut().find(|(s, _)| *s == setting) {
+            *resolved = ResolvedPath { path, source };
+        }
+    }
+
+    /// Split arguments into the `--config` file and path overrides.
+    fn parse_args(
+        &mut self,
+        args: impl IntoIterator<Item = String>,
+    ) -> (Option<PathBuf>, Vec<(PathSetting, PathBuf)>) {
+        let mut config_file = None;
+        let mut paths = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            };
+            let setting = PathSetting::ALL
+                .into_iter()
+                .find(|setting| setting.flag() == flag);
+            if setting.is_none() && flag != "--config" {
+                self.problem(
+                    Severity::Warning,
+                    format!("ignoring unknown argument \"{arg}\""),
+                );
+                continue;
+            }
+            let Some(value) = inline.or_else(|| args.next()) else {
+                self.problem(Severity::Error, format!("{flag} needs a path"));
+                continue;
+            };
+            match setting {
+                Some(setting) => paths.push((setting, PathBuf::from(value))),
+                None => config_file = Some(PathBuf::from(value)),
+            }
+        }
+        (config_file, paths)
+    }
+
+    fn read_file(&mut self, path: &Path) {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                self.problem(
+                    Severity::Error,
+                    format!("cannot read config file {}: {err}", path.display()),
+                );
+                return;
+            }
+        };
+        self.config_file = Some(path.to_path_buf());
+        let base = path.parent().unwrap_or(Path::new(""));
+
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let line_no = index + 1;
+            let Some((key, value)) = line.split_once('=') else {
+                self.problem(
+                    Severity::Warning,
+                    format!("{} line {line_no}: expected \"key = path\"", path.display()),
+                );
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            match PathSetting::ALL.into_iter().find(|s| s.key() == key) {
+                Some(_) if value.is_empty() => self.problem(
+                    Severity::Warning,
+                    format!("{} line {line_no}: {key} has no path", path.display()),
+                ),
+                Some(setting) => self.set(
+                    setting,
+                    base.join(value),
+                    ConfigSource::File(path.to_path_buf()),
+                ),
+                None => self.problem(
+                    Severity::Warning,
+                    format!("{} line {line_no}: unknown key \"{key}\"", path.display()),
+                ),
+            }
+        }
+    }
+}
+
+/// File names the pdfium shared library goes by on each platform.
+const PDFIUM_LIBRARY_NAMES: [&str; 3] = ["libpdfium.so", "libpdfium.dylib", "pdfium.dll"];
+
+/// The `tabs` directory the running binary belongs to: the closest
+/// directory above the executable that holds `examples/`, as it does for a
+/// build inside a checkout (`tabs/ui/target/<profile>/`) or an unpacked
+/// release. Otherwise the working directory.
+fn tabs_dir() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| {
+            exe.ancestors()
+                .skip(1)
+                .find(|dir| dir.join("examples").is_dir())
+                .map(Path::to_path_buf)
+        })
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default()
+}
+
+/// The nearest existing ancestor of `dir` (or `dir` itself) if it is a file,
+/// which would stop the directory from being created.
+fn blocking_file(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|ancestor| ancestor.exists())
+        .filter(|existing| !existing.is_dir())
+        .map(Path::to_path_buf)
+}
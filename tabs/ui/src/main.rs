@@ -1,9 +1,29 @@
-use ::image::ImageFormat;
-use iced::widget::{button, column, container, image, row, scrollable, text};
-use iced::{Alignment, Color, Element, Length, Sandbox, Settings};
-use pdfium::{set_library_location, PdfiumDocument, PdfiumRenderConfig, PdfiumResult};
-use std::fs;
-use tabs_backend::{AppState, BomItem, TabKind};
+use chrono::{NaiveDate, Weekday};
+use config::{AppConfig, PathSetting};
+use iced::keyboard::{self, Key, Modifiers};
+use iced::widget::{
+    button, canvas, column, container, image, pick_list, row, scrollable, text, text_input, Space,
+};
+use iced::{
+    executor, Alignment, Application, Color, Command, Element, Length, Settings, Subscription,
+    Theme,
+};
+use preview::{QuotePreview, RenderJob, RenderOutcome, Zoom, PAGE_WIDTH_AT_100};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use table::{TableEvent, VirtualTable, COLUMN_GAP};
+use tabs_backend::{
+    edit_bom_item, Adjustment, AdjustmentKind, AppState, BomDiff, BomField, BomItem, BomQuery,
+    BomSortKey, BomTree, LeftOutReason, LineDiff, LineStatus, LogEvent, LogFilter, LogLevel,
+    MemoryRepository, Money, MoneyError, OrderUrgency, PlannedOrder, ProjectId, ProjectRepository,
+    PurchaseOrder, Rate, Severity, SortDirection, StoredProject, TabKind, PROJECT_EXTENSION,
+};
+use timeline::{TimeScale, Timeline};
+
+mod config;
+mod preview;
+mod table;
+mod timeline;
 
 // Ink wash palette
 fn charcoal() -> Color {
@@ -18,121 +38,837 @@ fn soft_ivory() -> Color {
 fn slate_blue() -> Color {
     Color::from_rgb8(0x6D, 0x81, 0x96)
 }
+// Accents for warnings and errors.
+fn ochre() -> Color {
+    Color::from_rgb8(0xE8, 0xC5, 0x6B)
+}
+fn terracotta() -> Color {
+    Color::from_rgb8(0xE0, 0x7A, 0x6E)
+}
 
 // Approximate column widths for the BoM table (in logical px).
-const COL_NAME_WIDTH: f32 = 260.0;
+const COL_NAME_WIDTH: f32 = 200.0;
+const COL_SUPPLIER_WIDTH: f32 = 150.0;
+const COL_PART_WIDTH: f32 = 100.0;
 const COL_QTY_WIDTH: f32 = 60.0;
 const COL_UNIT_WIDTH: f32 = 100.0;
 const COL_TOTAL_WIDTH: f32 = 110.0;
 const COL_LEAD_WIDTH: f32 = 100.0;
 const COL_MIN_WIDTH: f32 = 80.0;
+const COL_LEVEL_WIDTH: f32 = 60.0;
+const COL_CHECK_WIDTH: f32 = 110.0;
+const COL_DATE_WIDTH: f32 = 110.0;
+const COL_STATUS_WIDTH: f32 = 150.0;
+const COL_ACTIONS_WIDTH: f32 = 130.0;
 
-pub fn main() -> iced::Result {
-    TabsApp::run(Settings::default())
-}
+/// Breakdown table columns in display order: heading, sort key, and starting width.
+const BOM_COLUMNS: [(&str, Option<BomSortKey>, f32); 11] = [
+    (
+        "Material",
+        Some(BomSortKey::Field(BomField::Name)),
+        COL_NAME_WIDTH,
+    ),
+    (
+        "Supplier",
+        Some(BomSortKey::Field(BomField::Supplier)),
+        COL_SUPPLIER_WIDTH,
+    ),
+    (
+        "Part no.",
+        Some(BomSortKey::Field(BomField::PartNumber)),
+        COL_PART_WIDTH,
+    ),
+    (
+        "Qty",
+        Some(BomSortKey::Field(BomField::Quantity)),
+        COL_QTY_WIDTH,
+    ),
+    (
+        "Unit cost",
+        Some(BomSortKey::Field(BomField::UnitCost)),
+        COL_UNIT_WIDTH,
+    ),
+    (
+        "Line total",
+        Some(BomSortKey::Field(BomField::TotalCost)),
+        COL_TOTAL_WIDTH,
+    ),
+    (
+        "Lead (days)",
+        Some(BomSortKey::Field(BomField::LeadTimeDays)),
+        COL_LEAD_WIDTH,
+    ),
+    (
+        "Min qty",
+        Some(BomSortKey::Field(BomField::MinQuantity)),
+        COL_MIN_WIDTH,
+    ),
+    (
+        "Level",
+        Some(BomSortKey::Field(BomField::Level)),
+        COL_LEVEL_WIDTH,
+    ),
+    ("Total check", Some(BomSortKey::TotalCheck), COL_CHECK_WIDTH),
+    ("", None, COL_ACTIONS_WIDTH),
+];
 
-fn render_quote_pdf_to_png(pdf_path: &str, output_png: &str) -> PdfiumResult<()> {
-    // Ensure the output directory for the rendered preview exists.
-    if let Some(parent) = std::path::Path::new(output_png).parent() {
-        let _ = fs::create_dir_all(parent);
-    }
+/// Assembly tree columns in display order: heading and starting width.
+const TREE_COLUMNS: [(&str, f32); 6] = [
+    ("Material", COL_NAME_WIDTH + 80.0),
+    ("Qty", COL_QTY_WIDTH),
+    ("Total qty", COL_MIN_WIDTH),
+    ("Unit cost", COL_UNIT_WIDTH),
+    ("Rolled-up total", COL_TOTAL_WIDTH + 20.0),
+    ("Lead (days)", COL_LEAD_WIDTH),
+];
+/// Indent per assembly level in the tree's Material column.
+const TREE_INDENT: f32 = 18.0;
 
-    // Configure pdfium-rs to load the Pdfium shared library from the repo-local location.
-    set_library_location("../rust/lib");
+/// Supplier grouping columns in display order: heading and starting width.
+const SUPPLIER_COLUMNS: [(&str, f32); 6] = [
+    ("Material", COL_NAME_WIDTH + 40.0),
+    ("Part no.", COL_PART_WIDTH),
+    ("Qty", COL_QTY_WIDTH),
+    ("Unit cost", COL_UNIT_WIDTH),
+    ("Line total", COL_TOTAL_WIDTH),
+    ("Lead (days)", COL_LEAD_WIDTH),
+];
 
-    let doc = PdfiumDocument::new_from_path(pdf_path, None)?;
-    // Render at a higher resolution so text remains readable
-    // when scaled into the preview area.
-    let config = PdfiumRenderConfig::new().with_height(1200);
-    let page = doc.page(0)?;
-    let bitmap = page.render(&config)?;
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
 
-    bitmap.save(output_png, ImageFormat::Png)?;
-    Ok(())
+pub fn main() -> iced::Result {
+    let config = AppConfig::from_environment();
+    for problem in &config.problems {
+        eprintln!("{problem}");
+    }
+    // pdfium-rs loads the shared library once, on first use, from this location.
+    pdfium::set_library_location(&config.path(PathSetting::PdfiumLib).to_string_lossy());
+    TabsApp::run(Settings::with_flags(config))
 }
 
 struct TabsApp {
+    config: AppConfig,
     backend_state: AppState,
+    /// File the estimate was last opened from or saved to.
+    project_path: Option<PathBuf>,
+    /// Path typed for Open and Save As; relative paths are under the projects directory.
+    project_draft: String,
+    /// Outcome of the last project action.
+    project_status: String,
+    /// New or Open waiting for the user to confirm discarding unsaved changes.
+    pending_discard: Option<ProjectAction>,
+    /// Note for the next quote revision.
+    revision_note: String,
+    /// Revisions compared on the Revisions tab; `None` is the current BoM.
+    compare_from: Option<u32>,
+    compare_to: Option<u32>,
+    revision_error: Option<String>,
+    /// Stored projects: the SQLite database, or memory if it is unavailable.
+    repository: Box<dyn ProjectRepository>,
+    /// Library project the estimate was last opened from or stored as.
+    stored_project: Option<ProjectId>,
+    library_open: bool,
+    library_search: String,
+    /// Stored projects matching `library_search`, newest first.
+    library: Vec<StoredProject>,
+    /// Outcome of the last library action.
+    library_status: String,
+    /// Stored project whose Delete button was pressed, awaiting confirmation.
+    pending_delete: Option<ProjectId>,
     active_tab: TabKind,
-    quote_image_path: Option<String>,
+    quote_preview: QuotePreview,
+    /// Outcome of the last quote generation, shown under the preview.
+    quote_status: String,
+    /// BoM cells being typed into, by line index and field; other cells show the backend value.
+    bom_drafts: BTreeMap<(usize, BomField), String>,
+    /// Why a BoM cell's text was rejected, by line index and field.
+    bom_errors: BTreeMap<(usize, BomField), String>,
+    /// Cell being typed into, whose valid values are applied as they are
+    /// typed and undo as one step, and the Breakdown rows as they stood when
+    /// typing began, so the row being edited stays put.
+    live_cell: Option<((usize, BomField), Vec<usize>)>,
+    /// Sort and filters for the Breakdown table; exports keep the same lines,
+    /// in BoM order.
+    bom_query: BomQuery,
+    /// Text in the range filter boxes; a bound only changes when its text parses.
+    bom_range_drafts: BTreeMap<BomRange, String>,
+    bom_filter_error: Option<String>,
+    /// Column widths and scroll position of the Breakdown table.
+    bom_table: VirtualTable,
+    /// Whether the Breakdown tab lists lines or shows the assembly tree.
+    bom_view: BomView,
+    /// Column widths and scroll position of the assembly tree.
+    tree_table: VirtualTable,
+    /// Assemblies whose parts are hidden in the tree, by BoM index.
+    collapsed_assemblies: BTreeSet<usize>,
+    /// Column widths and scroll position of the supplier grouping.
+    supplier_table: VirtualTable,
+    /// Outcome of the last supplier catalogue import.
+    catalogue_status: String,
+    /// Outcome of the last BoM export.
+    bom_export_status: String,
+    /// Issued purchase order whose lines are shown in the Orders tab, by number.
+    selected_order: Option<u32>,
+    /// Outcome of the last purchase order run.
+    orders_status: String,
+    /// In-progress text for each pricing adjustment's value, aligned with the backend list.
+    adjustment_drafts: Vec<String>,
+    pricing_error: Option<String>,
+    /// In-progress text for the installation date (YYYY-MM-DD).
+    installation_draft: String,
+    planning_error: Option<String>,
+    timeline_scale: TimeScale,
+    /// In-progress text for the surplus warning threshold, in percent.
+    threshold_draft: String,
+    ordering_error: Option<String>,
+    /// Problems loading holiday files at startup.
+    calendar_errors: Vec<String>,
+    log_filter: LogFilter,
+    /// Outcome of the last log export.
+    log_status: String,
 }
 
-struct MaterialRow {
-    name: &'static str,
-    lead_time_days: u32,
-    min_quantity: u32,
+/// Project actions that replace the current estimate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProjectAction {
+    New,
+    Open,
+    OpenStored(ProjectId),
 }
 
-fn demo_materials() -> Vec<MaterialRow> {
-    vec![
-        MaterialRow {
-            name: "Steel frame sections",
-            lead_time_days: 21,
-            min_quantity: 50,
-        },
-        MaterialRow {
-            name: "Electrical fixtures",
-            lead_time_days: 14,
-            min_quantity: 40,
-        },
-        MaterialRow {
-            name: "Finishing materials",
-            lead_time_days: 10,
-            min_quantity: 100,
-        },
-    ]
+/// Range filter boxes on the Breakdown tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum BomRange {
+    MinTotal,
+    MaxTotal,
+    MinLead,
+    MaxLead,
+}
+
+/// Layouts of the Breakdown tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BomView {
+    /// Every line as an editable row, sorted and filtered by the BoM query.
+    Lines,
+    /// Lines nested under their assemblies with rolled-up figures.
+    Assemblies,
+    /// Bought lines under the supplier they come from.
+    Suppliers,
 }
 
+/// Pricing steps that can be appended from the Settings tab.
 #[derive(Debug, Clone, Copy)]
+enum NewAdjustment {
+    Markup,
+    FixedFee,
+    Discount,
+    Tax,
+}
+
+/// Which lines a tax adjustment is charged on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TaxScope {
+    AllLines,
+    Category(String),
+}
+
+impl std::fmt::Display for TaxScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaxScope::AllLines => f.write_str("All lines"),
+            TaxScope::Category(category) => f.write_str(category),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 enum Message {
     TabSelected(TabKind),
+    Undo,
+    Redo,
+    ProjectPathChanged(String),
+    ProjectNew,
+    ProjectOpened,
+    ProjectSaved,
+    ProjectSavedAs,
+    ProjectDiscardConfirmed,
+    ProjectDiscardCancelled,
+    RevisionNoteChanged(String),
+    RevisionIssued,
+    CompareFromSet(Option<u32>),
+    CompareToSet(Option<u32>),
+    LibraryToggled,
+    LibrarySearchChanged(String),
+    LibraryStored,
+    LibraryOpened(ProjectId),
+    LibraryDeleteRequested(ProjectId),
+    LibraryDeleteConfirmed,
+    LibraryDeleteCancelled,
+    QuoteRegenerated,
+    NextPage,
+    PreviousPage,
+    JumpToPage(usize),
+    ZoomIn,
+    ZoomOut,
+    ZoomSet(Zoom),
+    PageRendered(RenderOutcome),
+    BomCellChanged(usize, BomField, String),
+    BomCellSubmitted,
+    BomLineAdded,
+    BomLineDuplicated(usize),
+    BomLineRemoved(usize),
+    BomSortClicked(BomSortKey),
+    BomNameFilterChanged(String),
+    BomRangeChanged(BomRange, String),
+    BomFiltersCleared,
+    BomViewExported,
+    BomTable(TableEvent),
+    BomViewSet(BomView),
+    TreeTable(TableEvent),
+    AssemblyToggled(usize),
+    AssembliesExpanded(bool),
+    SupplierTable(TableEvent),
+    CatalogueImported,
+    PurchaseOrdersIssued,
+    PurchaseOrderSelected(Option<u32>),
+    AdjustmentValueChanged(usize, String),
+    AdjustmentValueSubmitted(usize),
+    AdjustmentMoved(usize, bool),
+    AdjustmentRemoved(usize),
+    AdjustmentAdded(NewAdjustment),
+    TaxScopeSelected(usize, TaxScope),
+    InstallationDateChanged(String),
+    InstallationDateSubmitted,
+    WeekendToggled(Weekday),
+    TimelineScaleSet(TimeScale),
+    ThresholdChanged(String),
+    ThresholdSubmitted,
+    LogLevelSelected(LogLevel),
+    LogSearchChanged(String),
+    LogsExported,
 }
 
-impl Sandbox for TabsApp {
+impl Application for TabsApp {
+    type Executor = executor::Default;
     type Message = Message;
+    type Theme = Theme;
+    type Flags = AppConfig;
 
-    fn new() -> Self {
-        // BOM is wired from a CSV file under tabs/examples.
-        let backend_state = AppState::demo_with_bom_path("../examples/bom.csv");
-
-        // Render the example quote PDF into a PNG that the UI can display.
-        let pdf_path = "../examples/quote.pdf";
-        let png_path = "../gen/quote_preview.png";
-        let quote_image_path = match render_quote_pdf_to_png(pdf_path, png_path) {
-            Ok(()) => Some(png_path.to_string()),
-            Err(err) => {
-                eprintln!("PDF render error: {err:?}");
-                None
-            }
+    fn new(config: AppConfig) -> (Self, Command<Message>) {
+        let mut backend_state = AppState::demo_with_bom_path(config.path(PathSetting::BomCsv));
+        let loaded = match &config.config_file {
+            Some(file) => LogEvent::new(LogLevel::Info, "config", "configuration loaded")
+                .with_field("file", file.display()),
+            None => LogEvent::new(LogLevel::Info, "config", "no config file; using defaults"),
         };
+        backend_state.record(loaded);
+        for problem in &config.problems {
+            backend_state.record(LogEvent::new(
+                problem.severity.into(),
+                "config",
+                problem.message.clone(),
+            ));
+        }
 
-        Self {
+        let repository = open_repository(&config, &mut backend_state);
+        let mut app = Self {
+            quote_preview: QuotePreview::new(config.path(PathSetting::PreviewCache)),
+            config,
             backend_state,
+            project_path: None,
+            project_draft: String::new(),
+            project_status: String::new(),
+            pending_discard: None,
+            revision_note: String::new(),
+            compare_from: None,
+            compare_to: None,
+            revision_error: None,
+            repository,
+            stored_project: None,
+            library_open: false,
+            library_search: String::new(),
+            library: Vec::new(),
+            library_status: String::new(),
+            pending_delete: None,
             active_tab: TabKind::Overview,
-            quote_image_path,
-        }
+            quote_status: String::new(),
+            bom_drafts: BTreeMap::new(),
+            bom_errors: BTreeMap::new(),
+            live_cell: None,
+            bom_query: BomQuery::default(),
+            bom_range_drafts: BTreeMap::new(),
+            bom_filter_error: None,
+            bom_table: VirtualTable::new(&BOM_COLUMNS.map(|(_, _, width)| width)),
+            bom_view: BomView::Lines,
+            tree_table: VirtualTable::new(&TREE_COLUMNS.map(|(_, width)| width)),
+            collapsed_assemblies: BTreeSet::new(),
+            supplier_table: VirtualTable::new(&SUPPLIER_COLUMNS.map(|(_, width)| width)),
+            catalogue_status: String::new(),
+            selected_order: None,
+            orders_status: String::new(),
+            bom_export_status: String::new(),
+            adjustment_drafts: Vec::new(),
+            pricing_error: None,
+            installation_draft: String::new(),
+            planning_error: None,
+            timeline_scale: TimeScale::Week,
+            threshold_draft: String::new(),
+            ordering_error: None,
+            calendar_errors: Vec::new(),
+            log_filter: LogFilter::default(),
+            log_status: String::new(),
+        };
+        app.reset_project_draft();
+        app.reset_comparison();
+        app.reset_installation_draft();
+        app.reset_threshold_draft();
+        app.load_calendars();
+        app.load_catalogue();
+        // Loading the startup files is not something to undo.
+        app.backend_state.clear_history();
+        app.reset_adjustment_drafts();
+        // The first page renders in the background; the Quote tab shows a loading state meanwhile.
+        let command = app.regenerate_quote();
+        (app, command)
     }
 
     fn title(&self) -> String {
-        String::from("Tabs example – Rust + Iced")
+        let name = match (&self.project_path, self.stored_project) {
+            (Some(path), _) => path.file_name().map_or_else(
+                || path.display().to_string(),
+                |name| name.to_string_lossy().into_owned(),
+            ),
+            (None, Some(_)) => format!("{} (library)", self.backend_state.quote().number),
+            (None, None) => "Untitled".to_string(),
+        };
+        let modified = if self.backend_state.is_modified() {
+            " (modified)"
+        } else {
+            ""
+        };
+        format!("{name}{modified} – Tabs example – Rust + Iced")
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        let resize = if self.bom_table.is_resizing() {
+            iced::event::listen_with(table_resize)
+        } else if self.tree_table.is_resizing() {
+            iced::event::listen_with(tree_resize)
+        } else if self.supplier_table.is_resizing() {
+            iced::event::listen_with(supplier_resize)
+        } else {
+            Subscription::none()
+        };
+        Subscription::batch([keyboard::on_key_press(shortcut), resize])
     }
 
-    fn update(&mut self, message: Message) {
+    fn update(&mut self, message: Message) -> Command<Message> {
+        if self.ends_cell_edit(&message) {
+            let finished = self.finish_cell_edit();
+            return Command::batch([finished, self.update(message)]);
+        }
         match message {
             Message::TabSelected(tab) => {
                 self.active_tab = tab;
+                if tab == TabKind::Breakdown {
+                    return self.breakdown_table().restore_scroll();
+                }
+            }
+            Message::ProjectPathChanged(value) => self.project_draft = value,
+            Message::ProjectNew => return self.replace_project(ProjectAction::New),
+            Message::ProjectOpened => return self.replace_project(ProjectAction::Open),
+            Message::ProjectDiscardConfirmed => {
+                if let Some(action) = self.pending_discard.take() {
+                    return self.run_project_action(action);
+                }
+            }
+            Message::ProjectDiscardCancelled => self.pending_discard = None,
+            Message::RevisionNoteChanged(value) => self.revision_note = value,
+            Message::RevisionIssued => {
+                let today = chrono::Local::now().date_naive();
+                match self
+                    .backend_state
+                    .issue_revision(today, &self.revision_note)
+                {
+                    Ok(_) => {
+                        self.revision_note.clear();
+                        self.revision_error = None;
+                        self.reset_comparison();
+                    }
+                    Err(err) => self.revision_error = Some(err.to_string()),
+                }
+            }
+            Message::CompareFromSet(number) => self.compare_from = number,
+            Message::CompareToSet(number) => self.compare_to = number,
+            Message::LibraryToggled => {
+                self.library_open = !self.library_open;
+                self.pending_delete = None;
+                if self.library_open {
+                    self.refresh_library();
+                }
+            }
+            Message::LibrarySearchChanged(value) => {
+                self.library_search = value;
+                self.refresh_library();
+            }
+            Message::LibraryStored => {
+                match self
+                    .backend_state
+                    .save_stored_project(self.repository.as_mut(), self.stored_project)
+                {
+                    Ok(id) => {
+                        self.stored_project = Some(id);
+                        self.library_status =
+                            format!("Stored {}", self.backend_state.quote().number);
+                    }
+                    Err(err) => self.library_status = err.to_string(),
+                }
+                self.refresh_library();
+            }
+            Message::LibraryOpened(id) => {
+                return self.replace_project(ProjectAction::OpenStored(id))
+            }
+            Message::LibraryDeleteRequested(id) => self.pending_delete = Some(id),
+            Message::LibraryDeleteCancelled => self.pending_delete = None,
+            Message::LibraryDeleteConfirmed => {
+                if let Some(id) = self.pending_delete.take() {
+                    match self.repository.delete_project(id) {
+                        Ok(()) => {
+                            if self.stored_project == Some(id) {
+                                self.stored_project = None;
+                            }
+                            self.library_status = "Deleted the stored project".to_string();
+                        }
+                        Err(err) => self.library_status = err.to_string(),
+                    }
+                    self.refresh_library();
+                }
+            }
+            Message::ProjectSaved => {
+                let path = self
+                    .project_path
+                    .clone()
+                    .unwrap_or_else(|| self.project_target());
+                self.save_project(path);
+            }
+            Message::ProjectSavedAs => {
+                let path = self.project_target();
+                self.save_project(path);
+            }
+            Message::Undo => {
+                if self.backend_state.undo().is_some() {
+                    return self.history_changed();
+                }
+            }
+            Message::Redo => {
+                if self.backend_state.redo().is_some() {
+                    return self.history_changed();
+                }
+            }
+            Message::QuoteRegenerated => return self.regenerate_quote(),
+            Message::NextPage => return render_command(self.quote_preview.next()),
+            Message::PreviousPage => return render_command(self.quote_preview.previous()),
+            Message::JumpToPage(page) => return render_command(self.quote_preview.go_to(page)),
+            Message::ZoomIn => {
+                let zoom = self.quote_preview.zoom().zoom_in();
+                self.quote_preview.set_zoom(zoom);
+            }
+            Message::ZoomOut => {
+                let zoom = self.quote_preview.zoom().zoom_out();
+                self.quote_preview.set_zoom(zoom);
+            }
+            Message::ZoomSet(zoom) => self.quote_preview.set_zoom(zoom),
+            Message::PageRendered(mut outcome) => {
+                for event in outcome.take_log_events() {
+                    self.backend_state.record(event);
+                }
+                return render_command(self.quote_preview.finish(outcome));
+            }
+            Message::InstallationDateChanged(value) => self.installation_draft = value,
+            Message::InstallationDateSubmitted => {
+                match self
+                    .backend_state
+                    .set_installation_date(&self.installation_draft)
+                {
+                    Ok(()) => {
+                        self.planning_error = None;
+                        self.reset_installation_draft();
+                    }
+                    Err(err) => self.planning_error = Some(err.to_string()),
+                }
+            }
+            Message::WeekendToggled(day) => {
+                let off = !self
+                    .backend_state
+                    .planning()
+                    .calendar
+                    .weekend
+                    .contains(&day);
+                self.backend_state.set_weekend_day(None, day, off);
+            }
+            Message::TimelineScaleSet(scale) => self.timeline_scale = scale,
+            Message::ThresholdChanged(value) => self.threshold_draft = value,
+            Message::ThresholdSubmitted => {
+                match self
+                    .backend_state
+                    .set_surplus_threshold(&self.threshold_draft)
+                {
+                    Ok(()) => {
+                        self.ordering_error = None;
+                        self.reset_threshold_draft();
+                    }
+                    Err(err) => self.ordering_error = Some(err.to_string()),
+                }
+            }
+            Message::LogLevelSelected(level) => self.log_filter.min_level = level,
+            Message::LogSearchChanged(search) => self.log_filter.search = search,
+            Message::LogsExported => {
+                let path = self.config.path(PathSetting::LogExport);
+                self.log_status = match self.backend_state.log().export(path, &self.log_filter) {
+                    Ok(count) => format!("Exported {count} events to {}", path.display()),
+                    Err(err) => format!("Export to {} failed: {err}", path.display()),
+                };
+            }
+            Message::BomCellChanged(index, field, value) => {
+                let cell = (index, field);
+                if self.live_cell.is_none() {
+                    self.backend_state.begin_cell_edit(index, field);
+                    let rows = self.backend_state.query_bom(&self.bom_query);
+                    self.live_cell = Some((cell, rows));
+                }
+                // Apply each valid value as it is typed, so totals follow along.
+                let checked = self.backend_state.bom().get(index).map(|item| {
+                    edit_bom_item(item, field, &value, self.backend_state.settings().rounding)
+                        .map(|edited| edited.field_text(field) != item.field_text(field))
+                });
+                match checked {
+                    Some(Err(err)) => {
+                        self.bom_errors.insert(cell, err.to_string());
+                    }
+                    Some(Ok(changed)) => {
+                        self.bom_errors.remove(&cell);
+                        if changed {
+                            if let Err(err) = self.backend_state.set_bom_cell(index, field, &value)
+                            {
+                                self.bom_errors.insert(cell, err.to_string());
+                            }
+                        }
+                    }
+                    None => {}
+                }
+                self.bom_drafts.insert(cell, value);
+            }
+            // Typing already applied the value; Enter only ends the edit,
+            // which `ends_cell_edit` has done.
+            Message::BomCellSubmitted => {}
+            Message::BomLineAdded => {
+                self.backend_state.add_bom_line();
+                return self.bom_changed();
+            }
+            Message::BomLineDuplicated(index) => {
+                if self.backend_state.duplicate_bom_line(index).is_ok() {
+                    return self.bom_changed();
+                }
+            }
+            Message::BomLineRemoved(index) => {
+                if self.backend_state.remove_bom_line(index).is_ok() {
+                    return self.bom_changed();
+                }
+            }
+            Message::BomSortClicked(key) => {
+                self.bom_query.toggle_sort(key);
+                return self.bom_table.scroll_to_top();
+            }
+            Message::BomNameFilterChanged(value) => {
+                self.bom_query.name = value;
+                return self.bom_table.scroll_to_top();
+            }
+            Message::BomRangeChanged(range, value) => {
+                let currency = self.backend_state.settings().currency;
+                let raw = value.trim();
+                let money = || {
+                    (!raw.is_empty())
+                        .then(|| Money::parse(raw, currency))
+                        .transpose()
+                        .map_err(|err| format!("{raw:?} is not an amount: {err}"))
+                };
+                let days = || {
+                    (!raw.is_empty())
+                        .then(|| raw.parse::<u32>())
+                        .transpose()
+                        .map_err(|_| format!("{raw:?} is not a whole number of days"))
+                };
+                let query = &mut self.bom_query;
+                let parsed = match range {
+                    BomRange::MinTotal => money().map(|bound| query.min_total = bound),
+                    BomRange::MaxTotal => money().map(|bound| query.max_total = bound),
+                    BomRange::MinLead => days().map(|bound| query.min_lead_time_days = bound),
+                    BomRange::MaxLead => days().map(|bound| query.max_lead_time_days = bound),
+                };
+                self.bom_filter_error = parsed.err();
+                self.bom_range_drafts.insert(range, value);
+                return self.bom_table.scroll_to_top();
+            }
+            Message::BomFiltersCleared => {
+                self.bom_query = BomQuery {
+                    sort: self.bom_query.sort,
+                    ..BomQuery::default()
+                };
+                self.bom_range_drafts.clear();
+                self.bom_filter_error = None;
+                return self.bom_table.scroll_to_top();
+            }
+            Message::BomViewExported => {
+                let path = self.config.path(PathSetting::BomExport);
+                self.bom_export_status = match self.backend_state.export_bom(path, &self.bom_query)
+                {
+                    Ok(count) => format!("Exported {count} lines to {}", path.display()),
+                    Err(err) => format!("Export to {} failed: {err}", path.display()),
+                };
+            }
+            Message::BomTable(event) => self.bom_table.update(event),
+            Message::BomViewSet(view) => {
+                self.bom_view = view;
+                return self.breakdown_table().restore_scroll();
+            }
+            Message::TreeTable(event) => self.tree_table.update(event),
+            Message::AssemblyToggled(index) => {
+                if !self.collapsed_assemblies.remove(&index) {
+                    self.collapsed_assemblies.insert(index);
+                }
+            }
+            Message::AssembliesExpanded(expanded) => {
+                self.collapsed_assemblies.clear();
+                if !expanded {
+                    if let Ok(tree) = BomTree::build(self.backend_state.bom()) {
+                        self.collapsed_assemblies
+                            .extend((0..tree.nodes.len()).filter(|&i| tree.is_assembly(i)));
+                    }
+                }
+                return self.tree_table.scroll_to_top();
+            }
+            Message::SupplierTable(event) => self.supplier_table.update(event),
+            Message::CatalogueImported => self.load_catalogue(),
+            Message::PurchaseOrdersIssued => {
+                let dir = self.config.path(PathSetting::PurchaseOrders).to_path_buf();
+                let today = chrono::Local::now().date_naive();
+                self.orders_status = match self.backend_state.issue_purchase_orders(&dir, today) {
+                    Ok(orders) if orders.is_empty() => "Nothing left to order".to_string(),
+                    Ok(orders) => {
+                        self.selected_order = orders.first().map(|order| order.number);
+                        format!(
+                            "{} issued to {}",
+                            match orders.as_slice() {
+                                [order] => order.reference(),
+                                _ => format!("{} purchase orders", orders.len()),
+                            },
+                            dir.display()
+                        )
+                    }
+                    Err(err) => err.to_string(),
+                };
+            }
+            Message::PurchaseOrderSelected(number) => self.selected_order = number,
+            Message::AdjustmentValueChanged(index, value) => {
+                if let Some(draft) = self.adjustment_drafts.get_mut(index) {
+                    *draft = value;
+                }
+            }
+            Message::AdjustmentValueSubmitted(index) => {
+                let value = self
+                    .adjustment_drafts
+                    .get(index)
+                    .cloned()
+                    .unwrap_or_default();
+                match self.backend_state.set_adjustment_value(index, &value) {
+                    Ok(()) => {
+                        self.pricing_error = None;
+                        return self.pricing_changed();
+                    }
+                    Err(err) => self.pricing_error = Some(err.to_string()),
+                }
+            }
+            Message::AdjustmentMoved(index, up) => {
+                self.pricing_error = self
+                    .backend_state
+                    .move_adjustment(index, up)
+                    .err()
+                    .map(|err| err.to_string());
+                return self.pricing_changed();
+            }
+            Message::AdjustmentRemoved(index) => {
+                self.pricing_error = self
+                    .backend_state
+                    .remove_adjustment(index)
+                    .err()
+                    .map(|err| err.to_string());
+                return self.pricing_changed();
+            }
+            Message::AdjustmentAdded(kind) => {
+                let currency = self.backend_state.settings().currency;
+                let (label, kind) = match kind {
+                    NewAdjustment::Markup => ("Markup", AdjustmentKind::Markup(Rate::default())),
+                    NewAdjustment::FixedFee => {
+                        ("Fee", AdjustmentKind::FixedFee(Money::zero(currency)))
+                    }
+                    NewAdjustment::Discount => {
+                        ("Discount", AdjustmentKind::Discount(Rate::default()))
+                    }
+                    NewAdjustment::Tax => (
+                        "Tax",
+                        AdjustmentKind::Tax {
+                            rate: Rate::default(),
+                            category: None,
+                        },
+                    ),
+                };
+                self.pricing_error = self
+                    .backend_state
+                    .add_adjustment(Adjustment {
+                        label: label.to_string(),
+                        kind,
+                    })
+                    .err()
+                    .map(|err| err.to_string());
+                return self.pricing_changed();
+            }
+            Message::TaxScopeSelected(index, scope) => {
+                let category = match &scope {
+                    TaxScope::AllLines => None,
+                    TaxScope::Category(category) => Some(category.as_str()),
+                };
+                self.pricing_error = self
+                    .backend_state
+                    .set_tax_category(index, category)
+                    .err()
+                    .map(|err| err.to_string());
+                return self.pricing_changed();
             }
         }
+        Command::none()
     }
 
     fn view(&self) -> Element<'_, Message> {
         let tabs_row = row![
             tab_button("Quote", TabKind::Overview, self.active_tab),
-            tab_button("Breakdown", TabKind::Logs, self.active_tab),
+            tab_button("Breakdown", TabKind::Breakdown, self.active_tab),
+            tab_button("Revisions", TabKind::Revisions, self.active_tab),
             tab_button("Planning", TabKind::Advanced, self.active_tab),
+            tab_button("Orders", TabKind::Orders, self.active_tab),
             tab_button("Settings", TabKind::Settings, self.active_tab),
+            tab_button("Logs", TabKind::Logs, self.active_tab),
+            self.history_buttons(),
         ]
-        .spacing(12);
+        .spacing(12)
+        .align_items(Alignment::Center);
 
         let header = column![
             text("Project estimate")
@@ -141,33 +877,63 @@ impl Sandbox for TabsApp {
             text("Preview, breakdown, planning, and configuration in one view.")
                 .size(14)
                 .style(iced::theme::Text::Color(cool_gray())),
+            self.project_bar(),
+            self.library_picker(),
         ]
         .spacing(4);
 
         let content: Element<_> = match self.active_tab {
             // Quote tab: preview of the exported quote as a rendered PDF page.
             TabKind::Overview => {
-                let pdf_path = "../examples/quote.pdf";
-
-                let preview_content: Element<_> = if let Some(path) = &self.quote_image_path {
-                    // Display the rendered PNG of the first page.
-                    image::viewer(image::Handle::from_path(path)).into()
-                } else {
-                    // Fallback text if rendering failed.
-                    column![
-                        text("Quote PDF preview")
-                            .size(16)
-                            .style(iced::theme::Text::Color(charcoal())),
-                        text(format!("Source: {}", pdf_path))
+                let preview_content: Element<_> =
+                    if let Some(handle) = self.quote_preview.current_image() {
+                        // Display the rendered current page at the selected zoom.
+                        let page = image(handle.clone());
+                        match self.quote_preview.zoom() {
+                            Zoom::FitWidth => scrollable(page.width(Length::Fill))
+                                .height(Length::Fill)
+                                .into(),
+                            Zoom::Percent(percent) => scrollable(page.width(Length::Fixed(
+                                PAGE_WIDTH_AT_100 * f32::from(percent) / 100.0,
+                            )))
+                            .direction(scrollable::Direction::Both {
+                                vertical: scrollable::Properties::default(),
+                                horizontal: scrollable::Properties::default(),
+                            })
+                            .height(Length::Fill)
+                            .into(),
+                        }
+                    } else if self.quote_preview.is_loading() {
+                        text(format!(
+                            "Rendering page {}…",
+                            self.quote_preview.current_page() + 1
+                        ))
+                        .size(14)
+                        .style(iced::theme::Text::Color(charcoal()))
+                        .into()
+                    } else {
+                        // Fallback text if rendering failed.
+                        let reason = self
+                            .quote_preview
+                            .error()
+                            .unwrap_or("No quote has been generated yet.");
+                        column![
+                            text("Quote PDF preview unavailable")
+                                .size(16)
+                                .style(iced::theme::Text::Color(charcoal())),
+                            text(format!(
+                                "Source: {}",
+                                self.config.path(PathSetting::QuotePdf).display()
+                            ))
                             .size(12)
-                            .style(iced::theme::Text::Color(cool_gray())),
-                        text("This area would render a formatted PDF of the quote before you export or send it.")
-                            .size(13)
-                            .style(iced::theme::Text::Color(cool_gray())),
-                    ]
-                    .spacing(6)
-                    .into()
-                };
+                            .style(iced::theme::Text::Color(charcoal())),
+                            text(reason)
+                                .size(13)
+                                .style(iced::theme::Text::Color(charcoal())),
+                        ]
+                        .spacing(6)
+                        .into()
+                    };
 
                 let preview_box = container(preview_content)
                     .width(Length::Fill)
@@ -188,123 +954,193 @@ impl Sandbox for TabsApp {
                         },
                     )));
 
+                let total_text = match self.backend_state.price_breakdown() {
+                    Ok(breakdown) => format!("Quoted total: {}", breakdown.grand_total),
+                    Err(err) => format!("Quoted total unavailable: {err}"),
+                };
+
                 column![
                     text("Quote preview")
                         .size(18)
                         .style(iced::theme::Text::Color(slate_blue())),
+                    row![
+                        text(total_text)
+                            .size(14)
+                            .style(iced::theme::Text::Color(soft_ivory())),
+                        small_button("Regenerate PDF", Some(Message::QuoteRegenerated)),
+                        text(&self.quote_status)
+                            .size(12)
+                            .style(iced::theme::Text::Color(cool_gray())),
+                    ]
+                    .spacing(12)
+                    .align_items(Alignment::Center),
+                    self.preview_controls(),
                     preview_box,
                 ]
                 .spacing(12)
                 .into()
             }
             // Breakdown tab: detailed BoQ / BoM-style list backed by the shared state.
-            TabKind::Logs => {
-                // Explicitly show which BoM source file is driving this view.
-                let bom_path = "../examples/bom.csv";
+            TabKind::Breakdown => {
                 let materials: &[BomItem] = self.backend_state.bom();
+                let import = self.backend_state.bom_import();
 
-                let header_row = row![
-                    container(
-                        text("Material")
-                            .size(14)
-                            .style(iced::theme::Text::Color(cool_gray()))
-                    )
-                    .width(Length::Fixed(COL_NAME_WIDTH)),
-                    container(
-                        text("Qty")
-                            .size(14)
-                            .style(iced::theme::Text::Color(cool_gray()))
-                    )
-                    .width(Length::Fixed(COL_QTY_WIDTH)),
-                    container(
-                        text("Unit cost")
-                            .size(14)
-                            .style(iced::theme::Text::Color(cool_gray()))
-                    )
-                    .width(Length::Fixed(COL_UNIT_WIDTH)),
-                    container(
-                        text("Line total")
-                            .size(14)
-                            .style(iced::theme::Text::Color(cool_gray()))
-                    )
-                    .width(Length::Fixed(COL_TOTAL_WIDTH)),
-                    container(
-                        text("Lead time")
-                            .size(14)
-                            .style(iced::theme::Text::Color(cool_gray()))
-                    )
-                    .width(Length::Fixed(COL_LEAD_WIDTH)),
-                    container(
-                        text("Min qty")
-                            .size(14)
-                            .style(iced::theme::Text::Color(cool_gray()))
-                    )
-                    .width(Length::Fixed(COL_MIN_WIDTH)),
-                ]
-                .spacing(8);
-
-                let rows = materials.iter().fold(
+                // Explicitly show which BoM source file is driving this view,
+                // and surface any rows the importer refused rather than hiding them.
+                let import_status = import.diagnostics.iter().fold(
                     column![
-                        text(format!("BoM source: {}", bom_path))
+                        text(format!("BoM source: {}", import.source))
                             .size(12)
                             .style(iced::theme::Text::Color(cool_gray())),
-                        header_row,
+                        text(import.summary())
+                            .size(13)
+                            .style(iced::theme::Text::Color(severity_color(import.severity()))),
                     ]
-                    .spacing(6),
-                    |col, m| {
+                    .spacing(2),
+                    |col, diagnostic| {
                         col.push(
-                            row![
-                                container(
-                                    text(&m.name)
-                                        .size(14)
-                                        .style(iced::theme::Text::Color(soft_ivory()))
-                                )
-                                .width(Length::Fixed(COL_NAME_WIDTH)),
-                                container(
-                                    text(m.quantity.to_string())
-                                        .size(14)
-                                        .style(iced::theme::Text::Color(soft_ivory()))
-                                )
-                                .width(Length::Fixed(COL_QTY_WIDTH)),
-                                container(
-                                    text(format!("£{:.2}", m.unit_cost))
-                                        .size(14)
-                                        .style(iced::theme::Text::Color(soft_ivory()))
-                                )
-                                .width(Length::Fixed(COL_UNIT_WIDTH)),
-                                container(
-                                    text(format!("£{:.2}", m.total_cost))
-                                        .size(14)
-                                        .style(iced::theme::Text::Color(soft_ivory()))
-                                )
-                                .width(Length::Fixed(COL_TOTAL_WIDTH)),
-                                container(
-                                    text(format!("{} days", m.lead_time_days))
-                                        .size(14)
-                                        .style(iced::theme::Text::Color(soft_ivory()))
-                                )
-                                .width(Length::Fixed(COL_LEAD_WIDTH)),
-                                container(
-                                    text(m.min_quantity.to_string())
-                                        .size(14)
-                                        .style(iced::theme::Text::Color(soft_ivory()))
-                                )
-                                .width(Length::Fixed(COL_MIN_WIDTH)),
-                            ]
-                            .spacing(8),
+                            text(format!("{}: {}", diagnostic.severity, diagnostic))
+                                .size(12)
+                                .style(iced::theme::Text::Color(severity_color(
+                                    diagnostic.severity,
+                                ))),
+                        )
+                    },
+                );
+
+                let reconciliation = self.backend_state.reconciliation();
+                let mismatches = reconciliation
+                    .iter()
+                    .filter(|line| line.status == LineStatus::Mismatch)
+                    .count();
+                let reconcile_summary = if mismatches == 0 {
+                    text("Line totals agree with quantity × unit cost.")
+                        .size(13)
+                        .style(iced::theme::Text::Color(cool_gray()))
+                } else {
+                    text(format!(
+                        "{} line {} differ from quantity × unit cost (using {}).",
+                        mismatches,
+                        if mismatches == 1 { "total" } else { "totals" },
+                        self.backend_state.settings().reconcile.authoritative
+                    ))
+                    .size(13)
+                    .style(iced::theme::Text::Color(terracotta()))
+                };
+
+                // Rows keep their BoM index so edits land on the right line.
+                let visible = self.visible_bom_rows();
+                let table = &self.bom_table;
+                let width = |column: usize| Length::Fixed(table.width(column));
+                let bom_row = |position: usize| {
+                    let index = visible[position];
+                    let m = &materials[index];
+                    let (check_text, check_color) = match reconciliation.get(index) {
+                        Some(line) => match line.status {
+                            LineStatus::Matches => ("ok".to_string(), cool_gray()),
+                            LineStatus::WithinTolerance => {
+                                (format!("≈ {}", line.difference), cool_gray())
+                            }
+                            LineStatus::Mismatch => {
+                                (format!("Δ {}", line.difference), terracotta())
+                            }
+                        },
+                        None => ("–".to_string(), cool_gray()),
+                    };
+                    row![
+                        self.bom_cell(index, m, BomField::Name, width(0)),
+                        self.bom_cell(index, m, BomField::Supplier, width(1)),
+                        self.bom_cell(index, m, BomField::PartNumber, width(2)),
+                        self.bom_cell(index, m, BomField::Quantity, width(3)),
+                        self.bom_cell(index, m, BomField::UnitCost, width(4)),
+                        container(
+                            text(m.total_cost.to_string())
+                                .size(14)
+                                .style(iced::theme::Text::Color(soft_ivory()))
+                        )
+                        .width(width(5)),
+                        self.bom_cell(index, m, BomField::LeadTimeDays, width(6)),
+                        self.bom_cell(index, m, BomField::MinQuantity, width(7)),
+                        self.bom_cell(index, m, BomField::Level, width(8)),
+                        container(
+                            text(check_text)
+                                .size(14)
+                                .style(iced::theme::Text::Color(check_color))
+                        )
+                        .width(width(9)),
+                        row![
+                            small_button("Copy", Some(Message::BomLineDuplicated(index))),
+                            small_button("Delete", Some(Message::BomLineRemoved(index))),
+                        ]
+                        .spacing(4)
+                        .width(width(10)),
+                    ]
+                    .spacing(COLUMN_GAP)
+                    .align_items(Alignment::Center)
+                    .into()
+                };
+                let heading = |column: usize, _width: f32| match BOM_COLUMNS[column] {
+                    (label, Some(key), _) => self.sort_header(label, key),
+                    (label, None, _) => text(label)
+                        .size(14)
+                        .style(iced::theme::Text::Color(cool_gray()))
+                        .into(),
+                };
+
+                // Cell errors are listed above the table so every row keeps the same height.
+                let cell_errors = self.bom_errors.iter().fold(
+                    column![].spacing(2),
+                    |errors, ((index, field), err)| {
+                        errors.push(
+                            text(format!("Line {} {field}: {err}", index + 1))
+                                .size(12)
+                                .style(iced::theme::Text::Color(terracotta())),
                         )
                     },
                 );
 
+                let layouts = [
+                    (BomView::Lines, "Lines"),
+                    (BomView::Assemblies, "Assemblies"),
+                    (BomView::Suppliers, "By supplier"),
+                ]
+                .into_iter()
+                .fold(row![].spacing(4), |layouts, (view, label)| {
+                    let on_press = (self.bom_view != view).then_some(Message::BomViewSet(view));
+                    layouts.push(small_button(label, on_press))
+                });
+                let body: Element<_> = match self.bom_view {
+                    BomView::Lines => column![
+                        self.bom_filters(visible.len()),
+                        cell_errors,
+                        table.view(&visible, heading, bom_row, Message::BomTable),
+                    ]
+                    .spacing(12)
+                    .into(),
+                    BomView::Assemblies => self.assembly_tree(),
+                    BomView::Suppliers => self.supplier_view(),
+                };
+
                 column![
-                    text("Materials & costs")
-                        .size(18)
-                        .style(iced::theme::Text::Color(slate_blue())),
-                    rows,
+                    row![
+                        text("Materials & costs")
+                            .size(18)
+                            .style(iced::theme::Text::Color(slate_blue())),
+                        layouts,
+                    ]
+                    .spacing(16)
+                    .align_items(Alignment::Center),
+                    import_status,
+                    reconcile_summary,
+                    body,
+                    small_button("Add line", Some(Message::BomLineAdded)),
+                    self.price_summary(),
                 ]
                 .spacing(12)
                 .into()
             }
+            // Logs tab: recent backend and UI events, newest first.
+            TabKind::Logs => self.log_view(),
             // Settings tab: configuration status for the estimating profile and rules.
             TabKind::Settings => {
                 let settings = self.backend_state.settings();
@@ -324,45 +1160,23 @@ impl Sandbox for TabsApp {
                     text(&settings.description)
                         .size(14)
                         .style(iced::theme::Text::Color(cool_gray())),
+                    text(format!(
+                        "Currency: {} · Rounding: {}",
+                        settings.currency, settings.rounding
+                    ))
+                    .size(14)
+                    .style(iced::theme::Text::Color(cool_gray())),
+                    self.path_settings(),
+                    self.ordering_settings(),
+                    self.pricing_settings(),
                 ]
                 .spacing(10)
                 .into()
             }
-            // Planning tab: indicative ordering timeline derived from lead times.
-            TabKind::Advanced => {
-                let materials = demo_materials();
-                let rows = materials
-                    .iter()
-                    .fold(column![], |col, m| {
-                        col.push(
-                            column![
-                                text(m.name)
-                                    .size(14)
-                                    .style(iced::theme::Text::Color(soft_ivory())),
-                                text(format!(
-                                    "Order at least {} units approximately {} days before installation.",
-                                    m.min_quantity, m.lead_time_days
-                                ))
-                                .size(13)
-                                .style(iced::theme::Text::Color(cool_gray())),
-                            ]
-                            .spacing(2),
-                        )
-                    })
-                    .spacing(8);
-
-                column![
-                    text("Order planning")
-                        .size(18)
-                        .style(iced::theme::Text::Color(slate_blue())),
-                    text("Indicative order timings based on current lead times.")
-                        .size(14)
-                        .style(iced::theme::Text::Color(cool_gray())),
-                    rows,
-                ]
-                .spacing(12)
-                .into()
-            }
+            // Planning tab: order-by dates derived from lead times and the installation date.
+            TabKind::Advanced => self.planning_view(),
+            TabKind::Revisions => self.revisions_view(),
+            TabKind::Orders => self.orders_view(),
         };
 
         let card_inner = column![header, content]
@@ -415,6 +1229,2138 @@ impl Sandbox for TabsApp {
     }
 }
 
+impl TabsApp {
+    /// Write the quote PDF from the current state and preview exactly those bytes,
+    /// so the preview always shows what will be sent.
+    fn regenerate_quote(&mut self) -> Command<Message> {
+        let path = self.config.path(PathSetting::QuotePdf);
+        match self.backend_state.export_quote_pdf(path) {
+            Ok(pdf) => {
+                self.quote_status = format!("Saved to {}", path.display());
+                render_command(self.quote_preview.load(pdf))
+            }
+            Err(err) => {
+                self.quote_status = err.to_string();
+                Command::none()
+            }
+        }
+    }
+
+    /// Page navigation and zoom controls for the quote preview.
+    fn preview_controls(&self) -> Element<'_, Message> {
+        let preview = &self.quote_preview;
+        let current = preview.current_page();
+        let count = preview.page_count();
+
+        let pages = (0..count).fold(row![].spacing(4), |pages, page| {
+            let on_press = (page != current).then_some(Message::JumpToPage(page));
+            pages.push(small_button(&(page + 1).to_string(), on_press))
+        });
+
+        row![
+            small_button("Previous", (current > 0).then_some(Message::PreviousPage)),
+            pages,
+            small_button("Next", (current + 1 < count).then_some(Message::NextPage)),
+            text(format!("Page {} of {}", (current + 1).min(count), count))
+                .size(13)
+                .style(iced::theme::Text::Color(cool_gray())),
+            small_button("-", Some(Message::ZoomOut)),
+            text(preview.zoom().label())
+                .size(13)
+                .style(iced::theme::Text::Color(soft_ivory())),
+            small_button("+", Some(Message::ZoomIn)),
+            small_button(
+                "Fit width",
+                (preview.zoom() != Zoom::FitWidth).then_some(Message::ZoomSet(Zoom::FitWidth)),
+            ),
+        ]
+        .spacing(8)
+        .align_items(Alignment::Center)
+        .into()
+    }
+
+    /// Re-read the editable adjustment values from the backend, discarding unsaved edits.
+    fn reset_adjustment_drafts(&mut self) {
+        self.adjustment_drafts = self
+            .backend_state
+            .settings()
+            .pricing
+            .adjustments
+            .iter()
+            .map(|adjustment| adjustment.kind.value_text())
+            .collect();
+    }
+
+    /// Column header that sorts the BoM by `key`, marked with the current direction.
+    fn sort_header(&self, label: &str, key: BomSortKey) -> Element<'_, Message> {
+        let (label, color) = match self.bom_query.sort {
+            Some((sorted, direction)) if sorted == key => {
+                let arrow = match direction {
+                    SortDirection::Ascending => "▲",
+                    SortDirection::Descending => "▼",
+                };
+                (format!("{label} {arrow}"), soft_ivory())
+            }
+            _ => (label.to_string(), cool_gray()),
+        };
+        button(text(label).size(14).style(iced::theme::Text::Color(color)))
+            .on_press(Message::BomSortClicked(key))
+            .style(iced::theme::Button::Text)
+            .padding(0)
+            .width(Length::Fill)
+            .into()
+    }
+
+    /// Name and range filters for the Breakdown table, and export of what it shows.
+    fn bom_filters(&self, showing: usize) -> Element<'_, Message> {
+        let range = |placeholder: &str, bound: BomRange, width: f32| {
+            let draft = self.bom_range_drafts.get(&bound).map_or("", String::as_str);
+            text_input(placeholder, draft)
+                .on_input(move |value| Message::BomRangeChanged(bound, value))
+                .size(14)
+                .width(Length::Fixed(width))
+        };
+        let label = |label: &str| {
+            text(label)
+                .size(14)
+                .style(iced::theme::Text::Color(cool_gray()))
+        };
+
+        let controls = row![
+            text_input("Filter by material", &self.bom_query.name)
+                .on_input(Message::BomNameFilterChanged)
+                .size(14)
+                .width(Length::Fixed(COL_NAME_WIDTH)),
+            label("Line total"),
+            range("from", BomRange::MinTotal, 90.0),
+            range("to", BomRange::MaxTotal, 90.0),
+            label("Lead days"),
+            range("from", BomRange::MinLead, 60.0),
+            range("to", BomRange::MaxLead, 60.0),
+            small_button(
+                "Clear filters",
+                self.bom_query
+                    .is_filtered()
+                    .then_some(Message::BomFiltersCleared)
+            ),
+            small_button("Export view", Some(Message::BomViewExported)),
+        ]
+        .spacing(8)
+        .align_items(Alignment::Center);
+
+        let total = self.backend_state.bom().len();
+        let mut status = if self.bom_query.is_filtered() {
+            format!("Showing {showing} of {total} lines")
+        } else {
+            format!("{total} lines")
+        };
+        if !self.bom_export_status.is_empty() {
+            status.push_str(&format!(" · {}", self.bom_export_status));
+        }
+
+        let mut filters = column![
+            controls,
+            text(status)
+                .size(12)
+                .style(iced::theme::Text::Color(cool_gray())),
+        ]
+        .spacing(4);
+        if let Some(err) = &self.bom_filter_error {
+            filters = filters.push(
+                text(err)
+                    .size(12)
+                    .style(iced::theme::Text::Color(terracotta())),
+            );
+        }
+        filters.into()
+    }
+
+    /// The BoM as an outline of assemblies and their parts, with each
+    /// assembly's cost and lead time rolled up from the parts below it.
+    fn assembly_tree(&self) -> Element<'_, Message> {
+        let materials = self.backend_state.bom();
+        let tree = match BomTree::build(materials) {
+            Ok(tree) => tree,
+            Err(err) => {
+                return text(format!("Assembly tree unavailable: {err}"))
+                    .size(14)
+                    .style(iced::theme::Text::Color(terracotta()))
+                    .into()
+            }
+        };
+
+        // Parts come after their assembly, so one pass settles every line.
+        let mut hidden = vec![false; tree.nodes.len()];
+        for (index, node) in tree.nodes.iter().enumerate() {
+            hidden[index] = node.parent.is_some_and(|parent| {
+                hidden[parent] || self.collapsed_assemblies.contains(&parent)
+            });
+        }
+        let shown: Vec<usize> = (0..tree.nodes.len()).filter(|&i| !hidden[i]).collect();
+
+        let assemblies = (0..tree.nodes.len())
+            .filter(|&i| tree.is_assembly(i))
+            .count();
+        let summary = if tree.is_flat() {
+            "No line is part of an assembly; set a line's level to nest it under the line above."
+                .to_string()
+        } else {
+            format!(
+                "{assemblies} {}, {} of {} lines shown",
+                if assemblies == 1 {
+                    "assembly"
+                } else {
+                    "assemblies"
+                },
+                shown.len(),
+                materials.len(),
+            )
+        };
+        let controls = row![
+            small_button(
+                "Expand all",
+                (!self.collapsed_assemblies.is_empty())
+                    .then_some(Message::AssembliesExpanded(true)),
+            ),
+            small_button(
+                "Collapse all",
+                (assemblies > 0).then_some(Message::AssembliesExpanded(false)),
+            ),
+            text(summary)
+                .size(12)
+                .style(iced::theme::Text::Color(cool_gray())),
+        ]
+        .spacing(8)
+        .align_items(Alignment::Center);
+
+        let table = &self.tree_table;
+        let cell = |value: String, column: usize, color: Color| {
+            container(text(value).size(14).style(iced::theme::Text::Color(color)))
+                .width(Length::Fixed(table.width(column)))
+        };
+        let tree_row = |position: usize| {
+            let index = shown[position];
+            let (item, node) = (&materials[index], &tree.nodes[index]);
+            let assembly = tree.is_assembly(index);
+            let toggle: Element<_> = if assembly {
+                let arrow = if self.collapsed_assemblies.contains(&index) {
+                    "▸"
+                } else {
+                    "▾"
+                };
+                button(
+                    text(arrow)
+                        .size(14)
+                        .style(iced::theme::Text::Color(soft_ivory())),
+                )
+                .on_press(Message::AssemblyToggled(index))
+                .style(iced::theme::Button::Text)
+                .padding(0)
+                .width(Length::Fixed(TREE_INDENT))
+                .into()
+            } else {
+                Space::with_width(Length::Fixed(TREE_INDENT)).into()
+            };
+            let color = if assembly { soft_ivory() } else { cool_gray() };
+            row![
+                row![
+                    Space::with_width(Length::Fixed(node.depth as f32 * TREE_INDENT)),
+                    toggle,
+                    text(&item.name)
+                        .size(14)
+                        .style(iced::theme::Text::Color(soft_ivory())),
+                ]
+                .align_items(Alignment::Center)
+                .width(Length::Fixed(table.width(0))),
+                cell(item.quantity.to_string(), 1, color),
+                cell(node.total_quantity.to_string(), 2, color),
+                cell(item.unit_cost.to_string(), 3, color),
+                cell(node.rolled_up_total.to_string(), 4, soft_ivory()),
+                cell(node.rolled_up_lead_time_days.to_string(), 5, color),
+            ]
+            .spacing(COLUMN_GAP)
+            .align_items(Alignment::Center)
+            .into()
+        };
+        let heading = |column: usize, _width: f32| {
+            text(TREE_COLUMNS[column].0)
+                .size(14)
+                .style(iced::theme::Text::Color(cool_gray()))
+                .into()
+        };
+
+        column![
+            controls,
+            table.view(&shown, heading, tree_row, Message::TreeTable),
+        ]
+        .spacing(12)
+        .into()
+    }
+
+    /// The table the Breakdown tab currently shows.
+    fn breakdown_table(&self) -> &VirtualTable {
+        match self.bom_view {
+            BomView::Lines => &self.bom_table,
+            BomView::Assemblies => &self.tree_table,
+            BomView::Suppliers => &self.supplier_table,
+        }
+    }
+
+    /// Bought lines grouped by supplier, each group headed by the
+    /// supplier's catalogue details and subtotal.
+    fn supplier_view(&self) -> Element<'_, Message> {
+        let groups = match self.backend_state.supplier_groups() {
+            Ok(groups) => groups,
+            Err(err) => {
+                return text(format!("Supplier grouping unavailable: {err}"))
+                    .size(14)
+                    .style(iced::theme::Text::Color(terracotta()))
+                    .into()
+            }
+        };
+        let catalogue = self.backend_state.suppliers();
+        let currency = self.backend_state.settings().currency;
+
+        // A heading row per group followed by its lines: group and line positions.
+        let rows: Vec<(usize, Option<usize>)> = groups
+            .iter()
+            .enumerate()
+            .flat_map(|(group, lines)| {
+                std::iter::once((group, None))
+                    .chain((0..lines.lines.len()).map(move |line| (group, Some(line))))
+            })
+            .collect();
+
+        let named = groups
+            .iter()
+            .filter(|group| group.supplier.is_some())
+            .count();
+        let known = groups
+            .iter()
+            .filter_map(|group| group.supplier.as_deref())
+            .filter(|name| catalogue.find(name).is_some())
+            .count();
+        let mut status = format!(
+            "{named} {}, {known} in the catalogue",
+            if named == 1 { "supplier" } else { "suppliers" }
+        );
+        if !self.catalogue_status.is_empty() {
+            status.push_str(&format!(" · {}", self.catalogue_status));
+        }
+        let controls = row![
+            small_button("Reload catalogue", Some(Message::CatalogueImported)),
+            text(status)
+                .size(12)
+                .style(iced::theme::Text::Color(cool_gray())),
+        ]
+        .spacing(8)
+        .align_items(Alignment::Center);
+
+        let table = &self.supplier_table;
+        let cell = |value: String, column: usize| {
+            container(
+                text(value)
+                    .size(14)
+                    .style(iced::theme::Text::Color(soft_ivory())),
+            )
+            .width(Length::Fixed(table.width(column)))
+        };
+        let supplier_row = |position: usize| -> Element<'_, Message> {
+            let (group, line) = rows[position];
+            let group = &groups[group];
+            if let Some(line) = line {
+                let (_, item) = &group.lines[line];
+                return row![
+                    cell(item.name.clone(), 0),
+                    cell(item.part_number.clone().unwrap_or_default(), 1),
+                    cell(item.quantity.to_string(), 2),
+                    cell(item.unit_cost.to_string(), 3),
+                    cell(item.total_cost.to_string(), 4),
+                    cell(item.lead_time_days.to_string(), 5),
+                ]
+                .spacing(COLUMN_GAP)
+                .align_items(Alignment::Center)
+                .into();
+            }
+
+            let lines = group.lines.len();
+            let totals = format!(
+                "{lines} {} · {}",
+                if lines == 1 { "line" } else { "lines" },
+                group.subtotal
+            );
+            let (name, details, color) = match group.supplier.as_deref() {
+                None => (
+                    "No supplier".to_string(),
+                    "set one in the Lines view".to_string(),
+                    ochre(),
+                ),
+                Some(name) => match catalogue.find(name) {
+                    None => (
+                        name.to_string(),
+                        "not in the catalogue".to_string(),
+                        ochre(),
+                    ),
+                    Some(supplier) => {
+                        let mut details: Vec<String> =
+                            [&supplier.contact, &supplier.email, &supplier.phone]
+                                .into_iter()
+                                .flatten()
+                                .cloned()
+                                .collect();
+                        if let Some(days) = supplier.default_lead_time_days {
+                            details.push(format!("usually {days} days"));
+                        }
+                        if supplier.currency != currency {
+                            details.push(format!("invoices in {}", supplier.currency));
+                        }
+                        (supplier.name.clone(), details.join(" · "), cool_gray())
+                    }
+                },
+            };
+            row![
+                text(name)
+                    .size(15)
+                    .style(iced::theme::Text::Color(slate_blue())),
+                text(totals)
+                    .size(13)
+                    .style(iced::theme::Text::Color(soft_ivory())),
+                text(details)
+                    .size(12)
+                    .style(iced::theme::Text::Color(color)),
+            ]
+            .spacing(12)
+            .align_items(Alignment::Center)
+            .into()
+        };
+        let heading = |column: usize, _width: f32| {
+            text(SUPPLIER_COLUMNS[column].0)
+                .size(14)
+                .style(iced::theme::Text::Color(cool_gray()))
+                .into()
+        };
+
+        column![
+            controls,
+            table.view(&rows, heading, supplier_row, Message::SupplierTable),
+        ]
+        .spacing(12)
+        .into()
+    }
+
+    /// Text input for one editable BoM cell, showing unsaved text while it is being edited.
+    fn bom_cell(
+        &self,
+        index: usize,
+        item: &BomItem,
+        field: BomField,
+        width: Length,
+    ) -> Element<'_, Message> {
+        let value = self
+            .bom_drafts
+            .get(&(index, field))
+            .cloned()
+            .unwrap_or_else(|| item.field_text(field));
+        text_input(&field.to_string(), &value)
+            .on_input(move |value| Message::BomCellChanged(index, field, value))
+            .on_submit(Message::BomCellSubmitted)
+            .size(14)
+            .width(width)
+            .into()
+    }
+
+    /// Project path, New/Open/Save/Save As, and a confirmation before unsaved changes are dropped.
+    fn project_bar(&self) -> Element<'_, Message> {
+        let actions: Element<_> = match self.pending_discard {
+            Some(action) => row![
+                text(match action {
+                    ProjectAction::New => "Unsaved changes will be lost. Start a new project?",
+                    ProjectAction::Open | ProjectAction::OpenStored(_) => {
+                        "Unsaved changes will be lost. Open anyway?"
+                    }
+                })
+                .size(13)
+                .style(iced::theme::Text::Color(ochre())),
+                small_button("Discard changes", Some(Message::ProjectDiscardConfirmed)),
+                small_button("Cancel", Some(Message::ProjectDiscardCancelled)),
+            ]
+            .spacing(8)
+            .align_items(Alignment::Center)
+            .into(),
+            None => row![
+                small_button("New", Some(Message::ProjectNew)),
+                small_button("Open", Some(Message::ProjectOpened)),
+                small_button("Save", Some(Message::ProjectSaved)),
+                small_button("Save As", Some(Message::ProjectSavedAs)),
+                small_button(
+                    if self.library_open {
+                        "Hide library"
+                    } else {
+                        "Library"
+                    },
+                    Some(Message::LibraryToggled),
+                ),
+            ]
+            .spacing(4)
+            .into(),
+        };
+        column![
+            row![
+                text_input("estimate.tabs.json", &self.project_draft)
+                    .on_input(Message::ProjectPathChanged)
+                    .on_submit(Message::ProjectOpened)
+                    .size(14)
+                    .width(Length::Fixed(320.0)),
+                actions,
+            ]
+            .spacing(8)
+            .align_items(Alignment::Center),
+            text(&self.project_status)
+                .size(12)
+                .style(iced::theme::Text::Color(cool_gray())),
+        ]
+        .spacing(4)
+        .into()
+    }
+
+    /// Where Open and Save As point: the typed path, under the projects
+    /// directory when relative, with the project extension added if missing.
+    fn project_target(&self) -> PathBuf {
+        let typed = PathBuf::from(self.project_draft.trim());
+        let path = if typed.is_relative() {
+            self.config.path(PathSetting::Projects).join(typed)
+        } else {
+            typed
+        };
+        if path.extension().is_some() {
+            path
+        } else {
+            path.with_extension(PROJECT_EXTENSION)
+        }
+    }
+
+    fn reset_project_draft(&mut self) {
+        let projects = self.config.path(PathSetting::Projects);
+        self.project_draft = match &self.project_path {
+            Some(path) => path
+                .strip_prefix(projects)
+                .unwrap_or(path)
+                .display()
+                .to_string(),
+            None => format!("estimate.{PROJECT_EXTENSION}"),
+        };
+    }
+
+    /// Start New or Open, first asking before unsaved changes are dropped.
+    fn replace_project(&mut self, action: ProjectAction) -> Command<Message> {
+        if self.backend_state.is_modified() {
+            self.pending_discard = Some(action);
+            return Command::none();
+        }
+        self.run_project_action(action)
+    }
+
+    fn run_project_action(&mut self, action: ProjectAction) -> Command<Message> {
+        match action {
+            ProjectAction::New => {
+                self.backend_state.new_project();
+                // Holidays come from the configured files, not the project.
+                self.load_calendars();
+                self.backend_state.clear_history();
+                self.project_path = None;
+                self.stored_project = None;
+                self.project_status = "Started a new project".to_string();
+                self.reset_project_draft();
+                self.history_changed()
+            }
+            ProjectAction::Open => {
+                let path = self.project_target();
+                let quote_pdf = match self.backend_state.open_project(&path) {
+                    Ok(quote_pdf) => quote_pdf,
+                    Err(err) => {
+                        self.project_status = err.to_string();
+                        return Command::none();
+                    }
+                };
+                self.project_status = format!("Opened {}", path.display());
+                self.project_path = Some(path);
+                self.stored_project = None;
+                self.reset_project_draft();
+                self.project_opened(quote_pdf)
+            }
+            ProjectAction::OpenStored(id) => {
+                let quote_pdf = match self
+                    .backend_state
+                    .open_stored_project(self.repository.as_ref(), id)
+                {
+                    Ok(quote_pdf) => quote_pdf,
+                    Err(err) => {
+                        self.library_status = err.to_string();
+                        return Command::none();
+                    }
+                };
+                self.library_status = format!("Opened {}", self.backend_state.quote().number);
+                self.project_path = None;
+                self.stored_project = Some(id);
+                self.reset_project_draft();
+                self.project_opened(quote_pdf)
+            }
+        }
+    }
+
+    /// Re-read the whole estimate after opening a project.
+    fn project_opened(&mut self, quote_pdf: Option<Vec<u8>>) -> Command<Message> {
+        self.selected_order = None;
+        self.orders_status.clear();
+        match quote_pdf {
+            // Show the quote exactly as it was when the project was saved.
+            Some(pdf) => {
+                self.reset_comparison();
+                self.pricing_error = None;
+                self.planning_error = None;
+                self.ordering_error = None;
+                self.bom_drafts.clear();
+                self.bom_errors.clear();
+                self.collapsed_assemblies.clear();
+                self.reset_installation_draft();
+                self.reset_threshold_draft();
+                self.reset_adjustment_drafts();
+                self.quote_status = "Showing the quote saved with the project".to_string();
+                render_command(self.quote_preview.load(pdf))
+            }
+            None => self.history_changed(),
+        }
+    }
+
+    fn refresh_library(&mut self) {
+        match self.repository.find_projects(&self.library_search) {
+            Ok(found) => self.library = found,
+            Err(err) => {
+                self.library.clear();
+                self.library_status = err.to_string();
+            }
+        }
+    }
+
+    /// Search box and list of stored projects, shown while the library is open.
+    fn library_picker(&self) -> Element<'_, Message> {
+        if !self.library_open {
+            return column![].into();
+        }
+        let controls = row![
+            text_input(
+                "Search quote number, customer or material",
+                &self.library_search
+            )
+            .on_input(Message::LibrarySearchChanged)
+            .size(14)
+            .width(Length::Fixed(320.0)),
+            small_button(
+                if self.stored_project.is_some() {
+                    "Store new revision"
+                } else {
+                    "Store in library"
+                },
+                Some(Message::LibraryStored),
+            ),
+            text(&self.library_status)
+                .size(12)
+                .style(iced::theme::Text::Color(cool_gray())),
+        ]
+        .spacing(8)
+        .align_items(Alignment::Center);
+
+        let list: Element<_> = if self.library.is_empty() {
+            text(if self.library_search.trim().is_empty() {
+                "No stored projects yet."
+            } else {
+                "No stored projects match."
+            })
+            .size(13)
+            .style(iced::theme::Text::Color(cool_gray()))
+            .into()
+        } else {
+            let rows = self
+                .library
+                .iter()
+                .fold(column![].spacing(4), |rows, stored| {
+                    let current = self.stored_project == Some(stored.id);
+                    let actions: Element<_> = if self.pending_delete == Some(stored.id) {
+                        row![
+                            text("Delete with all revisions?")
+                                .size(13)
+                                .style(iced::theme::Text::Color(terracotta())),
+                            small_button("Delete", Some(Message::LibraryDeleteConfirmed)),
+                            small_button("Keep", Some(Message::LibraryDeleteCancelled)),
+                        ]
+                        .spacing(4)
+                        .align_items(Alignment::Center)
+                        .into()
+                    } else {
+                        row![
+                            small_button("Open", Some(Message::LibraryOpened(stored.id))),
+                            small_button(
+                                "Delete",
+                                Some(Message::LibraryDeleteRequested(stored.id))
+                            ),
+                        ]
+                        .spacing(4)
+                        .into()
+                    };
+                    let cell = |value: String, width: f32| {
+                        text(value)
+                            .size(13)
+                            .style(iced::theme::Text::Color(if current {
+                                soft_ivory()
+                            } else {
+                                cool_gray()
+                            }))
+                            .width(Length::Fixed(width))
+                    };
+                    rows.push(
+                        row![
+                            cell(stored.quote_number.clone(), 90.0),
+                            cell(stored.customer.clone(), 200.0),
+                            cell(stored.saved_at.format("%d %b %Y %H:%M").to_string(), 130.0),
+                            cell(format!("{} lines", stored.lines), 70.0),
+                            cell(
+                                stored
+                                    .total
+                                    .map_or_else(|| "—".to_string(), |total| total.to_string()),
+                                100.0
+                            ),
+                            cell(format!("rev {}", stored.revision), 50.0),
+                            actions,
+                        ]
+                        .spacing(8)
+                        .align_items(Alignment::Center),
+                    )
+                });
+            scrollable(rows).height(Length::Fixed(160.0)).into()
+        };
+        column![controls, list].spacing(6).into()
+    }
+
+    fn save_project(&mut self, path: PathBuf) {
+        match self.backend_state.save_project(&path) {
+            Ok(()) => {
+                self.project_status = format!("Saved to {}", path.display());
+                self.project_path = Some(path);
+                self.reset_project_draft();
+            }
+            Err(err) => self.project_status = err.to_string(),
+        }
+    }
+
+    /// Undo and redo buttons, enabled when there is a step to revert or re-apply.
+    fn history_buttons(&self) -> Element<'_, Message> {
+        let history = self.backend_state.history();
+        row![
+            small_button(
+                "Undo",
+                history.undo_label().is_some().then_some(Message::Undo)
+            ),
+            small_button(
+                "Redo",
+                history.redo_label().is_some().then_some(Message::Redo)
+            ),
+        ]
+        .spacing(4)
+        .into()
+    }
+
+    /// Re-read everything an undo or redo may have changed, discarding unsaved edits.
+    fn history_changed(&mut self) -> Command<Message> {
+        self.reset_comparison();
+        self.pricing_error = None;
+        self.planning_error = None;
+        self.ordering_error = None;
+        self.reset_installation_draft();
+        self.reset_threshold_draft();
+        self.reset_adjustment_drafts();
+        self.bom_changed()
+    }
+
+    /// Whether `message` ends the cell edit in progress: anything but more
+    /// typing in the same cell or activity nobody started (page rendering,
+    /// table scrolling and resizing) does.
+    fn ends_cell_edit(&self, message: &Message) -> bool {
+        let Some((cell, _)) = self.live_cell else {
+            return false;
+        };
+        !matches!(
+            message,
+            Message::BomCellChanged(index, field, _) if (*index, *field) == cell
+        ) && !matches!(
+            message,
+            Message::PageRendered(_)
+                | Message::BomTable(_)
+                | Message::TreeTable(_)
+                | Message::SupplierTable(_)
+        )
+    }
+
+    /// Close the cell edit in progress as one undo step, show the cell's
+    /// stored value again unless its text was rejected, and regenerate the
+    /// quote if the edit changed anything.
+    fn finish_cell_edit(&mut self) -> Command<Message> {
+        let Some((cell, _)) = self.live_cell.take() else {
+            return Command::none();
+        };
+        let changed = self.backend_state.end_cell_edit();
+        if !self.bom_errors.contains_key(&cell) {
+            self.bom_drafts.remove(&cell);
+        }
+        if changed {
+            self.regenerate_quote()
+        } else {
+            Command::none()
+        }
+    }
+
+    /// BoM line indices in the order the Breakdown table shows them, held
+    /// still while a cell is being typed into.
+    fn visible_bom_rows(&self) -> Vec<usize> {
+        match &self.live_cell {
+            Some((_, rows)) => rows.clone(),
+            None => self.backend_state.query_bom(&self.bom_query),
+        }
+    }
+
+    /// Drop unsaved cell edits once lines have moved, and refresh the quote.
+    fn bom_changed(&mut self) -> Command<Message> {
+        self.bom_drafts.clear();
+        self.bom_errors.clear();
+        // Line indices may have moved, so the tree opens fully expanded again.
+        self.collapsed_assemblies.clear();
+        self.regenerate_quote()
+    }
+
+    /// Refresh everything derived from the pricing rules after an edit.
+    fn pricing_changed(&mut self) -> Command<Message> {
+        self.reset_adjustment_drafts();
+        self.regenerate_quote()
+    }
+
+    /// Subtotal, each pricing adjustment and the grand total, as a two-column summary.
+    fn price_summary(&self) -> Element<'_, Message> {
+        let breakdown = match self.backend_state.price_breakdown() {
+            Ok(breakdown) => breakdown,
+            Err(err) => {
+                return text(format!("Totals unavailable: {err}"))
+                    .size(14)
+                    .style(iced::theme::Text::Color(terracotta()))
+                    .into()
+            }
+        };
+
+        let summary_row = |label: String, amount: String, color: Color| {
+            row![
+                container(text(label).size(14).style(iced::theme::Text::Color(color))).width(
+                    Length::Fixed(COL_NAME_WIDTH + COL_QTY_WIDTH + COL_UNIT_WIDTH + 16.0)
+                ),
+                container(text(amount).size(14).style(iced::theme::Text::Color(color)))
+                    .width(Length::Fixed(COL_TOTAL_WIDTH)),
+            ]
+            .spacing(8)
+        };
+
+        let lines = breakdown.lines.iter().fold(
+            column![summary_row(
+                "Subtotal".to_string(),
+                breakdown.subtotal.to_string(),
+                soft_ivory(),
+            )]
+            .spacing(4),
+            |col, line| {
+                let label = match &line.kind {
+                    AdjustmentKind::Markup(rate)
+                    | AdjustmentKind::Discount(rate)
+                    | AdjustmentKind::Tax { rate, .. } => format!("{} ({rate})", line.label),
+                    AdjustmentKind::FixedFee(_) => line.label.clone(),
+                };
+                col.push(summary_row(label, line.amount.to_string(), cool_gray()))
+            },
+        );
+
+        lines
+            .push(summary_row(
+                "Grand total".to_string(),
+                breakdown.grand_total.to_string(),
+                soft_ivory(),
+            ))
+            .into()
+    }
+
+    /// Re-read the installation date from the backend, discarding unsaved edits.
+    fn reset_installation_draft(&mut self) {
+        self.installation_draft = self
+            .backend_state
+            .planning()
+            .installation_date
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+    }
+
+    /// Load our holidays and one calendar per file in the supplier calendar
+    /// directory (the file name, without extension, is the supplier's name).
+    fn load_calendars(&mut self) {
+        let holidays = self.config.path(PathSetting::Holidays).to_path_buf();
+        if holidays.is_file() {
+            if let Err(err) = self.backend_state.load_holidays(&holidays, None) {
+                self.calendar_errors
+                    .push(format!("{}: {err}", holidays.display()));
+            }
+        }
+
+        let dir = self
+            .config
+            .path(PathSetting::SupplierCalendars)
+            .to_path_buf();
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            return;
+        };
+        let mut files: Vec<_> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension().is_some_and(|ext| {
+                    ext.eq_ignore_ascii_case("ics") || ext.eq_ignore_ascii_case("csv")
+                })
+            })
+            .collect();
+        files.sort();
+        for path in files {
+            let Some(supplier) = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+            else {
+                continue;
+            };
+            if let Err(err) = self.backend_state.load_holidays(&path, Some(&supplier)) {
+                self.calendar_errors
+                    .push(format!("{}: {err}", path.display()));
+            }
+        }
+    }
+
+    /// Read the configured supplier catalogue, if there is one, into the estimate.
+    fn load_catalogue(&mut self) {
+        let path = self
+            .config
+            .path(PathSetting::SupplierCatalogue)
+            .to_path_buf();
+        if !path.is_file() {
+            self.catalogue_status = format!("no catalogue at {}", path.display());
+            return;
+        }
+        self.catalogue_status = match self.backend_state.import_suppliers(&path) {
+            Ok(report) => format!("{} from {}", report.summary(), path.display()),
+            Err(err) => err.to_string(),
+        };
+    }
+
+    /// Working-week toggles and a summary of the holiday calendars in use.
+    fn calendar_settings(&self) -> Element<'_, Message> {
+        let planning = self.backend_state.planning();
+        let weekend = &planning.calendar.weekend;
+
+        let days = WEEKDAYS.into_iter().fold(row![].spacing(4), |days, day| {
+            let working = !weekend.contains(&day);
+            let label = text(day.to_string())
+                .size(13)
+                .style(iced::theme::Text::Color(if working {
+                    soft_ivory()
+                } else {
+                    charcoal()
+                }));
+            days.push(
+                button(label)
+                    .on_press(Message::WeekendToggled(day))
+                    .style(if working {
+                        iced::theme::Button::Primary
+                    } else {
+                        iced::theme::Button::Secondary
+                    }),
+            )
+        });
+
+        let suppliers = if planning.supplier_calendars.is_empty() {
+            "Supplier calendars: none; supplier lead times use our calendar.".to_string()
+        } else {
+            let names = planning
+                .supplier_calendars
+                .iter()
+                .map(|(name, calendar)| format!("{name} ({} holidays)", calendar.holidays.len()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("Supplier calendars: {names}")
+        };
+
+        let section = column![
+            row![
+                text("Working days:")
+                    .size(14)
+                    .style(iced::theme::Text::Color(soft_ivory())),
+                days,
+                text(format!("{} holidays", planning.calendar.holidays.len()))
+                    .size(13)
+                    .style(iced::theme::Text::Color(cool_gray())),
+            ]
+            .spacing(8)
+            .align_items(Alignment::Center),
+            text(suppliers)
+                .size(13)
+                .style(iced::theme::Text::Color(cool_gray())),
+        ]
+        .spacing(6);
+
+        self.calendar_errors
+            .iter()
+            .fold(section, |col, err| {
+                col.push(
+                    text(err)
+                        .size(13)
+                        .style(iced::theme::Text::Color(terracotta())),
+                )
+            })
+            .into()
+    }
+
+    fn reset_threshold_draft(&mut self) {
+        self.threshold_draft = self
+            .backend_state
+            .settings()
+            .ordering
+            .surplus_threshold
+            .to_string();
+    }
+
+    /// Surplus threshold used to flag wasteful rounding up.
+    fn ordering_settings(&self) -> Element<'_, Message> {
+        let mut section = row![
+            text("Flag order surplus above")
+                .size(14)
+                .style(iced::theme::Text::Color(soft_ivory())),
+            text_input("25%", &self.threshold_draft)
+                .on_input(Message::ThresholdChanged)
+                .on_submit(Message::ThresholdSubmitted)
+                .size(14)
+                .width(Length::Fixed(80.0)),
+            text("of the required quantity")
+                .size(14)
+                .style(iced::theme::Text::Color(cool_gray())),
+        ]
+        .spacing(8)
+        .align_items(Alignment::Center);
+        if let Some(err) = &self.ordering_error {
+            section = section.push(
+                text(err)
+                    .size(13)
+                    .style(iced::theme::Text::Color(terracotta())),
+            );
+        }
+        section.into()
+    }
+
+    /// Shortfall against MOQ, suggested quantity in whole packs and the cost of rounding up.
+    fn order_advice(&self) -> Element<'_, Message> {
+        let advice = match self.backend_state.order_advice() {
+            Ok(advice) => advice,
+            Err(err) => {
+                return text(format!("Ordering advice unavailable: {err}"))
+                    .size(14)
+                    .style(iced::theme::Text::Color(terracotta()))
+                    .into()
+            }
+        };
+        let currency = self.backend_state.settings().currency;
+        let extra_cost = Money::sum(currency, advice.iter().map(|line| &line.surplus_cost));
+        let flagged = advice.iter().filter(|line| line.flagged).count();
+
+        let summary =
+            match extra_cost {
+                Ok(extra) if flagged > 0 => text(format!(
+                "Rounding up to MOQs and packs adds {extra}; {flagged} {} more than {} surplus.",
+                if flagged == 1 { "line has" } else { "lines have" },
+                self.backend_state.settings().ordering.surplus_threshold
+            ))
+                .style(iced::theme::Text::Color(ochre())),
+                Ok(extra) => text(format!("Rounding up to MOQs and packs adds {extra}."))
+                    .style(iced::theme::Text::Color(cool_gray())),
+                Err(err) => text(format!("Extra cost unavailable: {err}"))
+                    .style(iced::theme::Text::Color(terracotta())),
+            }
+            .size(13);
+
+        let header_cell = |label: &str, width: f32| {
+            container(
+                text(label)
+                    .size(14)
+                    .style(iced::theme::Text::Color(cool_gray())),
+            )
+            .width(Length::Fixed(width))
+        };
+        let header_row = row![
+            header_cell("Material", COL_NAME_WIDTH),
+            header_cell("Need", COL_QTY_WIDTH),
+            header_cell("Min qty", COL_MIN_WIDTH),
+            header_cell("Pack", COL_QTY_WIDTH),
+            header_cell("Order", COL_QTY_WIDTH),
+            header_cell("Surplus", COL_MIN_WIDTH),
+            header_cell("Extra cost", COL_TOTAL_WIDTH),
+        ]
+        .spacing(8);
+
+        let rows = advice
+            .iter()
+            .fold(column![header_row].spacing(6), |col, line| {
+                let color = if line.flagged {
+                    ochre()
+                } else if line.surplus > 0 {
+                    soft_ivory()
+                } else {
+                    cool_gray()
+                };
+                let cell = |value: String, width: f32| {
+                    container(text(value).size(14).style(iced::theme::Text::Color(color)))
+                        .width(Length::Fixed(width))
+                };
+                let min_qty = if line.moq_shortfall > 0 {
+                    format!("{} (+{})", line.min_quantity, line.moq_shortfall)
+                } else {
+                    line.min_quantity.to_string()
+                };
+                col.push(
+                    row![
+                        cell(line.name.clone(), COL_NAME_WIDTH),
+                        cell(line.required.to_string(), COL_QTY_WIDTH),
+                        cell(min_qty, COL_MIN_WIDTH),
+                        cell(line.pack_size.to_string(), COL_QTY_WIDTH),
+                        cell(line.suggested.to_string(), COL_QTY_WIDTH),
+                        cell(line.surplus.to_string(), COL_MIN_WIDTH),
+                        cell(line.surplus_cost.to_string(), COL_TOTAL_WIDTH),
+                    ]
+                    .spacing(8),
+                )
+            });
+
+        column![
+            text("Order quantities")
+                .size(16)
+                .style(iced::theme::Text::Color(soft_ivory())),
+            summary,
+            rows,
+        ]
+        .spacing(8)
+        .into()
+    }
+
+    /// Installation date input and the order schedule, most urgent first.
+    fn planning_view(&self) -> Element<'_, Message> {
+        let today = chrono::Local::now().date_naive();
+        let orders = match self.backend_state.order_plan(today) {
+            Ok(orders) => orders,
+            Err(err) => {
+                return text(format!("Order schedule unavailable: {err}"))
+                    .size(14)
+                    .style(iced::theme::Text::Color(terracotta()))
+                    .into()
+            }
+        };
+
+        let mut date_row = row![
+            text("Installation date:")
+                .size(14)
+                .style(iced::theme::Text::Color(soft_ivory())),
+            text_input("YYYY-MM-DD", &self.installation_draft)
+                .on_input(Message::InstallationDateChanged)
+                .on_submit(Message::InstallationDateSubmitted)
+                .size(14)
+                .width(Length::Fixed(120.0)),
+        ]
+        .spacing(8)
+        .align_items(Alignment::Center);
+        if let Some(err) = &self.planning_error {
+            date_row = date_row.push(
+                text(err)
+                    .size(13)
+                    .style(iced::theme::Text::Color(terracotta())),
+            );
+        }
+
+        let overdue = orders
+            .iter()
+            .filter(|order| order.urgency == OrderUrgency::Overdue)
+            .count();
+        let summary = if self.backend_state.planning().installation_date.is_none() {
+            text("Enter an installation date to schedule orders.")
+                .size(13)
+                .style(iced::theme::Text::Color(cool_gray()))
+        } else if overdue > 0 {
+            text(format!(
+                "{overdue} {} past {} order-by date; installation is at risk.",
+                if overdue == 1 { "line is" } else { "lines are" },
+                if overdue == 1 { "its" } else { "their" },
+            ))
+            .size(13)
+            .style(iced::theme::Text::Color(terracotta()))
+        } else {
+            text("Lead times are counted in working days before installation.")
+                .size(13)
+                .style(iced::theme::Text::Color(cool_gray()))
+        };
+
+        let header_cell = |label: &str, width: f32| {
+            container(
+                text(label)
+                    .size(14)
+                    .style(iced::theme::Text::Color(cool_gray())),
+            )
+            .width(Length::Fixed(width))
+        };
+        let header_row = row![
+            header_cell("Material", COL_NAME_WIDTH),
+            header_cell("Min qty", COL_MIN_WIDTH),
+            header_cell("Lead time", COL_LEAD_WIDTH),
+            header_cell("Order by", COL_DATE_WIDTH),
+            header_cell("Status", COL_STATUS_WIDTH),
+        ]
+        .spacing(8);
+
+        let rows = orders
+            .iter()
+            .fold(column![header_row].spacing(6), |col, order| {
+                let days_left = order.working_days_left;
+                let (color, status) = match order.urgency {
+                    OrderUrgency::Overdue => (
+                        terracotta(),
+                        format!("overdue by {} working days", -days_left),
+                    ),
+                    OrderUrgency::DueSoon if days_left == 0 => (ochre(), "order today".to_string()),
+                    OrderUrgency::DueSoon => (ochre(), format!("{days_left} working days left")),
+                    OrderUrgency::Scheduled => {
+                        (soft_ivory(), format!("{days_left} working days left"))
+                    }
+                };
+                let cell = |value: String, width: f32| {
+                    container(text(value).size(14).style(iced::theme::Text::Color(color)))
+                        .width(Length::Fixed(width))
+                };
+                col.push(
+                    row![
+                        cell(order.name.clone(), COL_NAME_WIDTH),
+                        cell(order.min_quantity.to_string(), COL_MIN_WIDTH),
+                        cell(format!("{} days", order.lead_time_days), COL_LEAD_WIDTH),
+                        cell(
+                            order.order_by.format("%a %d %b").to_string(),
+                            COL_DATE_WIDTH
+                        ),
+                        cell(status, COL_STATUS_WIDTH),
+                    ]
+                    .spacing(8),
+                )
+            });
+
+        column![
+            text("Order planning")
+                .size(18)
+                .style(iced::theme::Text::Color(slate_blue())),
+            date_row,
+            self.calendar_settings(),
+            summary,
+            rows,
+            self.timeline(orders, today),
+            self.order_advice(),
+        ]
+        .spacing(12)
+        .into()
+    }
+
+    /// Gantt chart of the order schedule with its zoom buttons.
+    fn timeline(&self, orders: Vec<PlannedOrder>, today: NaiveDate) -> Element<'_, Message> {
+        let planning = self.backend_state.planning();
+        let Some(installation) = planning.installation_date else {
+            return column![].into();
+        };
+
+        let zoom = TimeScale::ALL
+            .into_iter()
+            .fold(row![].spacing(4), |zoom, scale| {
+                let on_press =
+                    (self.timeline_scale != scale).then_some(Message::TimelineScaleSet(scale));
+                zoom.push(small_button(scale.label(), on_press))
+            });
+        let header = row![
+            text("Timeline")
+                .size(16)
+                .style(iced::theme::Text::Color(soft_ivory())),
+            zoom,
+        ]
+        .spacing(12)
+        .align_items(Alignment::Center);
+
+        let chart = Timeline::new(
+            orders,
+            installation,
+            today,
+            planning.calendar.clone(),
+            self.timeline_scale,
+        );
+        let size = chart.size();
+        let canvas = canvas(chart)
+            .width(Length::Fixed(size.width))
+            .height(Length::Fixed(size.height));
+
+        column![
+            header,
+            scrollable(canvas).direction(scrollable::Direction::Horizontal(
+                scrollable::Properties::default()
+            )),
+        ]
+        .spacing(8)
+        .into()
+    }
+
+    /// Level filter, search box, export button and the matching events.
+    /// Compare the latest revision with the current BoM, or nothing if none was issued.
+    fn reset_comparison(&mut self) {
+        self.compare_from = self
+            .backend_state
+            .revisions()
+            .last()
+            .map(|revision| revision.number);
+        self.compare_to = None;
+        self.revision_error = None;
+    }
+
+    /// Issued quote revisions and a side-by-side comparison of two of them
+    /// (or of one with the current BoM).
+    fn revisions_view(&self) -> Element<'_, Message> {
+        let revisions = self.backend_state.revisions();
+        let next = revisions.last().map_or(1, |last| last.number + 1);
+
+        let mut issue_row = row![
+            text_input(
+                "What changed, e.g. customer asked for spares",
+                &self.revision_note
+            )
+            .on_input(Message::RevisionNoteChanged)
+            .on_submit(Message::RevisionIssued)
+            .size(14)
+            .width(Length::Fixed(360.0)),
+            small_button(
+                &format!("Issue revision {next}"),
+                Some(Message::RevisionIssued)
+            ),
+        ]
+        .spacing(8)
+        .align_items(Alignment::Center);
+        if let Some(err) = &self.revision_error {
+            issue_row = issue_row.push(
+                text(err)
+                    .size(13)
+                    .style(iced::theme::Text::Color(terracotta())),
+            );
+        }
+
+        let content = column![
+            text("Quote revisions")
+                .size(18)
+                .style(iced::theme::Text::Color(slate_blue())),
+            issue_row,
+        ]
+        .spacing(12);
+        if revisions.is_empty() {
+            return content
+                .push(
+                    text("No revisions issued yet. Issue one each time the quote is sent.")
+                        .size(13)
+                        .style(iced::theme::Text::Color(cool_gray())),
+                )
+                .into();
+        }
+
+        let history = revisions
+            .iter()
+            .fold(column![].spacing(4), |col, revision| {
+                col.push(
+                    row![
+                        text(format!("Rev {}", revision.number))
+                            .size(14)
+                            .style(iced::theme::Text::Color(soft_ivory()))
+                            .width(Length::Fixed(60.0)),
+                        text(revision.issued_on.format("%d %b %Y").to_string())
+                            .size(14)
+                            .style(iced::theme::Text::Color(cool_gray()))
+                            .width(Length::Fixed(COL_DATE_WIDTH)),
+                        text(format!("{} lines", revision.bom.len()))
+                            .size(14)
+                            .style(iced::theme::Text::Color(cool_gray()))
+                            .width(Length::Fixed(COL_QTY_WIDTH + 20.0)),
+                        text(revision.grand_total.to_string())
+                            .size(14)
+                            .style(iced::theme::Text::Color(soft_ivory()))
+                            .width(Length::Fixed(COL_TOTAL_WIDTH)),
+                        text(&revision.note)
+                            .size(13)
+                            .style(iced::theme::Text::Color(cool_gray())),
+                    ]
+                    .spacing(8),
+                )
+            });
+
+        // One button per revision plus the current BoM; the selected one is disabled.
+        let picker = |label: &str, selected: Option<u32>, on_select: fn(Option<u32>) -> Message| {
+            let choices = revisions
+                .iter()
+                .map(|revision| (format!("Rev {}", revision.number), Some(revision.number)))
+                .chain([("Current".to_string(), None)]);
+            choices.fold(
+                row![text(label)
+                    .size(14)
+                    .style(iced::theme::Text::Color(cool_gray()))
+                    .width(Length::Fixed(70.0))]
+                .spacing(4)
+                .align_items(Alignment::Center),
+                |row, (choice, number)| {
+                    row.push(small_button(
+                        &choice,
+                        (selected != number).then(|| on_select(number)),
+                    ))
+                },
+            )
+        };
+
+        let comparison: Element<_> = match self
+            .backend_state
+            .compare_revisions(self.compare_from, self.compare_to)
+        {
+            Ok(diff) => self.bom_diff_view(&diff),
+            Err(err) => text(err.to_string())
+                .size(13)
+                .style(iced::theme::Text::Color(terracotta()))
+                .into(),
+        };
+
+        content
+            .push(history)
+            .push(
+                text("Compare")
+                    .size(16)
+                    .style(iced::theme::Text::Color(slate_blue())),
+            )
+            .push(picker("From", self.compare_from, Message::CompareFromSet))
+            .push(picker("To", self.compare_to, Message::CompareToSet))
+            .push(comparison)
+            .into()
+    }
+
+    /// Summary of a BoM comparison and its changed lines, before and after side by side.
+    fn bom_diff_view(&self, diff: &BomDiff) -> Element<'_, Message> {
+        let quoted_total = |number: Option<u32>| match number {
+            Some(number) => self
+                .backend_state
+                .revisions()
+                .iter()
+                .find(|revision| revision.number == number)
+                .map(|revision| revision.grand_total),
+            None => self
+                .backend_state
+                .price_breakdown()
+                .ok()
+                .map(|breakdown| breakdown.grand_total),
+        };
+        let mut summary = column![text(if diff.is_empty() {
+            "No lines added, removed, re-quantified or re-priced.".to_string()
+        } else {
+            format!(
+                "{} added, {} removed, {} changed.",
+                diff.added(),
+                diff.removed(),
+                diff.changed()
+            )
+        })
+        .size(14)
+        .style(iced::theme::Text::Color(soft_ivory()))]
+        .spacing(2);
+        summary = summary.push(
+            text(format!(
+                "BoM subtotal {} → {} ({})",
+                diff.before_total,
+                diff.after_total,
+                signed(diff.total_delta())
+            ))
+            .size(13)
+            .style(iced::theme::Text::Color(cool_gray())),
+        );
+        if let (Some(before), Some(after)) = (
+            quoted_total(self.compare_from),
+            quoted_total(self.compare_to),
+        ) {
+            summary = summary.push(
+                text(format!(
+                    "Quoted total {before} → {after} ({})",
+                    signed(after.checked_sub(before))
+                ))
+                .size(13)
+                .style(iced::theme::Text::Color(cool_gray())),
+            );
+        }
+        if diff.is_empty() {
+            return summary.into();
+        }
+
+        let header_cell = |label: &str, width: f32| {
+            container(
+                text(label)
+                    .size(14)
+                    .style(iced::theme::Text::Color(cool_gray())),
+            )
+            .width(Length::Fixed(width))
+        };
+        let header_row = row![
+            header_cell("Material", COL_NAME_WIDTH),
+            header_cell("Qty", COL_QTY_WIDTH),
+            header_cell("Unit cost", COL_UNIT_WIDTH),
+            header_cell("Line total", COL_TOTAL_WIDTH),
+            header_cell("→ Qty", COL_QTY_WIDTH),
+            header_cell("Unit cost", COL_UNIT_WIDTH),
+            header_cell("Line total", COL_TOTAL_WIDTH),
+            header_cell("Difference", COL_TOTAL_WIDTH),
+            header_cell("Change", COL_STATUS_WIDTH),
+        ]
+        .spacing(8);
+
+        let rows = diff
+            .lines
+            .iter()
+            .fold(column![header_row].spacing(6), |col, line| {
+                let (color, change) = match line {
+                    LineDiff::Added(_) => (slate_blue(), "added".to_string()),
+                    LineDiff::Removed(_) => (terracotta(), "removed".to_string()),
+                    LineDiff::Changed { .. } => {
+                        let kinds: Vec<&str> = [
+                            line.quantity_changed().then_some("quantity"),
+                            line.price_changed().then_some("price"),
+                        ]
+                        .into_iter()
+                        .flatten()
+                        .collect();
+                        (ochre(), kinds.join(", "))
+                    }
+                };
+                let cell = |value: String, width: f32| {
+                    container(text(value).size(14).style(iced::theme::Text::Color(color)))
+                        .width(Length::Fixed(width))
+                };
+                let side = |item: Option<&BomItem>| {
+                    item.map_or_else(
+                        || ["–".to_string(), "–".to_string(), "–".to_string()],
+                        |item| {
+                            [
+                                item.quantity.to_string(),
+                                item.unit_cost.to_string(),
+                                item.total_cost.to_string(),
+                            ]
+                        },
+                    )
+                };
+                let [before_qty, before_unit, before_total] = side(line.before());
+                let [after_qty, after_unit, after_total] = side(line.after());
+                col.push(
+                    row![
+                        cell(line.name().to_string(), COL_NAME_WIDTH),
+                        cell(before_qty, COL_QTY_WIDTH),
+                        cell(before_unit, COL_UNIT_WIDTH),
+                        cell(before_total, COL_TOTAL_WIDTH),
+                        cell(after_qty, COL_QTY_WIDTH),
+                        cell(after_unit, COL_UNIT_WIDTH),
+                        cell(after_total, COL_TOTAL_WIDTH),
+                        cell(signed(line.total_delta()), COL_TOTAL_WIDTH),
+                        cell(change, COL_STATUS_WIDTH),
+                    ]
+                    .spacing(8),
+                )
+            });
+
+        column![
+            summary,
+            scrollable(rows).direction(scrollable::Direction::Horizontal(
+                scrollable::Properties::default()
+            )),
+        ]
+        .spacing(12)
+        .into()
+    }
+
+    /// Purchase orders issued so far, newest first, what the next run
+    /// would produce, and the lines of the selected order.
+    fn orders_view(&self) -> Element<'_, Message> {
+        let dir = self.config.path(PathSetting::PurchaseOrders);
+        let today = chrono::Local::now().date_naive();
+
+        let draft = self.backend_state.draft_purchase_orders(today);
+        let can_issue = draft.as_ref().is_ok_and(|draft| !draft.orders.is_empty());
+        let mut issue_row = row![small_button(
+            "Generate purchase orders",
+            can_issue.then_some(Message::PurchaseOrdersIssued)
+        )]
+        .spacing(8)
+        .align_items(Alignment::Center);
+        if !self.orders_status.is_empty() {
+            issue_row = issue_row.push(
+                text(&self.orders_status)
+                    .size(13)
+                    .style(iced::theme::Text::Color(cool_gray())),
+            );
+        }
+
+        // What the button would issue, and which lines it would leave out.
+        let next = match draft {
+            Ok(draft) => {
+                let mut summary = format!(
+                    "Next run: {} {} to {}, quantities rounded up to MOQs and packs.",
+                    draft.orders.len(),
+                    if draft.orders.len() == 1 {
+                        "order"
+                    } else {
+                        "orders"
+                    },
+                    dir.display()
+                );
+                let mut color = cool_gray();
+                for (reason, count) in draft.left_out_counts() {
+                    summary.push_str(&format!(
+                        " {count} {} {reason}{}.",
+                        if count == 1 { "line" } else { "lines" },
+                        if reason == LeftOutReason::AlreadyOrdered {
+                            ""
+                        } else {
+                            " and will be left out"
+                        }
+                    ));
+                    if reason != LeftOutReason::AlreadyOrdered {
+                        color = ochre();
+                    }
+                }
+                text(summary).style(iced::theme::Text::Color(color))
+            }
+            Err(err) => text(format!("Purchase orders unavailable: {err}"))
+                .style(iced::theme::Text::Color(terracotta())),
+        }
+        .size(13);
+
+        let content = column![
+            text("Purchase orders")
+                .size(18)
+                .style(iced::theme::Text::Color(slate_blue())),
+            issue_row,
+            next,
+        ]
+        .spacing(12);
+        let orders = self.backend_state.purchase_orders();
+        if orders.is_empty() {
+            return content
+                .push(
+                    text("No purchase orders issued yet. Each run writes one PDF and CSV per supplier.")
+                        .size(13)
+                        .style(iced::theme::Text::Color(cool_gray())),
+                )
+                .into();
+        }
+
+        // Newest first; the selected order's button is disabled.
+        let issued = orders
+            .iter()
+            .rev()
+            .fold(column![].spacing(4), |col, order| {
+                let cell = |value: String, width: f32, color: Color| {
+                    text(value)
+                        .size(14)
+                        .style(iced::theme::Text::Color(color))
+                        .width(Length::Fixed(width))
+                };
+                let selected = self.selected_order == Some(order.number);
+                col.push(
+                    row![
+                        small_button(
+                            &order.reference(),
+                            (!selected)
+                                .then_some(Message::PurchaseOrderSelected(Some(order.number)))
+                        )
+                        .width(Length::Fixed(130.0)),
+                        cell(
+                            order.issued_on.format("%d %b %Y").to_string(),
+                            COL_DATE_WIDTH,
+                            cool_gray()
+                        ),
+                        cell(order.supplier.clone(), COL_SUPPLIER_WIDTH, soft_ivory()),
+                        cell(
+                            format!("{} lines", order.lines.len()),
+                            COL_QTY_WIDTH + 20.0,
+                            cool_gray()
+                        ),
+                        cell(order.total.to_string(), COL_TOTAL_WIDTH, soft_ivory()),
+                        cell(
+                            order
+                                .delivery()
+                                .map(|date| format!("by {}", date.format("%d %b %Y")))
+                                .unwrap_or_default(),
+                            COL_DATE_WIDTH + 20.0,
+                            cool_gray()
+                        ),
+                    ]
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+                )
+            });
+
+        let content = content.push(issued);
+        match orders
+            .iter()
+            .find(|order| Some(order.number) == self.selected_order)
+        {
+            Some(order) => content.push(self.purchase_order_lines(order)).into(),
+            None => content.into(),
+        }
+    }
+
+    /// The supplier, files and lines of one issued purchase order.
+    fn purchase_order_lines<'a>(&self, order: &'a PurchaseOrder) -> Element<'a, Message> {
+        let dir = self.config.path(PathSetting::PurchaseOrders);
+        let mut contact = vec![order.supplier.clone()];
+        if let Some(details) = &order.details {
+            contact.extend(
+                [&details.contact, &details.email, &details.phone]
+                    .into_iter()
+                    .flatten()
+                    .cloned(),
+            );
+        }
+
+        let header_cell = |label: &str, width: f32| {
+            container(
+                text(label)
+                    .size(14)
+                    .style(iced::theme::Text::Color(cool_gray())),
+            )
+            .width(Length::Fixed(width))
+        };
+        let header_row = row![
+            header_cell("Material", COL_NAME_WIDTH),
+            header_cell("Part no.", COL_PART_WIDTH),
+            header_cell("Need", COL_QTY_WIDTH),
+            header_cell("Order", COL_QTY_WIDTH),
+            header_cell("Unit cost", COL_UNIT_WIDTH),
+            header_cell("Line total", COL_TOTAL_WIDTH),
+            header_cell("Delivery", COL_DATE_WIDTH),
+        ]
+        .spacing(8);
+        let rows = order
+            .lines
+            .iter()
+            .fold(column![header_row].spacing(6), |col, line| {
+                // Lines raised to an MOQ or whole pack stand out.
+                let color = if line.quantity > line.required {
+                    ochre()
+                } else {
+                    soft_ivory()
+                };
+                let cell = |value: String, width: f32| {
+                    container(text(value).size(14).style(iced::theme::Text::Color(color)))
+                        .width(Length::Fixed(width))
+                };
+                col.push(
+                    row![
+                        cell(line.name.clone(), COL_NAME_WIDTH),
+                        cell(line.part_number.clone().unwrap_or_default(), COL_PART_WIDTH),
+                        cell(line.required.to_string(), COL_QTY_WIDTH),
+                        cell(line.quantity.to_string(), COL_QTY_WIDTH),
+                        cell(line.unit_cost.to_string(), COL_UNIT_WIDTH),
+                        cell(line.total.to_string(), COL_TOTAL_WIDTH),
+                        cell(line.delivery.format("%d %b %Y").to_string(), COL_DATE_WIDTH),
+                    ]
+                    .spacing(8),
+                )
+            });
+
+        column![
+            text(format!("{} · {}", order.reference(), contact.join(" · ")))
+                .size(16)
+                .style(iced::theme::Text::Color(slate_blue())),
+            text(format!(
+                "Written to {} and {}; ordered on {}.",
+                order.file_path(dir, "pdf").display(),
+                order.file_path(dir, "csv").display(),
+                order.order_on.format("%d %b %Y")
+            ))
+            .size(13)
+            .style(iced::theme::Text::Color(cool_gray())),
+            rows,
+        ]
+        .spacing(8)
+        .into()
+    }
+
+    fn log_view(&self) -> Element<'_, Message> {
+        let log = self.backend_state.log();
+        let filter = &self.log_filter;
+
+        let levels = LogLevel::ALL
+            .into_iter()
+            .fold(row![].spacing(4), |levels, level| {
+                let on_press =
+                    (filter.min_level != level).then_some(Message::LogLevelSelected(level));
+                levels.push(small_button(&format!("{level}+"), on_press))
+            });
+
+        let controls = row![
+            text("Level:")
+                .size(14)
+                .style(iced::theme::Text::Color(cool_gray())),
+            levels,
+            text_input("Search target, message or fields", &filter.search)
+                .on_input(Message::LogSearchChanged)
+                .size(14)
+                .width(Length::Fixed(260.0)),
+            small_button("Export", Some(Message::LogsExported)),
+        ]
+        .spacing(8)
+        .align_items(Alignment::Center);
+
+        let matching = log.query(filter).count();
+        let mut summary = format!("Showing {matching} of {} events", log.len());
+        if log.dropped() > 0 {
+            summary.push_str(&format!(
+                " ({} older events dropped; keeping the last {})",
+                log.dropped(),
+                log.capacity()
+            ));
+        }
+
+        let events = log
+            .query(filter)
+            .rev()
+            .fold(column![].spacing(2), |col, event| {
+                let fields = event
+                    .fields
+                    .iter()
+                    .map(|(key, value)| format!("{key}={value}"))
+                    .collect::<Vec<_>>()
+                    .join("  ");
+                col.push(
+                    row![
+                        container(
+                            text(event.timestamp.format("%H:%M:%S%.3f").to_string())
+                                .size(12)
+                                .style(iced::theme::Text::Color(cool_gray()))
+                        )
+                        .width(Length::Fixed(90.0)),
+                        container(
+                            text(event.level.to_string())
+                                .size(12)
+                                .style(iced::theme::Text::Color(log_level_color(event.level)))
+                        )
+                        .width(Length::Fixed(50.0)),
+                        container(
+                            text(&event.target)
+                                .size(12)
+                                .style(iced::theme::Text::Color(cool_gray()))
+                        )
+                        .width(Length::Fixed(80.0)),
+                        column![
+                            text(&event.message)
+                                .size(13)
+                                .style(iced::theme::Text::Color(soft_ivory())),
+                            text(fields)
+                                .size(12)
+                                .style(iced::theme::Text::Color(cool_gray())),
+                        ]
+                        .width(Length::Fill),
+                    ]
+                    .spacing(8),
+                )
+            });
+
+        column![
+            text("Activity log")
+                .size(18)
+                .style(iced::theme::Text::Color(slate_blue())),
+            controls,
+            text(summary)
+                .size(13)
+                .style(iced::theme::Text::Color(cool_gray())),
+            text(&self.log_status)
+                .size(12)
+                .style(iced::theme::Text::Color(cool_gray())),
+            events,
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    /// Resolved file locations, where each came from, and any problems found at startup.
+    fn path_settings(&self) -> Element<'_, Message> {
+        let config = &self.config;
+        let source = match &config.config_file {
+            Some(file) => format!("Paths (config file: {})", file.display()),
+            None => "Paths (no config file)".to_string(),
+        };
+
+        let paths = PathSetting::ALL.into_iter().fold(
+            column![text(source)
+                .size(16)
+                .style(iced::theme::Text::Color(soft_ivory()))]
+            .spacing(4),
+            |col, setting| {
+                let resolved = config.resolved(setting);
+                col.push(
+                    row![
+                        container(
+                            text(setting.label())
+                                .size(14)
+                                .style(iced::theme::Text::Color(soft_ivory()))
+                        )
+                        .width(Length::Fixed(140.0)),
+                        text(format!("{} ({})", resolved.path.display(), resolved.source))
+                            .size(13)
+                            .style(iced::theme::Text::Color(cool_gray())),
+                    ]
+                    .spacing(8),
+                )
+            },
+        );
+
+        config
+            .problems
+            .iter()
+            .fold(paths, |col, problem| {
+                col.push(
+                    text(problem.to_string())
+                        .size(13)
+                        .style(iced::theme::Text::Color(severity_color(problem.severity))),
+                )
+            })
+            .into()
+    }
+
+    /// Editable list of pricing adjustments, applied top to bottom.
+    fn pricing_settings(&self) -> Element<'_, Message> {
+        let adjustments = &self.backend_state.settings().pricing.adjustments;
+        let count = adjustments.len();
+        let categories = self.backend_state.bom_categories();
+
+        let rows = adjustments.iter().enumerate().fold(
+            column![text("Pricing adjustments (applied in order)")
+                .size(16)
+                .style(iced::theme::Text::Color(soft_ivory())),]
+            .spacing(6),
+            |col, (index, adjustment)| {
+                let draft = self
+                    .adjustment_drafts
+                    .get(index)
+                    .map(String::as_str)
+                    .unwrap_or_default();
+                let scope: Element<'_, Message> = match &adjustment.kind {
+                    AdjustmentKind::Tax { category, .. } => {
+                        let selected = category
+                            .clone()
+                            .map_or(TaxScope::AllLines, TaxScope::Category);
+                        let mut scopes = vec![TaxScope::AllLines];
+                        scopes.extend(categories.iter().cloned().map(TaxScope::Category));
+                        if !scopes.contains(&selected) {
+                            scopes.push(selected.clone());
+                        }
+                        pick_list(scopes, Some(selected), move |scope| {
+                            Message::TaxScopeSelected(index, scope)
+                        })
+                        .text_size(14)
+                        .width(Length::Fixed(160.0))
+                        .into()
+                    }
+                    _ => Space::with_width(Length::Fixed(160.0)).into(),
+                };
+                col.push(
+                    row![
+                        container(
+                            text(&adjustment.label)
+                                .size(14)
+                                .style(iced::theme::Text::Color(soft_ivory()))
+                        )
+                        .width(Length::Fixed(180.0)),
+                        container(
+                            text(adjustment.kind.kind_label())
+                                .size(14)
+                                .style(iced::theme::Text::Color(cool_gray()))
+                        )
+                        .width(Length::Fixed(110.0)),
+                        text_input("value", draft)
+                            .on_input(move |value| Message::AdjustmentValueChanged(index, value))
+                            .on_submit(Message::AdjustmentValueSubmitted(index))
+                            .size(14)
+                            .width(Length::Fixed(110.0)),
+                        scope,
+                        small_button(
+                            "Up",
+                            (index > 0).then_some(Message::AdjustmentMoved(index, true))
+                        ),
+                        small_button(
+                            "Down",
+                            (index + 1 < count).then_some(Message::AdjustmentMoved(index, false)),
+                        ),
+                        small_button("Remove", Some(Message::AdjustmentRemoved(index))),
+                    ]
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+                )
+            },
+        );
+
+        let add_row = row![
+            text("Add:")
+                .size(14)
+                .style(iced::theme::Text::Color(cool_gray())),
+            small_button(
+                "Markup",
+                Some(Message::AdjustmentAdded(NewAdjustment::Markup))
+            ),
+            small_button(
+                "Fixed fee",
+                Some(Message::AdjustmentAdded(NewAdjustment::FixedFee))
+            ),
+            small_button(
+                "Discount",
+                Some(Message::AdjustmentAdded(NewAdjustment::Discount))
+            ),
+            small_button("Tax", Some(Message::AdjustmentAdded(NewAdjustment::Tax))),
+        ]
+        .spacing(8)
+        .align_items(Alignment::Center);
+
+        let mut section = column![
+            rows,
+            add_row,
+            text("Press Enter in a value field to apply it.")
+                .size(12)
+                .style(iced::theme::Text::Color(cool_gray())),
+        ]
+        .spacing(8);
+        if let Some(err) = &self.pricing_error {
+            section = section.push(
+                text(err)
+                    .size(13)
+                    .style(iced::theme::Text::Color(terracotta())),
+            );
+        }
+        section.into()
+    }
+}
+
+/// Start a background page render, if the preview asked for one.
+fn render_command(job: Option<RenderJob>) -> Command<Message> {
+    match job {
+        Some(job) => Command::perform(preview::render(job), Message::PageRendered),
+        None => Command::none(),
+    }
+}
+
+/// Compact text button; `None` renders it disabled.
+fn small_button<'a>(label: &str, on_press: Option<Message>) -> button::Button<'a, Message> {
+    let color = if on_press.is_some() {
+        soft_ivory()
+    } else {
+        charcoal()
+    };
+    button(text(label).size(13).style(iced::theme::Text::Color(color)))
+        .on_press_maybe(on_press)
+        .style(iced::theme::Button::Secondary)
+}
+
+/// The project library: the configured SQLite database when built with the
+/// `sqlite` feature and it opens, otherwise one kept in memory.
+fn open_repository(config: &AppConfig, state: &mut AppState) -> Box<dyn ProjectRepository> {
+    #[cfg(feature = "sqlite")]
+    {
+        let path = config.path(PathSetting::Database);
+        match tabs_backend::SqliteRepository::open(path) {
+            Ok(repository) => return Box::new(repository),
+            Err(err) => state.log.record(
+                LogEvent::new(
+                    LogLevel::Warn,
+                    "storage",
+                    format!("project library kept in memory: {err}"),
+                )
+                .with_field("path", path.display()),
+            ),
+        }
+    }
+    #[cfg(not(feature = "sqlite"))]
+    {
+        let _ = config;
+        state.log.record(LogEvent::new(
+            LogLevel::Info,
+            "storage",
+            "built without SQLite; the project library lasts for this session",
+        ));
+    }
+    Box::new(MemoryRepository::new())
+}
+
+/// Ctrl+S saves the project; Ctrl+Z undoes; Ctrl+Shift+Z and Ctrl+Y redo
+/// (Cmd on macOS). Keys typed into a focused text field go to the field instead.
+fn shortcut(key: Key, modifiers: Modifiers) -> Option<Message> {
+    if !modifiers.command() {
+        return None;
+    }
+    match key.as_ref() {
+        Key::Character(c) if c.eq_ignore_ascii_case("s") => Some(Message::ProjectSaved),
+        Key::Character(c) if c.eq_ignore_ascii_case("z") && modifiers.shift() => {
+            Some(Message::Redo)
+        }
+        Key::Character(c) if c.eq_ignore_ascii_case("z") => Some(Message::Undo),
+        Key::Character(c) if c.eq_ignore_ascii_case("y") => Some(Message::Redo),
+        _ => None,
+    }
+}
+
+fn table_resize(event: iced::Event, status: iced::event::Status) -> Option<Message> {
+    table::resize_event(event, status).map(Message::BomTable)
+}
+
+fn tree_resize(event: iced::Event, status: iced::event::Status) -> Option<Message> {
+    table::resize_event(event, status).map(Message::TreeTable)
+}
+
+fn supplier_resize(event: iced::Event, status: iced::event::Status) -> Option<Message> {
+    table::resize_event(event, status).map(Message::SupplierTable)
+}
+
+/// An amount with an explicit sign, for differences.
+fn signed(amount: Result<Money, MoneyError>) -> String {
+    match amount {
+        Ok(amount) if amount.is_negative() || amount.is_zero() => amount.to_string(),
+        Ok(amount) => format!("+{amount}"),
+        Err(err) => err.to_string(),
+    }
+}
+
+fn severity_color(severity: Severity) -> Color {
+    match severity {
+        Severity::Info => cool_gray(),
+        Severity::Warning => ochre(),
+        Severity::Error => terracotta(),
+    }
+}
+
+fn log_level_color(level: LogLevel) -> Color {
+    match level {
+        LogLevel::Debug | LogLevel::Info => cool_gray(),
+        LogLevel::Warn => ochre(),
+        LogLevel::Error => terracotta(),
+    }
+}
+
 fn tab_button<'a>(label: &str, tab: TabKind, active_tab: TabKind) -> button::Button<'a, Message> {
     let is_active = tab == active_tab;
     let color = if is_active { soft_ivory() } else { cool_gray() };
@@ -0,0 +1,398 @@
+use iced::widget::image;
+use pdfium::{PdfiumDocument, PdfiumRenderConfig};
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tabs_backend::{LogEvent, LogLevel};
+
+/// Pixel height pages are rasterised at; high enough to stay sharp at 200% zoom.
+const RENDER_HEIGHT: i32 = 1700;
+
+/// Logical width of an A4 page at 100% zoom.
+pub const PAGE_WIDTH_AT_100: f32 = 595.0;
+
+/// Zoom steps offered by the zoom in / out buttons, in percent.
+const ZOOM_STEPS: [u16; 6] = [50, 75, 100, 125, 150, 200];
+
+/// How the current page is scaled inside the preview area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Zoom {
+    FitWidth,
+    Percent(u16),
+}
+
+impl Zoom {
+    pub fn label(&self) -> String {
+        match self {
+            Zoom::FitWidth => "Fit width".to_string(),
+            Zoom::Percent(percent) => format!("{percent}%"),
+        }
+    }
+
+    pub fn zoom_in(self) -> Zoom {
+        match self {
+            Zoom::FitWidth => Zoom::Percent(125),
+            Zoom::Percent(current) => ZOOM_STEPS
+                .iter()
+                .find(|&&step| step > current)
+                .map_or(self, |&step| Zoom::Percent(step)),
+        }
+    }
+
+    pub fn zoom_out(self) -> Zoom {
+        match self {
+            Zoom::FitWidth => Zoom::Percent(100),
+            Zoom::Percent(current) => ZOOM_STEPS
+                .iter()
+                .rev()
+                .find(|&&step| step < current)
+                .map_or(self, |&step| Zoom::Percent(step)),
+        }
+    }
+}
+
+/// Identifies one rasterised page: which PDF (by content), which page, at what resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RenderKey {
+    pub content_hash: u64,
+    pub page: usize,
+    pub height: i32,
+}
+
+impl RenderKey {
+    fn file_name(&self) -> String {
+        format!(
+            "{:016x}-p{}-h{}.png",
+            self.content_hash, self.page, self.height
+        )
+    }
+}
+
+/// A page render to run off the UI thread.
+#[derive(Debug, Clone)]
+pub struct RenderJob {
+    key: RenderKey,
+    pdf: Arc<[u8]>,
+    cache_dir: PathBuf,
+    /// Also count the pages, for the first render of a new document.
+    count_pages: bool,
+}
+
+/// What a [`RenderJob`] produced, delivered back to [`QuotePreview::finish`].
+#[derive(Debug, Clone)]
+pub struct RenderOutcome {
+    key: RenderKey,
+    page_count: Option<usize>,
+    result: Result<image::Handle, String>,
+    /// What happened along the way, for the in-app log.
+    events: Vec<LogEvent>,
+}
+
+impl RenderOutcome {
+    fn failed(key: RenderKey, err: String) -> Self {
+        let event = LogEvent::new(LogLevel::Error, "preview", format!("render failed: {err}"))
+            .with_field("page", key.page + 1);
+        Self {
+            key,
+            page_count: None,
+            result: Err(err),
+            events: vec![event],
+        }
+    }
+
+    /// Hand over the log events gathered while rendering.
+    pub fn take_log_events(&mut self) -> Vec<LogEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// Run `job` on the blocking thread pool so rasterising never stalls the UI.
+pub async fn render(job: RenderJob) -> RenderOutcome {
+    let key = job.key;
+    tokio::task::spawn_blocking(move || job.run())
+        .await
+        .unwrap_or_else(|err| RenderOutcome::failed(key, format!("render task failed: {err}")))
+}
+
+/// Page navigation, zoom and cached page renders for one quote PDF.
+///
+/// Renders are keyed by a hash of the PDF bytes plus page and resolution, so
+/// an unchanged quote is served from the cache (in memory, then on disk under
+/// `cache_dir`) and a changed quote can never show a stale page.
+pub struct QuotePreview {
+    cache_dir: PathBuf,
+    pdf: Option<Arc<[u8]>>,
+    content_hash: u64,
+    /// `None` until the first render of the current document reports it.
+    page_count: Option<usize>,
+    current_page: usize,
+    zoom: Zoom,
+    pages: HashMap<RenderKey, image::Handle>,
+    pending: HashSet<RenderKey>,
+    error: Option<String>,
+}
+
+impl QuotePreview {
+    pub fn new(cache_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            cache_dir: cache_dir.into(),
+            pdf: None,
+            content_hash: 0,
+            page_count: None,
+            current_page: 0,
+            zoom: Zoom::FitWidth,
+            pages: HashMap::new(),
+            pending: HashSet::new(),
+            error: None,
+        }
+    }
+
+    /// Show a newly generated PDF, keeping the current page and zoom so
+    /// regeneration does not lose the user's place.
+    ///
+    /// Returns the render to start, if the current page is not cached yet.
+    pub fn load(&mut self, pdf: Vec<u8>) -> Option<RenderJob> {
+        let content_hash = content_hash(&pdf);
+        if self.pdf.is_some() && content_hash == self.content_hash {
+            return self.request();
+        }
+        self.content_hash = content_hash;
+        self.pdf = Some(pdf.into());
+        self.page_count = None;
+        self.error = None;
+        // Anything keyed by another hash belongs to an older quote.
+        self.pages.retain(|key, _| key.content_hash == content_hash);
+        self.pending.clear();
+        self.request()
+    }
+
+    /// The page count, once known.
+    pub fn page_count(&self) -> usize {
+        self.page_count.unwrap_or_default()
+    }
+
+    pub fn current_page(&self) -> usize {
+        self.current_page
+    }
+
+    pub fn zoom(&self) -> Zoom {
+        self.zoom
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Whether the current page is still being rendered.
+    pub fn is_loading(&self) -> bool {
+        self.pending.contains(&self.current_key())
+    }
+
+    pub fn current_image(&self) -> Option<&image::Handle> {
+        self.pages.get(&self.current_key())
+    }
+
+    pub fn go_to(&mut self, page: usize) -> Option<RenderJob> {
+        if page < self.page_count() {
+            self.current_page = page;
+            return self.request();
+        }
+        None
+    }
+
+    pub fn next(&mut self) -> Option<RenderJob> {
+        self.go_to(self.current_page + 1)
+    }
+
+    pub fn previous(&mut self) -> Option<RenderJob> {
+        self.current_page
+            .checked_sub(1)
+            .and_then(|page| self.go_to(page))
+    }
+
+    pub fn set_zoom(&mut self, zoom: Zoom) {
+        self.zoom = zoom;
+    }
+
+    /// Store a finished render. Results for an older document are dropped.
+    ///
+    /// Returns a follow-up render when the page the user is now on still
+    /// needs one (e.g. they navigated while this render was running).
+    pub fn finish(&mut self, outcome: RenderOutcome) -> Option<RenderJob> {
+        if outcome.key.content_hash != self.content_hash {
+            return None;
+        }
+        self.pending.remove(&outcome.key);
+        if let Some(count) = outcome.page_count {
+            self.page_count = Some(count);
+            // A request for a page past the end was answered with the last page.
+            self.pending.retain(|key| key.page < count);
+            self.current_page = self.current_page.min(count.saturating_sub(1));
+        }
+        match outcome.result {
+            Ok(handle) => {
+                self.pages.insert(outcome.key, handle);
+            }
+            Err(err) => {
+                self.error = Some(err);
+                return None;
+            }
+        }
+        self.request()
+    }
+
+    fn current_key(&self) -> RenderKey {
+        RenderKey {
+            content_hash: self.content_hash,
+            page: self.current_page,
+            height: RENDER_HEIGHT,
+        }
+    }
+
+    /// A render for the current page, unless it is cached or already running.
+    fn request(&mut self) -> Option<RenderJob> {
+        let pdf = self.pdf.clone()?;
+        let key = self.current_key();
+        if self.pages.contains_key(&key) || !self.pending.insert(key) {
+            return None;
+        }
+        Some(RenderJob {
+            key,
+            pdf,
+            cache_dir: self.cache_dir.clone(),
+            count_pages: self.page_count.is_none(),
+        })
+    }
+}
+
+impl RenderJob {
+    fn run(self) -> RenderOutcome {
+        let mut key = self.key;
+        let page_count = if self.count_pages {
+            match self.page_count() {
+                Ok(count) => {
+                    // The previous document may have had more pages.
+                    key.page = key.page.min(count.saturating_sub(1));
+                    prune_cache(&self.cache_dir, key.content_hash);
+                    Some(count)
+                }
+                Err(err) => return RenderOutcome::failed(key, err),
+            }
+        } else {
+            None
+        };
+        let mut events = Vec::new();
+        match self.load_or_render(key, &mut events) {
+            Ok(handle) => RenderOutcome {
+                key,
+                page_count,
+                result: Ok(handle),
+                events,
+            },
+            Err(err) => {
+                let mut outcome = RenderOutcome::failed(key, err);
+                events.append(&mut outcome.events);
+                outcome.events = events;
+                outcome
+            }
+        }
+    }
+
+    fn document(&self) -> Result<PdfiumDocument, String> {
+        PdfiumDocument::new_from_reader(Cursor::new(Arc::clone(&self.pdf)), None)
+            .map_err(|err| format!("{err:?}"))
+    }
+
+    fn page_count(&self) -> Result<usize, String> {
+        let doc = self.document()?;
+        Ok(usize::try_from(doc.page_count()).unwrap_or_default())
+    }
+
+    /// Read the page from the disk cache, rasterising and caching it on a miss.
+    fn load_or_render(
+        &self,
+        key: RenderKey,
+        events: &mut Vec<LogEvent>,
+    ) -> Result<image::Handle, String> {
+        let cached = self.cache_dir.join(key.file_name());
+        if let Ok(png) = ::image::open(&cached) {
+            events.push(
+                LogEvent::new(LogLevel::Debug, "preview", "page loaded from cache")
+                    .with_field("page", key.page + 1)
+                    .with_field("file", cached.display()),
+            );
+            let png = png.to_rgba8();
+            return Ok(image::Handle::from_pixels(
+                png.width(),
+                png.height(),
+                png.into_raw(),
+            ));
+        }
+
+        let (width, height, rgba) = self.rasterise(key)?;
+        let png = ::image::RgbaImage::from_raw(width, height, rgba)
+            .ok_or_else(|| "rendered bitmap has an unexpected size".to_string())?;
+        // A cache write failure only costs a re-render next time.
+        let saved = std::fs::create_dir_all(&self.cache_dir)
+            .map_err(|err| err.to_string())
+            .and_then(|()| png.save(&cached).map_err(|err| err.to_string()));
+        let event = match saved {
+            Ok(()) => LogEvent::new(LogLevel::Debug, "preview", "page rendered"),
+            Err(err) => LogEvent::new(
+                LogLevel::Warn,
+                "preview",
+                format!("page rendered but not cached: {err}"),
+            ),
+        };
+        events.push(
+            event
+                .with_field("page", key.page + 1)
+                .with_field("height", key.height),
+        );
+        Ok(image::Handle::from_pixels(width, height, png.into_raw()))
+    }
+
+    fn rasterise(&self, key: RenderKey) -> Result<(u32, u32, Vec<u8>), String> {
+        let doc = self.document()?;
+        // Render at a higher resolution so text remains readable
+        // when scaled into the preview area.
+        let config = PdfiumRenderConfig::new().with_height(key.height);
+        let page = doc
+            .page(i32::try_from(key.page).unwrap_or(i32::MAX))
+            .map_err(|err| format!("{err:?}"))?;
+        let bitmap = page.render(&config).map_err(|err| format!("{err:?}"))?;
+        let (width, height) = (bitmap.width(), bitmap.height());
+        let rgba = bitmap.as_rgba_bytes().map_err(|err| format!("{err:?}"))?;
+        Ok((
+            u32::try_from(width).unwrap_or_default(),
+            u32::try_from(height).unwrap_or_default(),
+            rgba,
+        ))
+    }
+}
+
+/// 64-bit FNV-1a over the PDF bytes.
+///
+/// Unlike `DefaultHasher` this is stable across builds, which matters
+/// because the hash names files in the on-disk cache.
+fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Delete cached pages belonging to any other version of the quote.
+fn prune_cache(cache_dir: &Path, keep_hash: u64) {
+    let keep = format!("{keep_hash:016x}-");
+    let Ok(entries) = std::fs::read_dir(cache_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.ends_with(".png") && !name.starts_with(&keep) {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use iced::event::{self, Event};
+use iced::mouse;
+use iced::widget::scrollable::{AbsoluteOffset, RelativeOffset, Viewport};
+use iced::widget::{
+    column, container, keyed_column, mouse_area, row, scrollable, vertical_rule, Space,
+};
+use iced::{Alignment, Command, Element, Length};
+use std::ops::Range;
+
+/// Height of every body row; rows are laid out at fixed offsets so only
+/// the visible ones need to exist.
+pub const ROW_HEIGHT: f32 = 34.0;
+/// Space between cells, which in the header holds the resize handle.
+pub const COLUMN_GAP: f32 = 8.0;
+const HEADER_HEIGHT: f32 = 24.0;
+const MIN_COLUMN_WIDTH: f32 = 40.0;
+/// Rows built beyond each edge of the viewport, so fast scrolling does not show gaps.
+const OVERSCAN: usize = 4;
+/// Assumed until the body reports its size on the first scroll.
+const INITIAL_VIEWPORT_HEIGHT: f32 = 400.0;
+
+/// Scrolling and column resizing reported by a [`VirtualTable`].
+#[derive(Debug, Clone, Copy)]
+pub enum TableEvent {
+    Scrolled(Viewport),
+    ResizeStarted(usize),
+    /// Pointer position during a resize, in window coordinates.
+    ResizeMoved(f32),
+    ResizeEnded,
+}
+
+/// Column widths and scroll position of a table that only lays out the rows
+/// in view. The header stays put while the body scrolls beneath it.
+#[derive(Debug)]
+pub struct VirtualTable {
+    id: scrollable::Id,
+    widths: Vec<f32>,
+    offset: f32,
+    viewport_height: f32,
+    resizing: Option<Resize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Resize {
+    column: usize,
+    start_width: f32,
+    /// Pointer position when the drag began; set by the first move.
+    anchor: Option<f32>,
+}
+
+impl VirtualTable {
+    pub fn new(widths: &[f32]) -> Self {
+        Self {
+            id: scrollable::Id::unique(),
+            widths: widths.to_vec(),
+            offset: 0.0,
+            viewport_height: INITIAL_VIEWPORT_HEIGHT,
+            resizing: None,
+        }
+    }
+
+    pub fn width(&self, column: usize) -> f32 {
+        self.widths[column]
+    }
+
+    pub fn is_resizing(&self) -> bool {
+        self.resizing.is_some()
+    }
+
+    pub fn update(&mut self, event: TableEvent) {
+        match event {
+            TableEvent::Scrolled(viewport) => {
+                self.offset = viewport.absolute_offset().y;
+                self.viewport_height = viewport.bounds().height;
+            }
+            TableEvent::ResizeStarted(column) => {
+                self.resizing = Some(Resize {
+                    column,
+                    start_width: self.widths[column],
+                    anchor: None,
+                });
+            }
+            TableEvent::ResizeMoved(x) => {
+                if let Some(resize) = &mut self.resizing {
+                    let anchor = *resize.anchor.get_or_insert(x);
+                    self.widths[resize.column] =
+                        (resize.start_width + x - anchor).max(MIN_COLUMN_WIDTH);
+                }
+            }
+            TableEvent::ResizeEnded => self.resizing = None,
+        }
+    }
+
+    /// Back to the first row, e.g. after the rows were re-sorted or filtered.
+    pub fn scroll_to_top<Message: 'static>(&mut self) -> Command<Message> {
+        self.offset = 0.0;
+        scrollable::snap_to(self.id.clone(), RelativeOffset::START)
+    }
+
+    /// Put the body back where it was, for when the table is shown again.
+    pub fn restore_scroll<Message: 'static>(&self) -> Command<Message> {
+        scrollable::scroll_to(
+            self.id.clone(),
+            AbsoluteOffset {
+                x: 0.0,
+                y: self.offset,
+            },
+        )
+    }
+
+    /// Rows to lay out out of `rows`.
+    pub fn visible_rows(&self, rows: usize) -> Range<usize> {
+        let shown = (self.viewport_height / ROW_HEIGHT).ceil() as usize + 1;
+        // The body may have shrunk since the last scroll; the scrollable then
+        // clamps its offset to the last page.
+        let first = ((self.offset / ROW_HEIGHT) as usize).min(rows.saturating_sub(shown));
+        first.saturating_sub(OVERSCAN)..(first + shown + OVERSCAN).min(rows)
+    }
+
+    /// The table with one body row per key in `keys`. `header` builds the
+    /// heading of a column at its current width, and `row` the body row at
+    /// a position, which must size its cells with [`VirtualTable::width`]
+    /// and space them by [`COLUMN_GAP`].
+    ///
+    /// Rows keep their widget state (such as a focused text input) by key,
+    /// not by position, so it stays with its row as the visible range
+    /// shifts or the rows are reordered.
+    pub fn view<'a, Key: Copy + PartialEq + 'static, Message: Clone + 'a>(
+        &self,
+        keys: &[Key],
+        header: impl Fn(usize, f32) -> Element<'a, Message>,
+        row: impl Fn(usize) -> Element<'a, Message>,
+        on_event: fn(TableEvent) -> Message,
+    ) -> Element<'a, Message> {
+        let header = self.widths.iter().enumerate().fold(
+            row![].align_items(Alignment::Center),
+            |cells, (column, &width)| {
+                let handle = mouse_area(
+                    container(vertical_rule(1))
+                        .width(Length::Fixed(COLUMN_GAP))
+                        .height(Length::Fixed(HEADER_HEIGHT))
+                        .center_x(),
+                )
+                .on_press(on_event(TableEvent::ResizeStarted(column)))
+                .interaction(mouse::Interaction::ResizingHorizontally);
+                cells
+                    .push(container(header(column, width)).width(Length::Fixed(width)))
+                    .push(handle)
+            },
+        );
+
+        let rows = keys.len();
+        let visible = self.visible_rows(rows);
+        let spacer = |rows: usize| Space::with_height(Length::Fixed(rows as f32 * ROW_HEIGHT));
+        let body = visible
+            .clone()
+            .fold(
+                keyed_column([(Slot::Before, spacer(visible.start).into())]),
+                |body, index| {
+                    body.push(
+                        Slot::Row(keys[index]),
+                        container(row(index))
+                            .height(Length::Fixed(ROW_HEIGHT))
+                            .center_y(),
+                    )
+                },
+            )
+            .push(Slot::After, spacer(rows - visible.end));
+        let body = scrollable(body)
+            .id(self.id.clone())
+            .height(Length::Fill)
+            .on_scroll(move |viewport| on_event(TableEvent::Scrolled(viewport)));
+
+        scrollable(column![header, body].spacing(4))
+            .direction(scrollable::Direction::Horizontal(
+                scrollable::Properties::default(),
+            ))
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+/// Key of a body child: the spacers either side of the visible rows, or a row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot<Key> {
+    Before,
+    Row(Key),
+    After,
+}
+
+/// Pointer events that drive a column resize. Listened to only while one is
+/// in progress, so the drag keeps working when the pointer leaves the header.
+pub fn resize_event(event: Event, _status: event::Status) -> Option<TableEvent> {
+    match event {
+        Event::Mouse(mouse::Event::CursorMoved { position }) => {
+            Some(TableEvent::ResizeMoved(position.x))
+        }
+        Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+            Some(TableEvent::ResizeEnded)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,299 @@
+use crate::{charcoal, cool_gray, ochre, slate_blue, soft_ivory, terracotta};
+use chrono::{Datelike, Days, NaiveDate};
+use iced::alignment;
+use iced::mouse;
+use iced::widget::canvas::{self, Frame, Geometry, Path, Stroke, Text};
+use iced::{Color, Point, Rectangle, Renderer, Size, Theme};
+use tabs_backend::{OrderUrgency, PlannedOrder, WorkingCalendar};
+
+const LABEL_WIDTH: f32 = 180.0;
+const HEADER_HEIGHT: f32 = 28.0;
+const ROW_HEIGHT: f32 = 24.0;
+const BAR_HEIGHT: f32 = 14.0;
+/// Days shown either side of the planned range.
+const MARGIN_DAYS: u64 = 3;
+
+/// Horizontal zoom of the timeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeScale {
+    Day,
+    Week,
+    Month,
+}
+
+impl TimeScale {
+    pub const ALL: [TimeScale; 3] = [TimeScale::Day, TimeScale::Week, TimeScale::Month];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TimeScale::Day => "Day",
+            TimeScale::Week => "Week",
+            TimeScale::Month => "Month",
+        }
+    }
+
+    fn pixels_per_day(&self) -> f32 {
+        match self {
+            TimeScale::Day => 28.0,
+            TimeScale::Week => 8.0,
+            TimeScale::Month => 2.5,
+        }
+    }
+
+    /// Whether a grid line (and label) starts at `date`.
+    fn is_tick(&self, date: NaiveDate) -> bool {
+        match self {
+            TimeScale::Day => true,
+            TimeScale::Week => date.weekday() == chrono::Weekday::Mon,
+            TimeScale::Month => date.day() == 1,
+        }
+    }
+
+    fn tick_label(&self, date: NaiveDate) -> String {
+        match self {
+            TimeScale::Day => date.format("%d").to_string(),
+            TimeScale::Week => date.format("%d %b").to_string(),
+            TimeScale::Month => date.format("%b %Y").to_string(),
+        }
+    }
+}
+
+/// Gantt-style chart: one bar per BoM line from order date to expected
+/// delivery, with today and the installation date marked.
+pub struct Timeline {
+    orders: Vec<PlannedOrder>,
+    installation: NaiveDate,
+    today: NaiveDate,
+    calendar: WorkingCalendar,
+    scale: TimeScale,
+    start: NaiveDate,
+    end: NaiveDate,
+}
+
+impl Timeline {
+    pub fn new(
+        orders: Vec<PlannedOrder>,
+        installation: NaiveDate,
+        today: NaiveDate,
+        calendar: WorkingCalendar,
+        scale: TimeScale,
+    ) -> Self {
+        let earliest = orders
+            .iter()
+            .map(|order| order.order_on)
+            .chain([today, installation])
+            .min()
+            .unwrap_or(today);
+        let latest = orders
+            .iter()
+            .map(|order| order.expected_delivery)
+            .chain([today, installation])
+            .max()
+            .unwrap_or(installation);
+        Self {
+            orders,
+            installation,
+            today,
+            calendar,
+            scale,
+            start: earliest - Days::new(MARGIN_DAYS),
+            end: latest + Days::new(MARGIN_DAYS),
+        }
+    }
+
+    /// Size the canvas needs to show every day and row.
+    pub fn size(&self) -> Size {
+        let days = (self.end - self.start).num_days() + 1;
+        Size::new(
+            LABEL_WIDTH + days as f32 * self.scale.pixels_per_day(),
+            HEADER_HEIGHT + self.orders.len() as f32 * ROW_HEIGHT + 4.0,
+        )
+    }
+
+    fn x(&self, date: NaiveDate) -> f32 {
+        LABEL_WIDTH + (date - self.start).num_days() as f32 * self.scale.pixels_per_day()
+    }
+
+    fn row_y(&self, row: usize) -> f32 {
+        HEADER_HEIGHT + row as f32 * ROW_HEIGHT
+    }
+
+    /// Bar rectangle for `order`; covers whole days, end date included.
+    fn bar(&self, row: usize, order: &PlannedOrder) -> Rectangle {
+        let x = self.x(order.order_on);
+        let width = (self.x(order.expected_delivery) + self.scale.pixels_per_day() - x).max(2.0);
+        Rectangle {
+            x,
+            y: self.row_y(row) + (ROW_HEIGHT - BAR_HEIGHT) / 2.0,
+            width,
+            height: BAR_HEIGHT,
+        }
+    }
+
+    fn draw_grid(&self, frame: &mut Frame) {
+        let height = frame.height();
+        let day_width = self.scale.pixels_per_day();
+        for date in self.start.iter_days().take_while(|date| *date <= self.end) {
+            let x = self.x(date);
+            if !self.calendar.is_working_day(date) && self.scale != TimeScale::Month {
+                frame.fill_rectangle(
+                    Point::new(x, HEADER_HEIGHT),
+                    Size::new(day_width, height - HEADER_HEIGHT),
+                    Color {
+                        a: 0.25,
+                        ..charcoal()
+                    },
+                );
+            }
+            if self.scale.is_tick(date) {
+                frame.stroke(
+                    &Path::line(Point::new(x, HEADER_HEIGHT - 6.0), Point::new(x, height)),
+                    Stroke::default()
+                        .with_color(Color {
+                            a: 0.4,
+                            ..cool_gray()
+                        })
+                        .with_width(1.0),
+                );
+                frame.fill_text(Text {
+                    content: self.scale.tick_label(date),
+                    position: Point::new(x + 2.0, 4.0),
+                    color: cool_gray(),
+                    size: 11.0.into(),
+                    ..Text::default()
+                });
+            }
+        }
+    }
+
+    fn draw_marker(&self, frame: &mut Frame, date: NaiveDate, color: Color, label: &str) {
+        let x = self.x(date) + self.scale.pixels_per_day() / 2.0;
+        frame.stroke(
+            &Path::line(
+                Point::new(x, HEADER_HEIGHT - 10.0),
+                Point::new(x, frame.height()),
+            ),
+            Stroke::default().with_color(color).with_width(2.0),
+        );
+        frame.fill_text(Text {
+            content: label.to_string(),
+            position: Point::new(x + 3.0, HEADER_HEIGHT - 12.0),
+            color,
+            size: 11.0.into(),
+            ..Text::default()
+        });
+    }
+
+    fn draw_rows(&self, frame: &mut Frame, hovered: Option<usize>) {
+        for (row, order) in self.orders.iter().enumerate() {
+            let y = self.row_y(row);
+            if hovered == Some(row) {
+                frame.fill_rectangle(
+                    Point::new(0.0, y),
+                    Size::new(frame.width(), ROW_HEIGHT),
+                    Color {
+                        a: 0.3,
+                        ..slate_blue()
+                    },
+                );
+            }
+            frame.fill_text(Text {
+                content: order.name.clone(),
+                position: Point::new(4.0, y + ROW_HEIGHT / 2.0),
+                color: soft_ivory(),
+                size: 12.0.into(),
+                vertical_alignment: alignment::Vertical::Center,
+                ..Text::default()
+            });
+            let bar = self.bar(row, order);
+            frame.fill_rectangle(bar.position(), bar.size(), urgency_color(order.urgency));
+        }
+    }
+
+    fn draw_tooltip(&self, frame: &mut Frame, cursor: Point, order: &PlannedOrder) {
+        let lines = [
+            order.name.clone(),
+            format!(
+                "Supplier: {}",
+                order.supplier.as_deref().unwrap_or("not specified")
+            ),
+            format!("Cost: {}", order.total_cost),
+            format!(
+                "Order {} → delivery {}",
+                order.order_on.format("%d %b"),
+                order.expected_delivery.format("%d %b")
+            ),
+            format!(
+                "{} working days lead time, {}",
+                order.lead_time_days, order.urgency
+            ),
+        ];
+        let size = Size::new(260.0, 8.0 + lines.len() as f32 * 16.0);
+        // Keep the box inside the canvas.
+        let x = (cursor.x + 12.0).min(frame.width() - size.width).max(0.0);
+        let y = (cursor.y + 12.0).min(frame.height() - size.height).max(0.0);
+
+        frame.fill_rectangle(Point::new(x, y), size, charcoal());
+        frame.stroke(
+            &Path::rectangle(Point::new(x, y), size),
+            Stroke::default().with_color(slate_blue()).with_width(1.0),
+        );
+        for (index, line) in lines.into_iter().enumerate() {
+            frame.fill_text(Text {
+                content: line,
+                position: Point::new(x + 6.0, y + 4.0 + index as f32 * 16.0),
+                color: if index == 0 {
+                    soft_ivory()
+                } else {
+                    cool_gray()
+                },
+                size: 12.0.into(),
+                ..Text::default()
+            });
+        }
+    }
+
+    /// Row under the cursor, if it is over a row's label or bar.
+    fn hovered_row(&self, cursor: Point) -> Option<usize> {
+        if cursor.y < HEADER_HEIGHT {
+            return None;
+        }
+        let row = ((cursor.y - HEADER_HEIGHT) / ROW_HEIGHT) as usize;
+        let order = self.orders.get(row)?;
+        (cursor.x < LABEL_WIDTH || self.bar(row, order).contains(cursor)).then_some(row)
+    }
+}
+
+impl<Message> canvas::Program<Message> for Timeline {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let cursor = cursor.position_in(bounds);
+        let hovered = cursor.and_then(|cursor| self.hovered_row(cursor));
+
+        self.draw_grid(&mut frame);
+        self.draw_rows(&mut frame, hovered);
+        self.draw_marker(&mut frame, self.today, cool_gray(), "Today");
+        self.draw_marker(&mut frame, self.installation, ochre(), "Installation");
+        if let (Some(cursor), Some(row)) = (cursor, hovered) {
+            self.draw_tooltip(&mut frame, cursor, &self.orders[row]);
+        }
+        vec![frame.into_geometry()]
+    }
+}
+
+fn urgency_color(urgency: OrderUrgency) -> Color {
+    match urgency {
+        OrderUrgency::Overdue => terracotta(),
+        OrderUrgency::DueSoon => ochre(),
+        OrderUrgency::Scheduled => slate_blue(),
+    }
+}